@@ -0,0 +1,263 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use std::cmp::Ordering;
+
+use crate::random::Random;
+use crate::simulation::{grid_cells, CellImage, Input, Options, Population, Simulation};
+use crate::Cell;
+
+const EMPTY: u8 = 0;
+const RED: u8 = 1;
+const BLUE: u8 = 2;
+
+const RED_COLOR: [u8; 4] = [230, 60, 60, 255];
+const BLUE_COLOR: [u8; 4] = [60, 120, 240, 255];
+/// The line between the two halves, shown while the players seed them
+const MIDLINE_COLOR: [u8; 4] = [60, 60, 60, 255];
+
+/// A two-player game of Life on a bounded grid. Red seeds the left half and blue the right,
+/// then the battle runs for a fixed number of generations under a two-state B/S rule: live
+/// cells keep their color and newborn cells take the color most of their parents have. When
+/// it's over, the color with more surviving cells wins.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Battle {
+    width: i32,
+    height: i32,
+    birth: Vec<usize>,
+    survival: Vec<usize>,
+    /// Generations the battle lasts
+    generations: usize,
+    /// Colors row by row
+    cells: Vec<u8>,
+    generation: usize,
+}
+
+impl Battle {
+    /// An `options.width` x `options.height` battlefield under `options.rules`, with each half
+    /// seeded at random with `--param soup` of its cells.
+    pub fn new(options: &Options) -> Result<Self, String> {
+        let rules = &options.rules;
+        if !rules.is_life_like() {
+            return Err("Battles need a two-state B/S rule such as B3/S23".to_string());
+        }
+        let generations: usize = options.param("generations", 500)?;
+        if generations == 0 {
+            return Err("A battle must last at least 1 generation".to_string());
+        }
+        let soup: f64 = options.param("soup", 0.0)?;
+        if !(0.0..=1.0).contains(&soup) {
+            return Err("The soup density must be between 0 and 1".to_string());
+        }
+        let (width, height) = (options.width.max(2), options.height.max(1));
+        let mut battle = Self {
+            width,
+            height,
+            birth: rules.birth.clone(),
+            survival: rules.survival.clone(),
+            generations,
+            cells: vec![EMPTY; grid_cells(width, height)?],
+            generation: 1,
+        };
+        let mut random = Random::new(options.seed);
+        for y in 0..height {
+            for x in 0..width {
+                if random.chance(soup) {
+                    battle.set(Cell(x, y), battle.side(x));
+                }
+            }
+        }
+        Ok(battle)
+    }
+
+    fn index(&self, cell: Cell) -> Option<usize> {
+        ((0..self.width).contains(&cell.0) && (0..self.height).contains(&cell.1))
+            .then(|| (cell.1 * self.width + cell.0) as usize)
+    }
+
+    fn set(&mut self, cell: Cell, color: u8) -> bool {
+        match self.index(cell) {
+            Some(i) if self.cells[i] != color => {
+                self.cells[i] = color;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The color of the player whose half column `x` is in.
+    fn side(&self, x: i32) -> u8 {
+        if x < self.width / 2 {
+            RED
+        } else {
+            BLUE
+        }
+    }
+
+    fn count(&self, color: u8) -> usize {
+        self.cells.iter().filter(|&&cell| cell == color).count()
+    }
+
+    fn seeding(&self) -> bool {
+        self.generation == 1
+    }
+
+    fn finished(&self) -> bool {
+        self.generation > self.generations
+    }
+}
+
+impl Simulation for Battle {
+    fn name(&self) -> &'static str {
+        "battle"
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Runs one generation of the battle. It only starts once both players have seeded
+    /// cells, and stops after its last generation.
+    fn step(&mut self) {
+        if self.finished() || self.seeding() && (self.count(RED) == 0 || self.count(BLUE) == 0) {
+            return;
+        }
+        let mut next = vec![EMPTY; self.cells.len()];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let mut parents = [0usize; 3];
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        if (dx, dy) == (0, 0) {
+                            continue;
+                        }
+                        if let Some(j) = self.index(Cell(x + dx, y + dy)) {
+                            parents[self.cells[j] as usize] += 1;
+                        }
+                    }
+                }
+                let i = (y * self.width + x) as usize;
+                let neighbors = parents[RED as usize] + parents[BLUE as usize];
+                next[i] = match self.cells[i] {
+                    EMPTY if self.birth.contains(&neighbors) => {
+                        match parents[RED as usize].cmp(&parents[BLUE as usize]) {
+                            Ordering::Greater => RED,
+                            Ordering::Less => BLUE,
+                            // Even splits alternate like a checkerboard, so neither side is
+                            // favored
+                            Ordering::Equal if (x + y) % 2 == 0 => RED,
+                            Ordering::Equal => BLUE,
+                        }
+                    }
+                    color if color != EMPTY && self.survival.contains(&neighbors) => color,
+                    _ => EMPTY,
+                };
+            }
+        }
+        self.cells = next;
+        self.generation += 1;
+    }
+
+    fn bounds(&self) -> Option<(Cell, Cell)> {
+        Some((Cell(0, 0), Cell(self.width - 1, self.height - 1)))
+    }
+
+    fn draw(&self, image: &mut CellImage) {
+        for (i, &cell) in self.cells.iter().enumerate() {
+            let i = i as i32;
+            let (x, y) = (i % self.width, i / self.width);
+            let color = match cell {
+                RED => RED_COLOR,
+                BLUE => BLUE_COLOR,
+                _ if self.seeding() && x == self.width / 2 => MIDLINE_COLOR,
+                _ => continue,
+            };
+            image.set(Cell(x, y), color);
+        }
+    }
+
+    fn occupied(&self, cell: Cell) -> bool {
+        self.index(cell).is_some_and(|i| self.cells[i] != EMPTY)
+    }
+
+    /// Painting seeds cells in the color of the half they're in, and erasing clears them;
+    /// once the battle has started the field can't be touched.
+    fn handle_input(&mut self, input: Input) -> bool {
+        match input {
+            _ if !self.seeding() => false,
+            Input::Paint { cell, erase: false } => self.set(cell, self.side(cell.0)),
+            Input::Paint { cell, erase: true } => self.set(cell, EMPTY),
+            Input::Activate(_) => false,
+        }
+    }
+
+    fn status(&self) -> Option<String> {
+        let (red, blue) = (self.count(RED), self.count(BLUE));
+        if self.seeding() {
+            Some(format!(
+                "Seeding: red {} (left half), blue {} (right half)",
+                red, blue
+            ))
+        } else if let Some(outcome) = self.outcome() {
+            Some(outcome)
+        } else {
+            Some(format!(
+                "Red: {}, blue: {}, {} generations left",
+                red,
+                blue,
+                self.generations + 1 - self.generation
+            ))
+        }
+    }
+
+    fn outcome(&self) -> Option<String> {
+        if !self.finished() {
+            return None;
+        }
+        let (red, blue) = (self.count(RED), self.count(BLUE));
+        Some(match red.cmp(&blue) {
+            Ordering::Greater => format!("Red wins, {} cells to {}", red, blue),
+            Ordering::Less => format!("Blue wins, {} cells to {}", blue, red),
+            Ordering::Equal => format!("A draw, {} cells each", red),
+        })
+    }
+
+    fn populations(&self) -> Vec<Population> {
+        vec![
+            Population {
+                name: "Red",
+                count: self.count(RED),
+                color: RED_COLOR,
+            },
+            Population {
+                name: "Blue",
+                count: self.count(BLUE),
+                color: BLUE_COLOR,
+            },
+        ]
+    }
+
+    /// An empty battlefield, ready to be seeded again.
+    fn blank(&self) -> Box<dyn Simulation> {
+        let mut blank = self.clone();
+        blank.cells.iter_mut().for_each(|cell| *cell = EMPTY);
+        blank.generation = 1;
+        Box::new(blank)
+    }
+
+    fn save(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    fn restore(&mut self, state: Value) -> Result<(), String> {
+        let restored: Self = serde_json::from_value(state)
+            .map_err(|err| format!("Invalid battle state: {}", err))?;
+        if restored.cells.len() != grid_cells(restored.width, restored.height)?
+            || restored.cells.iter().any(|&cell| cell > BLUE)
+        {
+            return Err("Battle state doesn't match its size".to_string());
+        }
+        *self = restored;
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::Cell;
+
+/// Width and height of a chunk, matching the bits in a `u64` row.
+const CHUNK: i32 = 64;
+
+/// The first and last chunk along each axis, covering every `i32` coordinate; births never
+/// spill past them.
+const CHUNKS: std::ops::RangeInclusive<i32> = i32::MIN / CHUNK..=i32::MAX / CHUNK;
+
+/// Population below which the per-cell engine is fast enough that converting isn't worth it.
+pub const MIN_POPULATION: usize = 1000;
+
+/// Live cells per unit of bounding-box area above which chunks are dense enough to pay off.
+pub const MIN_DENSITY: f64 = 0.02;
+
+/// 64x64 cells: bit `x` of row `y` is the cell at `(x, y)` within the chunk.
+type Chunk = [u64; CHUNK as usize];
+
+const EMPTY: Chunk = [0; CHUNK as usize];
+
+/// Runs one generation of a two-state Moore-neighborhood rule, counting neighbors 64 cells at a
+/// time. `birth` and `survival` have bit `n` set when `n` live neighbors cause a birth or let a
+/// cell survive.
+pub fn step(cells: &HashSet<Cell>, birth: u16, survival: u16) -> HashSet<Cell> {
+    let mut chunks: HashMap<(i32, i32), Chunk> = HashMap::new();
+    for cell in cells {
+        let key = (cell.0.div_euclid(CHUNK), cell.1.div_euclid(CHUNK));
+        let chunk = chunks.entry(key).or_insert(EMPTY);
+        chunk[cell.1.rem_euclid(CHUNK) as usize] |= 1 << cell.0.rem_euclid(CHUNK);
+    }
+
+    // Births can spill over into the empty chunks around occupied ones
+    let mut targets: HashSet<(i32, i32)> = HashSet::new();
+    for &(cx, cy) in chunks.keys() {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if CHUNKS.contains(&(cx + dx)) && CHUNKS.contains(&(cy + dy)) {
+                    targets.insert((cx + dx, cy + dy));
+                }
+            }
+        }
+    }
+
+    let mut next = HashSet::with_capacity(cells.len());
+    for (cx, cy) in targets {
+        let chunk = step_chunk(&chunks, cx, cy, birth, survival);
+        for (y, &row) in chunk.iter().enumerate() {
+            let mut bits = row;
+            while bits != 0 {
+                let x = bits.trailing_zeros() as i32;
+                next.insert(Cell(cx * CHUNK + x, cy * CHUNK + y as i32));
+                bits &= bits - 1;
+            }
+        }
+    }
+    next
+}
+
+fn step_chunk(
+    chunks: &HashMap<(i32, i32), Chunk>,
+    cx: i32,
+    cy: i32,
+    birth: u16,
+    survival: u16,
+) -> Chunk {
+    let get = |dx: i32, dy: i32| chunks.get(&(cx + dx, cy + dy)).unwrap_or(&EMPTY);
+    let (north_west, north, north_east) = (get(-1, -1), get(0, -1), get(1, -1));
+    let (west, center, east) = (get(-1, 0), get(0, 0), get(1, 0));
+    let (south_west, south, south_east) = (get(-1, 1), get(0, 1), get(1, 1));
+    let last = CHUNK as usize - 1;
+    // Row `y` of this chunk with the rows of its west and east neighbors, reaching one row
+    // into the chunks above and below
+    let row = |y: i32| match y {
+        -1 => (north_west[last], north[last], north_east[last]),
+        CHUNK => (south_west[0], south[0], south_east[0]),
+        _ => (west[y as usize], center[y as usize], east[y as usize]),
+    };
+
+    let mut next = EMPTY;
+    for y in 0..CHUNK {
+        // Bit-sliced counters: bit `x` of `planes[i]` is bit `i` of cell x's neighbor count
+        let mut planes = [0u64; 4];
+        for dy in -1..=1 {
+            let (w, c, e) = row(y + dy);
+            // Shifted so bit `x` holds the cell to the west (x - 1) and east (x + 1) of x
+            add(&mut planes, (c << 1) | (w >> (CHUNK - 1)));
+            add(&mut planes, (c >> 1) | (e << (CHUNK - 1)));
+            if dy != 0 {
+                add(&mut planes, c);
+            }
+        }
+        let alive = center[y as usize];
+        next[y as usize] =
+            (!alive & counts_in(&planes, birth)) | (alive & counts_in(&planes, survival));
+    }
+    next
+}
+
+/// Adds one neighbor bit to every cell's counter at once.
+fn add(planes: &mut [u64; 4], mut carry: u64) {
+    for plane in planes {
+        let sum = *plane ^ carry;
+        carry &= *plane;
+        *plane = sum;
+    }
+}
+
+/// Cells whose count is one of those set in `counts`.
+fn counts_in(planes: &[u64; 4], counts: u16) -> u64 {
+    let mut mask = 0;
+    for count in (0..=8).filter(|count| counts >> count & 1 == 1) {
+        let mut equal = !0u64;
+        for (bit, plane) in planes.iter().enumerate() {
+            equal &= if count >> bit & 1 == 1 {
+                *plane
+            } else {
+                !*plane
+            };
+        }
+        mask |= equal;
+    }
+    mask
+}
+
+/// Packs neighbor counts into the bitmask `step` takes.
+pub fn count_mask(counts: &[usize]) -> u16 {
+    counts
+        .iter()
+        .filter(|&&count| count <= 8)
+        .fold(0, |mask, &count| mask | 1 << count)
+}
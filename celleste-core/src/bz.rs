@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::random::Random;
+use crate::simulation::{grid_cells, CellImage, Input, Options, Simulation};
+use crate::Cell;
+
+/// Alan Turner's model of the Belousov–Zhabotinsky reaction on a toroidal dish: three
+/// chemicals, each of which eats the next (A eats B, B eats C and C eats A) at rates `alpha`,
+/// `beta` and `gamma` after diffusing over the 3x3 block around every cell. Random mixes
+/// organise themselves into rotating spirals.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Bz {
+    width: i32,
+    height: i32,
+    alpha: f32,
+    beta: f32,
+    gamma: f32,
+    /// Concentrations from 0 to 1 of the three chemicals, row by row
+    a: Vec<f32>,
+    b: Vec<f32>,
+    c: Vec<f32>,
+    random: Random,
+    generation: usize,
+}
+
+impl Bz {
+    /// A `options.width` x `options.height` dish filled with a random mix of the chemicals.
+    pub fn new(options: &Options) -> Result<Self, String> {
+        let rate = |name: &str| {
+            let rate: f32 = options.param(name, 1.0)?;
+            if rate.is_finite() && rate >= 0.0 {
+                Ok(rate)
+            } else {
+                Err(format!("The {} rate must be a number of at least 0", name))
+            }
+        };
+        let (width, height) = (options.width.max(1), options.height.max(1));
+        let cells = grid_cells(width, height)?;
+        let mut bz = Self {
+            width,
+            height,
+            alpha: rate("alpha")?,
+            beta: rate("beta")?,
+            gamma: rate("gamma")?,
+            a: vec![0.0; cells],
+            b: vec![0.0; cells],
+            c: vec![0.0; cells],
+            random: Random::new(options.seed),
+            generation: 1,
+        };
+        for i in 0..cells {
+            bz.a[i] = bz.random.unit() as f32;
+            bz.b[i] = bz.random.unit() as f32;
+            bz.c[i] = bz.random.unit() as f32;
+        }
+        Ok(bz)
+    }
+
+    fn index(&self, cell: Cell) -> Option<usize> {
+        ((0..self.width).contains(&cell.0) && (0..self.height).contains(&cell.1))
+            .then(|| (cell.1 * self.width + cell.0) as usize)
+    }
+
+    fn set(&mut self, cell: Cell, mix: (f32, f32, f32)) -> bool {
+        match self.index(cell) {
+            Some(i) if (self.a[i], self.b[i], self.c[i]) != mix => {
+                (self.a[i], self.b[i], self.c[i]) = mix;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Mean concentrations of the three chemicals over the 3x3 block around `(x, y)`,
+    /// wrapping around the edges.
+    fn diffused(&self, x: i32, y: i32) -> (f32, f32, f32) {
+        let mut sums = (0.0, 0.0, 0.0);
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let nx = (x + dx).rem_euclid(self.width);
+                let ny = (y + dy).rem_euclid(self.height);
+                let i = (ny * self.width + nx) as usize;
+                sums.0 += self.a[i];
+                sums.1 += self.b[i];
+                sums.2 += self.c[i];
+            }
+        }
+        (sums.0 / 9.0, sums.1 / 9.0, sums.2 / 9.0)
+    }
+}
+
+impl Simulation for Bz {
+    fn name(&self) -> &'static str {
+        "bz"
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn step(&mut self) {
+        let cells = self.a.len();
+        let (mut a, mut b, mut c) = (vec![0.0; cells], vec![0.0; cells], vec![0.0; cells]);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let i = (y * self.width + x) as usize;
+                let (ma, mb, mc) = self.diffused(x, y);
+                a[i] = (ma + ma * (self.alpha * mb - self.gamma * mc)).clamp(0.0, 1.0);
+                b[i] = (mb + mb * (self.beta * mc - self.alpha * ma)).clamp(0.0, 1.0);
+                c[i] = (mc + mc * (self.gamma * ma - self.beta * mb)).clamp(0.0, 1.0);
+            }
+        }
+        (self.a, self.b, self.c) = (a, b, c);
+        self.generation += 1;
+    }
+
+    fn bounds(&self) -> Option<(Cell, Cell)> {
+        Some((Cell(0, 0), Cell(self.width - 1, self.height - 1)))
+    }
+
+    /// Each chemical drives one color channel: A red, B green and C blue.
+    fn draw(&self, image: &mut CellImage) {
+        let channel = |value: f32| (value * 255.0) as u8;
+        for i in 0..self.a.len() {
+            let color = [
+                channel(self.a[i]),
+                channel(self.b[i]),
+                channel(self.c[i]),
+                255,
+            ];
+            let i = i as i32;
+            image.set(Cell(i % self.width, i / self.width), color);
+        }
+    }
+
+    fn occupied(&self, cell: Cell) -> bool {
+        self.index(cell)
+            .is_some_and(|i| self.a[i] + self.b[i] + self.c[i] > 0.0)
+    }
+
+    /// Painting drips pure A onto the dish and erasing wipes it clean; activating drops a
+    /// fresh random mix.
+    fn handle_input(&mut self, input: Input) -> bool {
+        match input {
+            Input::Paint { cell, erase: true } => self.set(cell, (0.0, 0.0, 0.0)),
+            Input::Paint { cell, erase: false } => self.set(cell, (1.0, 0.0, 0.0)),
+            Input::Activate(cell) => {
+                let mix = (
+                    self.random.unit() as f32,
+                    self.random.unit() as f32,
+                    self.random.unit() as f32,
+                );
+                self.set(cell, mix)
+            }
+        }
+    }
+
+    fn status(&self) -> Option<String> {
+        let mean = |chemical: &[f32]| chemical.iter().sum::<f32>() / chemical.len() as f32;
+        Some(format!(
+            "A: {:.2}, B: {:.2}, C: {:.2}",
+            mean(&self.a),
+            mean(&self.b),
+            mean(&self.c)
+        ))
+    }
+
+    fn blank(&self) -> Box<dyn Simulation> {
+        let mut blank = self.clone();
+        for chemical in [&mut blank.a, &mut blank.b, &mut blank.c] {
+            chemical.iter_mut().for_each(|value| *value = 0.0);
+        }
+        blank.generation = 1;
+        Box::new(blank)
+    }
+
+    fn save(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    fn restore(&mut self, state: Value) -> Result<(), String> {
+        let restored: Self =
+            serde_json::from_value(state).map_err(|err| format!("Invalid BZ state: {}", err))?;
+        let cells = grid_cells(restored.width, restored.height)?;
+        let chemicals = [&restored.a, &restored.b, &restored.c];
+        if chemicals.iter().any(|chemical| chemical.len() != cells) {
+            return Err("BZ state doesn't match its size".to_string());
+        }
+        let rates = [restored.alpha, restored.beta, restored.gamma];
+        if chemicals
+            .iter()
+            .flat_map(|chemical| chemical.iter())
+            .any(|value| !(0.0..=1.0).contains(value))
+            || rates.iter().any(|rate| !(rate.is_finite() && *rate >= 0.0))
+        {
+            return Err("BZ state has concentrations or rates out of range".to_string());
+        }
+        *self = restored;
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+use crate::{bitboard, patterns, Cell};
+
+/// Common objects of Life ash: name, RLE and period. Every phase of each is recognized, and
+/// objects are told apart by how they evolve under Life.
+const KNOWN_OBJECTS: &[(&str, &str, usize)] = &[
+    ("block", "2o$2o!", 1),
+    ("beehive", "b2o$o2bo$b2o!", 1),
+    ("loaf", "b2o$o2bo$bobo$2bo!", 1),
+    ("boat", "2o$obo$bo!", 1),
+    ("ship", "2o$obo$b2o!", 1),
+    ("tub", "bo$obo$bo!", 1),
+    ("pond", "b2o$o2bo$o2bo$b2o!", 1),
+    ("long boat", "2o$obo$bobo$2bo!", 1),
+    ("barge", "bo$obo$bobo$2bo!", 1),
+    ("mango", "b2o$o2bo$bo2bo$2b2o!", 1),
+    ("blinker", "3o!", 2),
+    ("toad", "b3o$3o!", 2),
+    ("beacon", "2o$2o$2b2o$2b2o!", 2),
+    (
+        "pulsar",
+        "2b3o3b3o2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2$2b3o3b3o$o4bobo4bo$o4bobo4bo$\
+         o4bobo4bo2$2b3o3b3o!",
+        3,
+    ),
+    ("glider", "bo$2bo$3o!", 4),
+    ("lightweight spaceship", "bo2bo$o$o3bo$4o!", 4),
+];
+
+/// Generations looked ahead when grouping cells into objects: cells connected in any of
+/// them belong together, so oscillators whose phases fall apart into pieces, like the toad's,
+/// stay whole while neighbors that never touch stay separate.
+const LOOKAHEAD: usize = 4;
+
+/// An object's cells moved to the origin in a fixed orientation, the same for every phase
+/// and position it could be found in.
+pub type Shape = Vec<(i32, i32)>;
+type Orientation = fn(i32, i32) -> (i32, i32);
+
+/// One separate object of the pattern.
+pub struct Object {
+    pub name: String,
+    /// Whether it's one of the objects `name` was looked up for
+    pub known: bool,
+    pub shape: Shape,
+    pub cells: Vec<Cell>,
+}
+
+/// Splits the pattern into separate objects and names the ones it recognizes.
+pub fn objects(cells: &HashSet<Cell>) -> Vec<Object> {
+    let names = known_shapes();
+    clusters(cells)
+        .into_iter()
+        .map(|cells| {
+            let shape = canonical(&cells);
+            let (name, known) = match names.get(&shape) {
+                Some(name) => (name.to_string(), true),
+                None => (format!("unidentified {}-cell object", cells.len()), false),
+            };
+            Object {
+                name,
+                known,
+                shape,
+                cells,
+            }
+        })
+        .collect()
+}
+
+/// Splits the pattern into separate objects and counts each kind, most common first.
+pub fn census(cells: &HashSet<Cell>) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for object in objects(cells) {
+        *counts.entry(object.name).or_insert(0) += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// One line per kind of object, e.g. `12 block`.
+pub fn lines(counts: &[(String, usize)]) -> Vec<String> {
+    counts
+        .iter()
+        .map(|(name, count)| format!("{} {}", count, name))
+        .collect()
+}
+
+/// Every phase of every known object, keyed by canonical shape. Built on first use.
+fn known_shapes() -> &'static HashMap<Shape, &'static str> {
+    static SHAPES: OnceLock<HashMap<Shape, &'static str>> = OnceLock::new();
+    SHAPES.get_or_init(|| {
+        let (birth, survival) = life_masks();
+        let mut shapes = HashMap::new();
+        for &(name, rle, period) in KNOWN_OBJECTS {
+            let Ok(pattern) = patterns::parse_rle(rle) else {
+                continue;
+            };
+            let mut phase = pattern.cells;
+            for _ in 0..period {
+                let cells: Vec<Cell> = phase.iter().copied().collect();
+                shapes.insert(canonical(&cells), name);
+                phase = bitboard::step(&phase, birth, survival);
+            }
+        }
+        shapes
+    })
+}
+
+fn clusters(cells: &HashSet<Cell>) -> Vec<Vec<Cell>> {
+    let (birth, survival) = life_masks();
+    let mut footprint = cells.clone();
+    let mut phase = cells.clone();
+    for _ in 0..LOOKAHEAD {
+        phase = bitboard::step(&phase, birth, survival);
+        footprint.extend(&phase);
+    }
+    let mut clusters = Vec::new();
+    while let Some(&start) = footprint.iter().next() {
+        footprint.remove(&start);
+        let mut component = vec![start];
+        let mut i = 0;
+        while i < component.len() {
+            let cell = component[i];
+            i += 1;
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let Some(neighbor) = cell.offset(dx, dy) else {
+                        continue;
+                    };
+                    if footprint.remove(&neighbor) {
+                        component.push(neighbor);
+                    }
+                }
+            }
+        }
+        // Only cells alive now make up the object; the rest were just where it was headed
+        let cluster: Vec<Cell> = component
+            .into_iter()
+            .filter(|cell| cells.contains(cell))
+            .collect();
+        if !cluster.is_empty() {
+            clusters.push(cluster);
+        }
+    }
+    clusters
+}
+
+fn life_masks() -> (u16, u16) {
+    (bitboard::count_mask(&[3]), bitboard::count_mask(&[2, 3]))
+}
+
+/// The same shape for every rotation, reflection and position of an object: the smallest of
+/// its eight orientations, moved to the origin and sorted.
+fn canonical(cells: &[Cell]) -> Shape {
+    // Moving it to the origin first keeps flipping it from overflowing at the edge of the
+    // universe; an object is never anywhere near as wide as that
+    let min_x = cells.iter().map(|cell| cell.0).min().unwrap_or(0);
+    let min_y = cells.iter().map(|cell| cell.1).min().unwrap_or(0);
+    let cells: Vec<Cell> = cells
+        .iter()
+        .map(|cell| {
+            Cell(
+                (cell.0 as i64 - min_x as i64) as i32,
+                (cell.1 as i64 - min_y as i64) as i32,
+            )
+        })
+        .collect();
+    let orientations: [Orientation; 8] = [
+        |x, y| (x, y),
+        |x, y| (-x, y),
+        |x, y| (x, -y),
+        |x, y| (-x, -y),
+        |x, y| (y, x),
+        |x, y| (-y, x),
+        |x, y| (y, -x),
+        |x, y| (-y, -x),
+    ];
+    orientations
+        .iter()
+        .map(|orient| {
+            let mut shape: Shape = cells.iter().map(|cell| orient(cell.0, cell.1)).collect();
+            let min_x = shape.iter().map(|&(x, _)| x).min().unwrap_or(0);
+            let min_y = shape.iter().map(|&(_, y)| y).min().unwrap_or(0);
+            for (x, y) in &mut shape {
+                *x -= min_x;
+                *y -= min_y;
+            }
+            shape.sort_unstable();
+            shape
+        })
+        .min()
+        .unwrap_or_default()
+}
@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::random::Random;
+use crate::simulation::{grid_cells, CellImage, Input, Options, Population, Simulation};
+use crate::Cell;
+
+const SUSCEPTIBLE: u8 = 0;
+const INFECTED: u8 = 1;
+const RECOVERED: u8 = 2;
+
+const SUSCEPTIBLE_COLOR: [u8; 4] = [60, 150, 60, 255];
+const INFECTED_COLOR: [u8; 4] = [230, 50, 50, 255];
+const RECOVERED_COLOR: [u8; 4] = [70, 110, 220, 255];
+
+/// A stochastic SIR epidemic on a bounded grid where every cell is a person: each infected
+/// neighbor passes the infection on with probability `infection` per step, the infected
+/// recover with probability `recovery`, and the recovered lose their immunity again with
+/// probability `immunity_loss`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Epidemic {
+    width: i32,
+    height: i32,
+    infection: f64,
+    recovery: f64,
+    immunity_loss: f64,
+    /// States row by row
+    states: Vec<u8>,
+    random: Random,
+    generation: usize,
+}
+
+impl Epidemic {
+    /// A susceptible population of `options.width` x `options.height` with one infected cell
+    /// in the middle.
+    pub fn new(options: &Options) -> Result<Self, String> {
+        let probability = |name: &str, default: f64| {
+            let p: f64 = options.param(name, default)?;
+            if (0.0..=1.0).contains(&p) {
+                Ok(p)
+            } else {
+                Err(format!("The {} probability must be between 0 and 1", name))
+            }
+        };
+        let (width, height) = (options.width.max(1), options.height.max(1));
+        let mut epidemic = Self {
+            width,
+            height,
+            infection: probability("infection", 0.2)?,
+            recovery: probability("recovery", 0.1)?,
+            immunity_loss: probability("immunity_loss", 0.0)?,
+            states: vec![SUSCEPTIBLE; grid_cells(width, height)?],
+            random: Random::new(options.seed),
+            generation: 1,
+        };
+        epidemic.set(Cell(width / 2, height / 2), INFECTED);
+        Ok(epidemic)
+    }
+
+    fn index(&self, cell: Cell) -> Option<usize> {
+        ((0..self.width).contains(&cell.0) && (0..self.height).contains(&cell.1))
+            .then(|| (cell.1 * self.width + cell.0) as usize)
+    }
+
+    fn set(&mut self, cell: Cell, state: u8) -> bool {
+        match self.index(cell) {
+            Some(i) if self.states[i] != state => {
+                self.states[i] = state;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn infected_around(&self, cell: Cell) -> i32 {
+        let mut infected = 0;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let neighbor = Cell(cell.0 + dx, cell.1 + dy);
+                if (dx, dy) != (0, 0)
+                    && self
+                        .index(neighbor)
+                        .is_some_and(|i| self.states[i] == INFECTED)
+                {
+                    infected += 1;
+                }
+            }
+        }
+        infected
+    }
+
+    fn count(&self, state: u8) -> usize {
+        self.states.iter().filter(|&&s| s == state).count()
+    }
+}
+
+impl Simulation for Epidemic {
+    fn name(&self) -> &'static str {
+        "sir"
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn step(&mut self) {
+        let mut next = self.states.clone();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let i = (y * self.width + x) as usize;
+                next[i] = match self.states[i] {
+                    SUSCEPTIBLE => {
+                        // Each infected neighbor is a separate chance of catching it
+                        let infected = self.infected_around(Cell(x, y));
+                        let escape = (1.0 - self.infection).powi(infected);
+                        if self.random.chance(1.0 - escape) {
+                            INFECTED
+                        } else {
+                            SUSCEPTIBLE
+                        }
+                    }
+                    INFECTED if self.random.chance(self.recovery) => RECOVERED,
+                    RECOVERED if self.random.chance(self.immunity_loss) => SUSCEPTIBLE,
+                    state => state,
+                };
+            }
+        }
+        self.states = next;
+        self.generation += 1;
+    }
+
+    fn bounds(&self) -> Option<(Cell, Cell)> {
+        Some((Cell(0, 0), Cell(self.width - 1, self.height - 1)))
+    }
+
+    fn draw(&self, image: &mut CellImage) {
+        for (i, &state) in self.states.iter().enumerate() {
+            let color = match state {
+                INFECTED => INFECTED_COLOR,
+                RECOVERED => RECOVERED_COLOR,
+                _ => SUSCEPTIBLE_COLOR,
+            };
+            let i = i as i32;
+            image.set(Cell(i % self.width, i / self.width), color);
+        }
+    }
+
+    /// Strokes starting on an infected cell heal cells back to susceptible.
+    fn occupied(&self, cell: Cell) -> bool {
+        self.index(cell).is_some_and(|i| self.states[i] == INFECTED)
+    }
+
+    /// Painting infects cells, or makes them susceptible again when erasing; activating
+    /// immunizes a cell.
+    fn handle_input(&mut self, input: Input) -> bool {
+        match input {
+            Input::Paint { cell, erase: false } => self.set(cell, INFECTED),
+            Input::Paint { cell, erase: true } => self.set(cell, SUSCEPTIBLE),
+            Input::Activate(cell) => self.set(cell, RECOVERED),
+        }
+    }
+
+    fn status(&self) -> Option<String> {
+        Some(format!(
+            "Susceptible: {}, infected: {}, recovered: {}",
+            self.count(SUSCEPTIBLE),
+            self.count(INFECTED),
+            self.count(RECOVERED)
+        ))
+    }
+
+    fn populations(&self) -> Vec<Population> {
+        vec![
+            Population {
+                name: "Susceptible",
+                count: self.count(SUSCEPTIBLE),
+                color: SUSCEPTIBLE_COLOR,
+            },
+            Population {
+                name: "Infected",
+                count: self.count(INFECTED),
+                color: INFECTED_COLOR,
+            },
+            Population {
+                name: "Recovered",
+                count: self.count(RECOVERED),
+                color: RECOVERED_COLOR,
+            },
+        ]
+    }
+
+    /// An entirely susceptible population.
+    fn blank(&self) -> Box<dyn Simulation> {
+        let mut blank = self.clone();
+        blank
+            .states
+            .iter_mut()
+            .for_each(|state| *state = SUSCEPTIBLE);
+        blank.generation = 1;
+        Box::new(blank)
+    }
+
+    fn save(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    fn restore(&mut self, state: Value) -> Result<(), String> {
+        let restored: Self =
+            serde_json::from_value(state).map_err(|err| format!("Invalid SIR state: {}", err))?;
+        if restored.states.len() != grid_cells(restored.width, restored.height)? {
+            return Err("SIR state doesn't match its size".to_string());
+        }
+        *self = restored;
+        Ok(())
+    }
+}
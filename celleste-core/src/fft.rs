@@ -0,0 +1,164 @@
+//! Radix-2 fast Fourier transforms over rows and columns, for convolving toroidal fields with
+//! large kernels.
+
+use std::f32::consts::PI;
+use std::ops::{Add, Mul, Sub};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Complex {
+    pub re: f32,
+    pub im: f32,
+}
+
+impl Complex {
+    pub fn new(re: f32, im: f32) -> Self {
+        Self { re, im }
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+/// Transforms `data` in place; its length must be a power of two. The inverse transform is
+/// scaled by `1 / len`, so a forward and an inverse pass give back the input.
+pub fn fft(data: &mut [Complex], inverse: bool) {
+    let len = data.len();
+    debug_assert!(len.is_power_of_two());
+    // Bit-reversal permutation
+    let mut j = 0;
+    for i in 1..len {
+        let mut bit = len >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut size = 2;
+    while size <= len {
+        let angle = sign * 2.0 * PI / size as f32;
+        let step = Complex::new(angle.cos(), angle.sin());
+        for start in (0..len).step_by(size) {
+            let mut twiddle = Complex::new(1.0, 0.0);
+            for k in 0..size / 2 {
+                let even = data[start + k];
+                let odd = data[start + k + size / 2] * twiddle;
+                data[start + k] = even + odd;
+                data[start + k + size / 2] = even - odd;
+                twiddle = twiddle * step;
+            }
+        }
+        size *= 2;
+    }
+    if inverse {
+        let scale = 1.0 / len as f32;
+        for value in data.iter_mut() {
+            *value = Complex::new(value.re * scale, value.im * scale);
+        }
+    }
+}
+
+/// Transforms a `width` x `height` field stored row by row; both sides must be powers of two.
+pub fn fft_2d(data: &mut [Complex], width: usize, height: usize, inverse: bool) {
+    for row in data.chunks_mut(width) {
+        fft(row, inverse);
+    }
+    let mut column = vec![Complex::default(); height];
+    for x in 0..width {
+        for (y, value) in column.iter_mut().enumerate() {
+            *value = data[y * width + x];
+        }
+        fft(&mut column, inverse);
+        for (y, value) in column.iter().enumerate() {
+            data[y * width + x] = *value;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A field of small, uneven values that are easy to tell apart.
+    fn field(width: usize, height: usize, seed: usize) -> Vec<Complex> {
+        (0..width * height)
+            .map(|i| Complex::new(((i * 7 + seed) % 11) as f32 / 10.0, 0.0))
+            .collect()
+    }
+
+    fn assert_close(actual: &[Complex], expected: &[Complex]) {
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(
+                (a.re - e.re).abs() < 1e-4 && (a.im - e.im).abs() < 1e-4,
+                "{:?} != {:?}",
+                a,
+                e
+            );
+        }
+    }
+
+    #[test]
+    fn forward_then_inverse_gives_back_the_input() {
+        let original = field(16, 8, 3);
+        let mut data = original.clone();
+        fft_2d(&mut data, 16, 8, false);
+        assert_ne!(data, original);
+        fft_2d(&mut data, 16, 8, true);
+        assert_close(&data, &original);
+    }
+
+    #[test]
+    fn multiplying_transforms_convolves_around_the_torus() {
+        let (width, height) = (8, 4);
+        let a = field(width, height, 1);
+        let b = field(width, height, 5);
+        let mut direct = vec![Complex::default(); width * height];
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = 0.0;
+                for dy in 0..height {
+                    for dx in 0..width {
+                        let other = ((y + height - dy) % height) * width + (x + width - dx) % width;
+                        sum += a[dy * width + dx].re * b[other].re;
+                    }
+                }
+                direct[y * width + x] = Complex::new(sum, 0.0);
+            }
+        }
+
+        let (mut fa, mut fb) = (a, b);
+        fft_2d(&mut fa, width, height, false);
+        fft_2d(&mut fb, width, height, false);
+        let mut product: Vec<Complex> = fa.into_iter().zip(fb).map(|(a, b)| a * b).collect();
+        fft_2d(&mut product, width, height, true);
+        assert_close(&product, &direct);
+    }
+}
@@ -0,0 +1,221 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use crate::{bitboard, bounding_box, cell_hash, patterns, Cell, Neighborhood, Rules, Torus};
+
+/// The parts of a generation `Grid::step_timed` reports the time of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepPhase {
+    /// Counting the live neighbors of every cell next to a live one
+    NeighborCount,
+    /// Working out the next state from the counts, or the whole step on a bitboard
+    RuleEval,
+    /// Swapping the new generation in
+    StateSwap,
+}
+
+/// A universe of cells evolving under a set of rules.
+#[derive(Clone)]
+pub struct Grid {
+    pub alive_cells: HashSet<Cell>,
+    /// Cells decaying under a Generations rule, with their state (2 and up)
+    pub dying_cells: HashMap<Cell, u8>,
+    pub rules: Rules,
+    /// Bounded grid wrapping around its edges, instead of an unbounded plane
+    pub torus: Option<Torus>,
+    /// Number of the current generation; the starting pattern is generation 1
+    pub generation: usize,
+}
+
+impl Grid {
+    /// An unbounded grid holding `alive_cells` at generation 1.
+    pub fn new(alive_cells: HashSet<Cell>, rules: Rules) -> Self {
+        Self {
+            alive_cells,
+            dying_cells: HashMap::new(),
+            rules,
+            torus: None,
+            generation: 1,
+        }
+    }
+
+    /// Reads an RLE pattern with its top-left corner at the origin, running the rule from its
+    /// header or Conway's Life if it names none.
+    pub fn from_rle(text: &str) -> Result<Self, String> {
+        let pattern = patterns::parse_rle(text)?;
+        let rules = Rules::from_string(pattern.rules.as_deref().unwrap_or("B3/S23"))?;
+        let mut grid = Self::new(pattern.cells, rules);
+        grid.dying_cells = pattern.dying.into_iter().collect();
+        Ok(grid)
+    }
+
+    /// The smallest and largest corner of the rectangle enclosing every live cell.
+    pub fn bounding_box(&self) -> Option<(Cell, Cell)> {
+        bounding_box(&self.alive_cells)
+    }
+
+    /// Advances one generation.
+    pub fn step(&mut self) {
+        self.step_timed(|_, _| {});
+    }
+
+    /// Advances one generation like `step`, telling `record` how long each phase took, e.g.
+    /// for a profiler.
+    pub fn step_timed(&mut self, mut record: impl FnMut(StepPhase, Duration)) {
+        if self.use_bitboard() {
+            let start = Instant::now();
+            self.alive_cells = bitboard::step(
+                &self.alive_cells,
+                bitboard::count_mask(&self.rules.birth),
+                bitboard::count_mask(&self.rules.survival),
+            );
+            record(StepPhase::RuleEval, start.elapsed());
+        } else {
+            let start = Instant::now();
+            let counts = self.neighbor_counts();
+            record(StepPhase::NeighborCount, start.elapsed());
+
+            let start = Instant::now();
+            let (alive_cells, dying_cells) = self.next_state(counts);
+            record(StepPhase::RuleEval, start.elapsed());
+
+            let start = Instant::now();
+            self.alive_cells = alive_cells;
+            self.dying_cells = dying_cells;
+            record(StepPhase::StateSwap, start.elapsed());
+        }
+        self.generation += 1;
+    }
+
+    /// Advances `n` generations. Stepping is deterministic: the same grid always reaches the
+    /// same state.
+    pub fn step_n(&mut self, n: u64) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    /// Hash of the live and decaying cells at their positions, equal for equal states however
+    /// they were reached.
+    pub fn state_hash(&self) -> u64 {
+        self.hash_shifted(Cell(0, 0))
+    }
+
+    /// Like `state_hash`, but with the pattern moved so its bounding box starts at the origin,
+    /// so a spaceship hashes the same wherever it has flown to.
+    pub fn canonical_hash(&self) -> u64 {
+        let (min, _) = self.bounding_box().unwrap_or((Cell(0, 0), Cell(0, 0)));
+        self.hash_shifted(min)
+    }
+
+    /// The state hash with every cell moved by `-origin`.
+    fn hash_shifted(&self, origin: Cell) -> u64 {
+        // Wrapping keeps equal shapes hashing equally even across the whole coordinate range
+        let shift = |cell: Cell| Cell(cell.0.wrapping_sub(origin.0), cell.1.wrapping_sub(origin.1));
+        let alive = self
+            .alive_cells
+            .iter()
+            .fold(0u64, |acc, &cell| acc.wrapping_add(cell_hash(shift(cell))));
+        // Each decaying state hashes differently from the others and from live cells
+        self.dying_cells.iter().fold(alive, |acc, (&cell, &state)| {
+            acc.wrapping_add(cell_hash(shift(cell)).rotate_left(state as u32))
+        })
+    }
+
+    /// Dense soups of two-state Moore rules step faster as bitboards than cell by cell.
+    pub fn use_bitboard(&self) -> bool {
+        let rules = &self.rules;
+        if self.torus.is_some()
+            || rules.is_ltl()
+            || rules.neighborhood != Neighborhood::Moore
+            || rules.states > 2
+            || rules.birth.contains(&0)
+            || rules.survival.contains(&0)
+            || self.alive_cells.len() < bitboard::MIN_POPULATION
+        {
+            return false;
+        }
+        let Some((min, max)) = self.bounding_box() else {
+            return false;
+        };
+        let area = (max.0 as f64 - min.0 as f64 + 1.0) * (max.1 as f64 - min.1 as f64 + 1.0);
+        self.alive_cells.len() as f64 / area >= bitboard::MIN_DENSITY
+    }
+
+    /// The cells `cell` counts as neighbors under the rules' neighborhood, wrapped onto the
+    /// torus if there is one. Cells past the edge of the universe are left out.
+    pub fn neighbors(&self, cell: Cell) -> Vec<Cell> {
+        let range = self.rules.range;
+        let mut neighbors = Vec::new();
+        for dy in -range..=range {
+            for dx in -range..=range {
+                if !self.rules.neighborhood.contains(dx, dy, range) {
+                    continue;
+                }
+                if dx != 0 || dy != 0 || self.rules.include_middle {
+                    let Some(neighbor) = cell.offset(dx, dy) else {
+                        continue;
+                    };
+                    neighbors.push(match self.torus {
+                        Some(torus) => torus.wrap(neighbor),
+                        None => neighbor,
+                    });
+                }
+            }
+        }
+        neighbors
+    }
+
+    /// Live neighbors of every cell next to at least one live cell, the first half of the
+    /// cell-by-cell step.
+    pub fn neighbor_counts(&self) -> HashMap<Cell, usize> {
+        let mut neighbor_counts: HashMap<Cell, usize> = HashMap::new();
+        for &cell in &self.alive_cells {
+            // For each neighbor of a live cell, increment its count
+            for neighbor in self.neighbors(cell) {
+                *neighbor_counts.entry(neighbor).or_insert(0) += 1;
+            }
+        }
+        neighbor_counts
+    }
+
+    /// The live and decaying cells of the next generation given `neighbor_counts`, the second
+    /// half of the cell-by-cell step.
+    pub fn next_state(
+        &self,
+        neighbor_counts: HashMap<Cell, usize>,
+    ) -> (HashSet<Cell>, HashMap<Cell, u8>) {
+        let mut new_state = HashSet::new();
+        // Evaluate the new state based on neighbor counts
+        for (cell, count) in neighbor_counts {
+            if self.alive_cells.contains(&cell) {
+                // For live cells, check if they survive
+                if self.rules.survival.contains(&count) {
+                    new_state.insert(cell);
+                }
+            } else if !self.dying_cells.contains_key(&cell) {
+                // For dead cells, check if they are born
+                if self.rules.birth.contains(&count) {
+                    new_state.insert(cell);
+                }
+            }
+        }
+
+        // Under Generations rules, cells that didn't survive start decaying and decaying
+        // cells age until they run out of states
+        let mut new_dying = HashMap::new();
+        if self.rules.states > 2 {
+            for (&cell, &state) in &self.dying_cells {
+                if state + 1 < self.rules.states {
+                    new_dying.insert(cell, state + 1);
+                }
+            }
+            for &cell in &self.alive_cells {
+                if !new_state.contains(&cell) {
+                    new_dying.insert(cell, 2);
+                }
+            }
+        }
+        (new_state, new_dying)
+    }
+}
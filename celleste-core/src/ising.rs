@@ -0,0 +1,241 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::random::Random;
+use crate::simulation::{grid_cells, CellImage, Input, Options, Population, Simulation};
+use crate::Cell;
+
+const UP_COLOR: [u8; 4] = [240, 240, 240, 255];
+const DOWN_COLOR: [u8; 4] = [30, 40, 90, 255];
+/// How far one notch of adjusting moves the temperature.
+const TEMPERATURE_STEP: f64 = 0.05;
+/// The lowest temperature adjusting goes down to.
+const MIN_TEMPERATURE: f64 = 0.05;
+
+/// How a spin decides whether to flip, given the energy the flip would cost.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum Dynamics {
+    /// Always flips when it lowers the energy, otherwise with probability `exp(-ΔE / T)`
+    Metropolis,
+    /// Flips with probability `1 / (1 + exp(ΔE / T))`
+    Glauber,
+}
+
+/// The 2D Ising model on a torus: every cell is a spin, up or down, and neighboring spins
+/// that agree lower the energy. Each step is one sweep of as many single-spin flip attempts
+/// as there are cells, at random cells, accepted by Metropolis or Glauber dynamics at the
+/// current temperature. Below about 2.27 the spins order into large domains.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Ising {
+    width: i32,
+    height: i32,
+    dynamics: Dynamics,
+    temperature: f64,
+    /// Spins row by row, `true` for up
+    spins: Vec<bool>,
+    random: Random,
+    generation: usize,
+}
+
+impl Ising {
+    /// An `options.width` x `options.height` torus of random spins at `--param temperature`.
+    pub fn new(options: &Options) -> Result<Self, String> {
+        let dynamics: String = options.param("dynamics", "metropolis".to_string())?;
+        let dynamics = match dynamics.to_ascii_lowercase().as_str() {
+            "metropolis" => Dynamics::Metropolis,
+            "glauber" => Dynamics::Glauber,
+            _ => {
+                return Err(format!(
+                    "Unknown dynamics '{}' (known: metropolis, glauber)",
+                    dynamics
+                ))
+            }
+        };
+        let temperature: f64 = options.param("temperature", 2.27)?;
+        if temperature.is_nan() || temperature <= 0.0 {
+            return Err("The temperature must be above 0".to_string());
+        }
+        let (width, height) = (options.width.max(1), options.height.max(1));
+        let mut random = Random::new(options.seed);
+        let spins = (0..grid_cells(width, height)?)
+            .map(|_| random.chance(0.5))
+            .collect();
+        Ok(Self {
+            width,
+            height,
+            dynamics,
+            temperature,
+            spins,
+            random,
+            generation: 1,
+        })
+    }
+
+    fn index(&self, cell: Cell) -> Option<usize> {
+        ((0..self.width).contains(&cell.0) && (0..self.height).contains(&cell.1))
+            .then(|| (cell.1 * self.width + cell.0) as usize)
+    }
+
+    fn set(&mut self, cell: Cell, up: bool) -> bool {
+        match self.index(cell) {
+            Some(i) if self.spins[i] != up => {
+                self.spins[i] = up;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn spin(&self, x: i32, y: i32) -> i32 {
+        let i = (y.rem_euclid(self.height) * self.width + x.rem_euclid(self.width)) as usize;
+        if self.spins[i] {
+            1
+        } else {
+            -1
+        }
+    }
+
+    /// Sum of the four spins next to `(x, y)`, wrapping around the edges.
+    fn neighbor_sum(&self, x: i32, y: i32) -> i32 {
+        self.spin(x + 1, y) + self.spin(x - 1, y) + self.spin(x, y + 1) + self.spin(x, y - 1)
+    }
+
+    /// Chance of accepting a flip, indexed by the energy it costs divided by 4, plus 2.
+    fn acceptance(&self) -> [f64; 5] {
+        [-8.0, -4.0, 0.0, 4.0, 8.0].map(|cost: f64| match self.dynamics {
+            Dynamics::Metropolis => (-cost / self.temperature).exp().min(1.0),
+            Dynamics::Glauber => 1.0 / (1.0 + (cost / self.temperature).exp()),
+        })
+    }
+
+    fn count(&self, up: bool) -> usize {
+        self.spins.iter().filter(|&&spin| spin == up).count()
+    }
+
+    /// Mean spin, from -1 (all down) to 1 (all up).
+    fn magnetization(&self) -> f64 {
+        let up = self.count(true) as f64;
+        (2.0 * up - self.spins.len() as f64) / self.spins.len() as f64
+    }
+
+    /// Mean energy per spin, from -2 (fully ordered) to 2.
+    fn energy(&self) -> f64 {
+        let mut energy = 0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                // Counting each bond once, to the right and below
+                energy -= self.spin(x, y) * (self.spin(x + 1, y) + self.spin(x, y + 1));
+            }
+        }
+        energy as f64 / self.spins.len() as f64
+    }
+}
+
+impl Simulation for Ising {
+    fn name(&self) -> &'static str {
+        "ising"
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn step(&mut self) {
+        let acceptance = self.acceptance();
+        for _ in 0..self.spins.len() {
+            let i =
+                ((self.random.unit() * self.spins.len() as f64) as usize).min(self.spins.len() - 1);
+            let (x, y) = (i as i32 % self.width, i as i32 / self.width);
+            // Flipping costs 2 * spin * (sum of neighbors) of energy, a multiple of 4
+            let cost = self.spin(x, y) * self.neighbor_sum(x, y) / 2;
+            if self.random.chance(acceptance[(cost + 2) as usize]) {
+                self.spins[i] = !self.spins[i];
+            }
+        }
+        self.generation += 1;
+    }
+
+    fn bounds(&self) -> Option<(Cell, Cell)> {
+        Some((Cell(0, 0), Cell(self.width - 1, self.height - 1)))
+    }
+
+    fn draw(&self, image: &mut CellImage) {
+        for (i, &up) in self.spins.iter().enumerate() {
+            let i = i as i32;
+            let color = if up { UP_COLOR } else { DOWN_COLOR };
+            image.set(Cell(i % self.width, i / self.width), color);
+        }
+    }
+
+    fn occupied(&self, cell: Cell) -> bool {
+        self.index(cell).is_some_and(|i| self.spins[i])
+    }
+
+    /// Painting turns spins up and erasing turns them down.
+    fn handle_input(&mut self, input: Input) -> bool {
+        match input {
+            Input::Paint { cell, erase } => self.set(cell, !erase),
+            Input::Activate(_) => false,
+        }
+    }
+
+    fn status(&self) -> Option<String> {
+        Some(format!(
+            "Temperature: {:.2}, magnetization: {:.3}, energy: {:.3}",
+            self.temperature,
+            self.magnetization(),
+            self.energy()
+        ))
+    }
+
+    /// Warms (or cools) the spins by one notch.
+    fn adjust(&mut self, up: bool) -> Option<String> {
+        let change = if up {
+            TEMPERATURE_STEP
+        } else {
+            -TEMPERATURE_STEP
+        };
+        self.temperature = (self.temperature + change).max(MIN_TEMPERATURE);
+        Some(format!("Temperature {:.2}", self.temperature))
+    }
+
+    fn populations(&self) -> Vec<Population> {
+        vec![
+            Population {
+                name: "Up",
+                count: self.count(true),
+                color: UP_COLOR,
+            },
+            Population {
+                name: "Down",
+                count: self.count(false),
+                color: DOWN_COLOR,
+            },
+        ]
+    }
+
+    /// Every spin down.
+    fn blank(&self) -> Box<dyn Simulation> {
+        let mut blank = self.clone();
+        blank.spins.iter_mut().for_each(|spin| *spin = false);
+        blank.generation = 1;
+        Box::new(blank)
+    }
+
+    fn save(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    fn restore(&mut self, state: Value) -> Result<(), String> {
+        let restored: Self =
+            serde_json::from_value(state).map_err(|err| format!("Invalid Ising state: {}", err))?;
+        if restored.spins.len() != grid_cells(restored.width, restored.height)? {
+            return Err("Ising state doesn't match its size".to_string());
+        }
+        if restored.temperature.is_nan() || restored.temperature <= 0.0 {
+            return Err("The temperature must be above 0".to_string());
+        }
+        *self = restored;
+        Ok(())
+    }
+}
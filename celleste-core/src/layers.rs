@@ -0,0 +1,315 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::random::Random;
+use crate::simulation::{grid_cells, CellImage, Input, Options, Population, Simulation};
+use crate::{Cell, Rules};
+
+const A_COLOR: [u8; 4] = [240, 90, 70, 255];
+const B_COLOR: [u8; 4] = [70, 200, 250, 255];
+/// Cells alive in both layers while both are shown
+const BOTH_COLOR: [u8; 4] = [245, 245, 245, 255];
+const NAMES: [&str; 2] = ["A", "B"];
+
+/// How layer A's cells sway layer B's.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum Coupling {
+    /// The layers evolve independently
+    None,
+    /// B cells are only born on top of live A cells
+    Require,
+    /// B cells are never born on top of live A cells
+    Inhibit,
+    /// B cells count live A neighbors as well as their own
+    Count,
+}
+
+const COUPLINGS: [(Coupling, &str); 4] = [
+    (Coupling::None, "none"),
+    (Coupling::Require, "require"),
+    (Coupling::Inhibit, "inhibit"),
+    (Coupling::Count, "count"),
+];
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Layer {
+    birth: Vec<usize>,
+    survival: Vec<usize>,
+    /// Cells row by row
+    alive: Vec<bool>,
+    visible: bool,
+}
+
+impl Layer {
+    /// Whether cell `i` is alive next generation with `count` live neighbors, if births are
+    /// `allowed` there.
+    fn next(&self, i: usize, count: usize, allowed: bool) -> bool {
+        if self.alive[i] {
+            self.survival.contains(&count)
+        } else {
+            allowed && self.birth.contains(&count)
+        }
+    }
+}
+
+/// Two Life-like grids stacked on top of each other, each stepped by its own rule: layer A
+/// evolves on its own, and the coupling rule lets it decide where layer B's cells can be
+/// born.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Layers {
+    width: i32,
+    height: i32,
+    coupling: Coupling,
+    layers: [Layer; 2],
+    generation: usize,
+}
+
+impl Layers {
+    /// Both layers `options.width` x `options.height`, filled at random with `--param soup`
+    /// of their cells. Layer A runs `--param rule_a` (the `--rules`) and layer B
+    /// `--param rule_b` (B3/S23).
+    pub fn new(options: &Options) -> Result<Self, String> {
+        let rule_a = match options.params.get("rule_a") {
+            Some(rule) => Rules::from_string(rule)?,
+            None => options.rules.clone(),
+        };
+        let rule_b = Rules::from_string(&options.param("rule_b", "B3/S23".to_string())?)?;
+        if !rule_a.is_life_like() || !rule_b.is_life_like() {
+            return Err("Layers need two-state B/S rules such as B3/S23".to_string());
+        }
+        let coupling: String = options.param("coupling", "inhibit".to_string())?;
+        let coupling = COUPLINGS
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(&coupling))
+            .map(|&(coupling, _)| coupling)
+            .ok_or_else(|| {
+                let names: Vec<&str> = COUPLINGS.iter().map(|(_, name)| *name).collect();
+                format!(
+                    "Unknown coupling '{}' (known: {})",
+                    coupling,
+                    names.join(", ")
+                )
+            })?;
+        let soup: f64 = options.param("soup", 0.25)?;
+        if !(0.0..=1.0).contains(&soup) {
+            return Err("The soup density must be between 0 and 1".to_string());
+        }
+        let (width, height) = (options.width.max(1), options.height.max(1));
+        let cells = grid_cells(width, height)?;
+        let mut random = Random::new(options.seed);
+        let mut layer = |rules: Rules| Layer {
+            birth: rules.birth,
+            survival: rules.survival,
+            alive: (0..cells).map(|_| random.chance(soup)).collect(),
+            visible: true,
+        };
+        Ok(Self {
+            width,
+            height,
+            coupling,
+            layers: [layer(rule_a), layer(rule_b)],
+            generation: 1,
+        })
+    }
+
+    fn index(&self, cell: Cell) -> Option<usize> {
+        ((0..self.width).contains(&cell.0) && (0..self.height).contains(&cell.1))
+            .then(|| (cell.1 * self.width + cell.0) as usize)
+    }
+
+    /// The layer painting edits: the first one shown.
+    fn editing(&self) -> usize {
+        if self.layers[0].visible {
+            0
+        } else {
+            1
+        }
+    }
+
+    /// Live neighbors of every cell in `layer`, with cells beyond the edges dead.
+    fn neighbor_counts(&self, layer: usize) -> Vec<usize> {
+        let alive = &self.layers[layer].alive;
+        let mut counts = vec![0; alive.len()];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if !alive[(y * self.width + x) as usize] {
+                    continue;
+                }
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        if (dx, dy) == (0, 0) {
+                            continue;
+                        }
+                        if let Some(j) = self.index(Cell(x + dx, y + dy)) {
+                            counts[j] += 1;
+                        }
+                    }
+                }
+            }
+        }
+        counts
+    }
+
+    fn count(&self, layer: usize) -> usize {
+        self.layers[layer]
+            .alive
+            .iter()
+            .filter(|&&alive| alive)
+            .count()
+    }
+}
+
+impl Simulation for Layers {
+    fn name(&self) -> &'static str {
+        "layers"
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Steps both layers at once, with layer B reading layer A as it was before the step.
+    fn step(&mut self) {
+        let counts_a = self.neighbor_counts(0);
+        let mut counts_b = self.neighbor_counts(1);
+        if self.coupling == Coupling::Count {
+            for (count, count_a) in counts_b.iter_mut().zip(&counts_a) {
+                *count += count_a;
+            }
+        }
+        let [a, b] = &self.layers;
+        let next_a = (0..a.alive.len())
+            .map(|i| a.next(i, counts_a[i], true))
+            .collect();
+        let next_b = (0..b.alive.len())
+            .map(|i| {
+                let allowed = match self.coupling {
+                    Coupling::Require => a.alive[i],
+                    Coupling::Inhibit => !a.alive[i],
+                    Coupling::None | Coupling::Count => true,
+                };
+                b.next(i, counts_b[i], allowed)
+            })
+            .collect();
+        self.layers[0].alive = next_a;
+        self.layers[1].alive = next_b;
+        self.generation += 1;
+    }
+
+    fn bounds(&self) -> Option<(Cell, Cell)> {
+        Some((Cell(0, 0), Cell(self.width - 1, self.height - 1)))
+    }
+
+    /// Each shown layer in its own color, and cells alive in both in white.
+    fn draw(&self, image: &mut CellImage) {
+        let [a, b] = &self.layers;
+        for i in 0..a.alive.len() {
+            let color = match (a.visible && a.alive[i], b.visible && b.alive[i]) {
+                (true, true) => BOTH_COLOR,
+                (true, false) => A_COLOR,
+                (false, true) => B_COLOR,
+                (false, false) => continue,
+            };
+            let i = i as i32;
+            image.set(Cell(i % self.width, i / self.width), color);
+        }
+    }
+
+    fn occupied(&self, cell: Cell) -> bool {
+        let layer = &self.layers[self.editing()];
+        self.index(cell).is_some_and(|i| layer.alive[i])
+    }
+
+    /// Painting edits the first layer shown, so hiding layer A paints on layer B.
+    fn handle_input(&mut self, input: Input) -> bool {
+        let Input::Paint { cell, erase } = input else {
+            return false;
+        };
+        let editing = self.editing();
+        match self.index(cell) {
+            Some(i) if self.layers[editing].alive[i] == erase => {
+                self.layers[editing].alive[i] = !erase;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn status(&self) -> Option<String> {
+        let describe = |layer: usize| {
+            format!(
+                "{}: {}{}",
+                NAMES[layer],
+                self.count(layer),
+                if self.layers[layer].visible {
+                    ""
+                } else {
+                    " (hidden)"
+                }
+            )
+        };
+        let coupling = COUPLINGS
+            .iter()
+            .find(|(coupling, _)| *coupling == self.coupling)
+            .map_or("", |(_, name)| name);
+        Some(format!(
+            "Layer {}, layer {}, coupling: {}",
+            describe(0),
+            describe(1),
+            coupling
+        ))
+    }
+
+    fn toggle_layer(&mut self, layer: usize) -> Option<String> {
+        let shown = &mut self.layers.get_mut(layer)?.visible;
+        *shown = !*shown;
+        Some(format!(
+            "Layer {} {}",
+            NAMES[layer],
+            if *shown { "shown" } else { "hidden" }
+        ))
+    }
+
+    fn populations(&self) -> Vec<Population> {
+        vec![
+            Population {
+                name: "Layer A",
+                count: self.count(0),
+                color: A_COLOR,
+            },
+            Population {
+                name: "Layer B",
+                count: self.count(1),
+                color: B_COLOR,
+            },
+        ]
+    }
+
+    fn blank(&self) -> Box<dyn Simulation> {
+        let mut blank = self.clone();
+        for layer in &mut blank.layers {
+            layer.alive.iter_mut().for_each(|alive| *alive = false);
+        }
+        blank.generation = 1;
+        Box::new(blank)
+    }
+
+    fn save(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    fn restore(&mut self, state: Value) -> Result<(), String> {
+        let restored: Self = serde_json::from_value(state)
+            .map_err(|err| format!("Invalid layers state: {}", err))?;
+        let size = grid_cells(restored.width, restored.height)?;
+        if restored
+            .layers
+            .iter()
+            .any(|layer| layer.alive.len() != size)
+        {
+            return Err("Layers state doesn't match its size".to_string());
+        }
+        *self = restored;
+        Ok(())
+    }
+}
@@ -0,0 +1,320 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::fft::{fft_2d, Complex};
+use crate::simulation::{grid_cells, CellImage, Input, Options, Simulation};
+use crate::Cell;
+
+/// Width of the kernel's Gaussian ring, as a fraction of its radius.
+const RING_WIDTH: f32 = 0.15;
+
+/// A Lenia lifeform with the parameters it lives under.
+pub struct Creature {
+    pub name: &'static str,
+    pub radius: f32,
+    /// Steps per unit of time; each step moves `1 / time_scale` of the way
+    pub time_scale: f32,
+    /// Center and width of the growth mapping
+    pub mu: f32,
+    pub sigma: f32,
+    /// Cell values row by row, 0 to 100
+    pub cells: &'static [&'static [u8]],
+}
+
+/// Creatures `--param creature=NAME` starts from and Shift+Right Click drops.
+#[rustfmt::skip]
+pub const CREATURES: &[Creature] = &[Creature {
+    name: "orbium",
+    radius: 13.0,
+    time_scale: 10.0,
+    mu: 0.15,
+    sigma: 0.015,
+    cells: &[
+        &[0, 0, 0, 0, 0, 0, 10, 14, 10, 0, 0, 3, 3, 0, 0, 30, 0, 0, 0, 0],
+        &[0, 0, 0, 0, 0, 8, 24, 30, 30, 18, 14, 15, 16, 15, 9, 20, 0, 0, 0, 0],
+        &[0, 0, 0, 0, 0, 15, 34, 44, 46, 38, 18, 14, 11, 13, 19, 18, 45, 0, 0, 0],
+        &[0, 0, 0, 0, 6, 13, 39, 50, 50, 37, 6, 0, 0, 0, 2, 16, 68, 0, 0, 0],
+        &[0, 0, 0, 11, 17, 17, 33, 40, 38, 28, 14, 0, 0, 0, 0, 0, 18, 42, 0, 0],
+        &[0, 0, 9, 18, 13, 6, 8, 26, 32, 32, 27, 0, 0, 0, 0, 0, 0, 82, 0, 0],
+        &[27, 0, 16, 12, 0, 0, 0, 25, 38, 44, 45, 34, 0, 0, 0, 0, 0, 22, 17, 0],
+        &[0, 7, 20, 2, 0, 0, 0, 31, 48, 57, 60, 57, 0, 0, 0, 0, 0, 0, 49, 0],
+        &[0, 59, 19, 0, 0, 0, 0, 20, 57, 69, 76, 76, 49, 0, 0, 0, 0, 0, 36, 0],
+        &[0, 58, 19, 0, 0, 0, 0, 0, 67, 83, 90, 92, 87, 12, 0, 0, 0, 0, 22, 7],
+        &[0, 0, 46, 0, 0, 0, 0, 0, 70, 93, 100, 100, 100, 61, 0, 0, 0, 0, 18, 11],
+        &[0, 0, 82, 0, 0, 0, 0, 0, 47, 100, 100, 98, 100, 96, 27, 0, 0, 0, 19, 10],
+        &[0, 0, 46, 0, 0, 0, 0, 0, 25, 100, 100, 84, 92, 97, 54, 14, 4, 10, 21, 5],
+        &[0, 0, 0, 40, 0, 0, 0, 0, 9, 80, 100, 82, 80, 85, 63, 31, 18, 19, 20, 1],
+        &[0, 0, 0, 36, 10, 0, 0, 0, 5, 54, 86, 79, 74, 72, 60, 39, 28, 24, 13, 0],
+        &[0, 0, 0, 1, 30, 7, 0, 0, 8, 36, 64, 70, 64, 60, 51, 39, 29, 19, 4, 0],
+        &[0, 0, 0, 0, 10, 24, 14, 10, 15, 29, 45, 53, 52, 46, 40, 31, 21, 8, 0, 0],
+        &[0, 0, 0, 0, 0, 8, 21, 21, 22, 29, 36, 39, 37, 33, 26, 18, 9, 0, 0, 0],
+        &[0, 0, 0, 0, 0, 0, 3, 13, 19, 22, 24, 24, 23, 18, 13, 5, 0, 0, 0, 0],
+        &[0, 0, 0, 0, 0, 0, 0, 0, 2, 6, 8, 9, 7, 5, 1, 0, 0, 0, 0, 0],
+    ],
+}];
+
+/// Bert Chan's Lenia on a torus: cell values between 0 and 1 grow or shrink by how well the
+/// weighted sum of their surroundings, through a Gaussian ring kernel, matches the growth
+/// mapping's center `mu`. The sum is a convolution done with FFTs, so the field's sides are
+/// rounded up to powers of two.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Lenia {
+    width: i32,
+    height: i32,
+    radius: f32,
+    time_scale: f32,
+    mu: f32,
+    sigma: f32,
+    /// Name of the creature activation drops
+    creature: Option<String>,
+    /// Cell values row by row
+    field: Vec<f32>,
+    generation: usize,
+    /// The kernel's transform, ready to multiply the field's by
+    #[serde(skip)]
+    kernel: Vec<Complex>,
+}
+
+impl Lenia {
+    /// A field of at least `options.width` x `options.height` cells with a creature in the
+    /// middle. The creature (`--param creature=orbium`, or `none`) supplies the radius, time
+    /// scale and growth mapping, which parameters can override.
+    pub fn new(options: &Options) -> Result<Self, String> {
+        let name: String = options.param("creature", "orbium".to_string())?;
+        let creature = match name.as_str() {
+            "none" => None,
+            name => Some(find_creature(name)?),
+        };
+        let defaults = creature.unwrap_or(&CREATURES[0]);
+        // Checked before rounding too, so the sides can't round past the i32 range
+        grid_cells(options.width.max(1), options.height.max(1))?;
+        let width = (options.width.max(1) as u32).next_power_of_two() as i32;
+        let height = (options.height.max(1) as u32).next_power_of_two() as i32;
+        let mut lenia = Self {
+            width,
+            height,
+            radius: options.param("radius", defaults.radius)?,
+            time_scale: options.param("time_scale", defaults.time_scale)?,
+            mu: options.param("mu", defaults.mu)?,
+            sigma: options.param("sigma", defaults.sigma)?,
+            creature: creature.map(|creature| creature.name.to_string()),
+            field: vec![0.0; grid_cells(width, height)?],
+            generation: 1,
+            kernel: Vec::new(),
+        };
+        lenia.check_parameters()?;
+        lenia.build_kernel();
+        lenia.handle_input(Input::Activate(Cell(width / 2, height / 2)));
+        Ok(lenia)
+    }
+
+    /// Rejects parameters that aren't finite, a kernel wider than the torus, which would wrap
+    /// onto itself, and a growth center outside the 0 to 1 a potential can take.
+    fn check_parameters(&self) -> Result<(), String> {
+        let max_radius = self.width.min(self.height) as f32 / 2.0;
+        if !(self.radius >= 1.0 && self.radius <= max_radius) {
+            return Err(format!(
+                "Lenia needs 1 <= radius <= {}, half the field's shorter side",
+                max_radius
+            ));
+        }
+        if !(self.time_scale.is_finite() && self.time_scale >= 1.0) {
+            return Err("Lenia needs a finite time_scale >= 1".to_string());
+        }
+        if !(self.mu.is_finite() && (0.0..=1.0).contains(&self.mu)) {
+            return Err("Lenia needs 0 <= mu <= 1".to_string());
+        }
+        if !(self.sigma.is_finite() && self.sigma > 0.0) {
+            return Err("Lenia needs a finite sigma > 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// Transforms the kernel, a Gaussian ring normalized to sum to 1, laid out around cell
+    /// (0, 0) of the torus.
+    fn build_kernel(&mut self) {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let reach = self.radius.ceil() as i32;
+        let mut kernel = vec![Complex::default(); width * height];
+        let mut total = 0.0;
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let distance = (dx as f32).hypot(dy as f32) / self.radius;
+                if distance >= 1.0 {
+                    continue;
+                }
+                let weight = (-((distance - 0.5) / RING_WIDTH).powi(2) / 2.0).exp();
+                let x = dx.rem_euclid(self.width) as usize;
+                let y = dy.rem_euclid(self.height) as usize;
+                kernel[y * width + x].re += weight;
+                total += weight;
+            }
+        }
+        for value in &mut kernel {
+            value.re /= total;
+        }
+        fft_2d(&mut kernel, width, height, false);
+        self.kernel = kernel;
+    }
+
+    fn index(&self, cell: Cell) -> Option<usize> {
+        ((0..self.width).contains(&cell.0) && (0..self.height).contains(&cell.1))
+            .then(|| (cell.1 * self.width + cell.0) as usize)
+    }
+
+    /// Sets a cell, wrapping around the torus's edges.
+    fn set(&mut self, cell: Cell, value: f32) -> bool {
+        let cell = Cell(
+            cell.0.rem_euclid(self.width),
+            cell.1.rem_euclid(self.height),
+        );
+        match self.index(cell) {
+            Some(i) if self.field[i] != value => {
+                self.field[i] = value;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// How much a cell grows, from -1 to 1, given the kernel-weighted sum around it.
+    fn growth(&self, potential: f32) -> f32 {
+        2.0 * (-((potential - self.mu) / self.sigma).powi(2) / 2.0).exp() - 1.0
+    }
+}
+
+fn find_creature(name: &str) -> Result<&'static Creature, String> {
+    CREATURES
+        .iter()
+        .find(|creature| creature.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let names: Vec<&str> = CREATURES.iter().map(|creature| creature.name).collect();
+            format!(
+                "Unknown Lenia creature '{}' (known: none, {})",
+                name,
+                names.join(", ")
+            )
+        })
+}
+
+/// Lenia's usual palette: dark blue through cyan and yellow to white as cells fill in.
+fn palette(value: f32) -> [u8; 4] {
+    const STOPS: [[f32; 3]; 5] = [
+        [0.0, 0.0, 0.0],
+        [20.0, 30.0, 140.0],
+        [0.0, 160.0, 200.0],
+        [240.0, 220.0, 40.0],
+        [255.0, 255.0, 255.0],
+    ];
+    let position = value.clamp(0.0, 1.0) * (STOPS.len() - 1) as f32;
+    let low = (position as usize).min(STOPS.len() - 2);
+    let t = position - low as f32;
+    let channel = |c: usize| (STOPS[low][c] + (STOPS[low + 1][c] - STOPS[low][c]) * t) as u8;
+    [channel(0), channel(1), channel(2), 255]
+}
+
+impl Simulation for Lenia {
+    fn name(&self) -> &'static str {
+        "lenia"
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn step(&mut self) {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mut potential: Vec<Complex> = self
+            .field
+            .iter()
+            .map(|&value| Complex::new(value, 0.0))
+            .collect();
+        fft_2d(&mut potential, width, height, false);
+        for (value, kernel) in potential.iter_mut().zip(&self.kernel) {
+            *value = *value * *kernel;
+        }
+        fft_2d(&mut potential, width, height, true);
+        let dt = 1.0 / self.time_scale;
+        let growth: Vec<f32> = potential.iter().map(|sum| self.growth(sum.re)).collect();
+        for (value, growth) in self.field.iter_mut().zip(growth) {
+            *value = (*value + dt * growth).clamp(0.0, 1.0);
+        }
+        self.generation += 1;
+    }
+
+    fn bounds(&self) -> Option<(Cell, Cell)> {
+        Some((Cell(0, 0), Cell(self.width - 1, self.height - 1)))
+    }
+
+    fn draw(&self, image: &mut CellImage) {
+        for (i, &value) in self.field.iter().enumerate() {
+            let i = i as i32;
+            image.set(Cell(i % self.width, i / self.width), palette(value));
+        }
+    }
+
+    fn occupied(&self, cell: Cell) -> bool {
+        self.index(cell).is_some_and(|i| self.field[i] > 0.5)
+    }
+
+    /// Painting fills cells in completely; activating drops the creature centered on the cell.
+    fn handle_input(&mut self, input: Input) -> bool {
+        match input {
+            Input::Paint { cell, .. } if self.index(cell).is_none() => false,
+            Input::Paint { cell, erase } => self.set(cell, if erase { 0.0 } else { 1.0 }),
+            Input::Activate(center) => {
+                let Some(creature) = self
+                    .creature
+                    .as_deref()
+                    .and_then(|name| CREATURES.iter().find(|creature| creature.name == name))
+                else {
+                    return false;
+                };
+                let rows = creature.cells.len() as i32;
+                let mut changed = false;
+                for (y, row) in creature.cells.iter().enumerate() {
+                    let columns = row.len() as i32;
+                    for (x, &value) in row.iter().enumerate() {
+                        let cell = Cell(
+                            center.0 + x as i32 - columns / 2,
+                            center.1 + y as i32 - rows / 2,
+                        );
+                        changed |= self.set(cell, value as f32 / 100.0);
+                    }
+                }
+                changed
+            }
+        }
+    }
+
+    fn status(&self) -> Option<String> {
+        Some(format!("Mass: {:.0}", self.field.iter().sum::<f32>()))
+    }
+
+    fn blank(&self) -> Box<dyn Simulation> {
+        let mut blank = self.clone();
+        blank.field.iter_mut().for_each(|value| *value = 0.0);
+        blank.generation = 1;
+        Box::new(blank)
+    }
+
+    fn save(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    fn restore(&mut self, state: Value) -> Result<(), String> {
+        let mut restored: Self =
+            serde_json::from_value(state).map_err(|err| format!("Invalid Lenia state: {}", err))?;
+        let cells = grid_cells(restored.width, restored.height)?;
+        let (width, height) = (restored.width as u32, restored.height as u32);
+        if !width.is_power_of_two() || !height.is_power_of_two() || restored.field.len() != cells {
+            return Err("Lenia state doesn't match its size".to_string());
+        }
+        restored
+            .check_parameters()
+            .map_err(|err| format!("Invalid Lenia state: {}", err))?;
+        restored.build_kernel();
+        *self = restored;
+        Ok(())
+    }
+}
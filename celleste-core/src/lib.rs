@@ -0,0 +1,95 @@
+//! The engine behind Celleste: an unbounded (or toroidal) grid of cells, the rules that evolve
+//! it, and the pattern files it reads and writes. The `celleste` binary is a frontend over this
+//! crate.
+//!
+//! ```
+//! use celleste_core::Grid;
+//!
+//! let mut grid = Grid::from_rle("x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!").unwrap();
+//! grid.step();
+//! assert_eq!(grid.generation, 2);
+//! assert_eq!(grid.alive_cells.len(), 5);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashSet;
+
+pub mod battle;
+pub mod bitboard;
+pub mod bz;
+pub mod census;
+pub mod epidemic;
+mod fft;
+pub mod grid;
+pub mod ising;
+pub mod layers;
+pub mod lenia;
+pub mod margolus;
+pub mod patterns;
+mod random;
+pub mod rules;
+pub mod sand;
+pub mod sandpile;
+pub mod simulation;
+pub mod smoothlife;
+pub mod topology;
+pub mod wator;
+pub mod wireworld;
+
+pub use grid::Grid;
+pub use rules::{Neighborhood, Rules};
+pub use simulation::Simulation;
+pub use topology::Torus;
+
+/// A cell's `(x, y)` position; `y` grows downwards. The universe ends at the edges of the
+/// `i32` range: nothing is ever born beyond them, so patterns flying that far are cut off
+/// there rather than wrapping around to the other side.
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub struct Cell(pub i32, pub i32);
+
+impl Cell {
+    /// Parses an `X,Y` coordinate pair.
+    pub fn from_string(point: &str) -> Result<Self, String> {
+        let (x, y) = point
+            .split_once(',')
+            .ok_or_else(|| "Invalid coordinates. Expected 'X,Y'.".to_string())?;
+        match (x.trim().parse(), y.trim().parse()) {
+            (Ok(x), Ok(y)) => Ok(Cell(x, y)),
+            _ => Err("Invalid coordinates. Expected 'X,Y'.".to_string()),
+        }
+    }
+
+    /// The cell `(dx, dy)` away, unless that's past the edge of the universe.
+    pub fn offset(self, dx: i32, dy: i32) -> Option<Cell> {
+        Some(Cell(self.0.checked_add(dx)?, self.1.checked_add(dy)?))
+    }
+}
+
+/// The smallest and largest corner of the rectangle enclosing every cell, if there are any.
+pub fn bounding_box(cells: &HashSet<Cell>) -> Option<(Cell, Cell)> {
+    let mut iter = cells.iter();
+    let first = *iter.next()?;
+    Some(iter.fold((first, first), |(min, max), cell| {
+        (
+            Cell(min.0.min(cell.0), min.1.min(cell.1)),
+            Cell(max.0.max(cell.0), max.1.max(cell.1)),
+        )
+    }))
+}
+
+/// Order-independent hash of a set of cells: equal states hash equally however the set is laid out.
+pub fn state_hash(cells: &HashSet<Cell>) -> u64 {
+    cells
+        .iter()
+        .fold(0u64, |acc, &cell| acc.wrapping_add(cell_hash(cell)))
+}
+
+/// splitmix64 finalizer over the packed coordinates, summed by the state hashes.
+pub(crate) fn cell_hash(cell: Cell) -> u64 {
+    let mut z =
+        (((cell.0 as u32 as u64) << 32) | cell.1 as u32 as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
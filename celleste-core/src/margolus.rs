@@ -0,0 +1,218 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::random::Random;
+use crate::simulation::{grid_cells, CellImage, Input, Options, Simulation};
+use crate::Cell;
+
+/// A block rule as the new state of each 2x2 block, indexed by its current state with the
+/// top-left cell as bit 0, top-right bit 1, bottom-left bit 2 and bottom-right bit 3.
+type BlockRule = [u8; 16];
+
+/// Every block rule `--param rule=NAME` picks from. Each one is a permutation of the block
+/// states, so it can be run backwards.
+const RULES: &[(&str, BlockRule)] = &[
+    // Blocks of two live cells stay put, the rest are complemented, and blocks of three are
+    // also turned around
+    (
+        "critters",
+        [15, 14, 13, 3, 11, 5, 6, 1, 7, 9, 10, 2, 12, 4, 8, 0],
+    ),
+    // Empty and full blocks swap, everything else stays put
+    (
+        "tron",
+        [15, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 0],
+    ),
+    // Fredkin's billiard ball machine: lone balls fly diagonally across their block, and two
+    // meeting head-on bounce off at right angles
+    (
+        "bbm",
+        [0, 8, 4, 3, 2, 5, 9, 7, 1, 6, 10, 11, 12, 13, 14, 15],
+    ),
+];
+
+/// A block cellular automaton on a torus: the grid is cut into 2x2 blocks, each block is
+/// replaced according to the rule, and the cut shifts by one cell diagonally every
+/// generation. The rules are reversible, so the simulation can run backwards to where it
+/// started.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Margolus {
+    width: i32,
+    height: i32,
+    rule: String,
+    /// Whether steps undo generations instead of computing new ones
+    backwards: bool,
+    /// Cells row by row
+    alive: Vec<bool>,
+    generation: usize,
+}
+
+impl Margolus {
+    /// An `options.width` x `options.height` torus, rounded up to even sides, with a random
+    /// square of cells in the middle.
+    pub fn new(options: &Options) -> Result<Self, String> {
+        let rule: String = options.param("rule", "critters".to_string())?;
+        find_rule(&rule)?;
+        let width = options.width.max(2).saturating_add(1) / 2 * 2;
+        let height = options.height.max(2).saturating_add(1) / 2 * 2;
+        let mut margolus = Self {
+            width,
+            height,
+            rule: rule.to_ascii_lowercase(),
+            backwards: false,
+            alive: vec![false; grid_cells(width, height)?],
+            generation: 1,
+        };
+        let mut random = Random::new(options.seed);
+        let side = width.min(height) / 4;
+        let corner = Cell((width - side) / 2, (height - side) / 2);
+        for y in 0..side {
+            for x in 0..side {
+                let alive = random.chance(0.5);
+                margolus.set(Cell(corner.0 + x, corner.1 + y), alive);
+            }
+        }
+        Ok(margolus)
+    }
+
+    fn index(&self, cell: Cell) -> Option<usize> {
+        ((0..self.width).contains(&cell.0) && (0..self.height).contains(&cell.1))
+            .then(|| (cell.1 * self.width + cell.0) as usize)
+    }
+
+    fn set(&mut self, cell: Cell, alive: bool) -> bool {
+        match self.index(cell) {
+            Some(i) if self.alive[i] != alive => {
+                self.alive[i] = alive;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Replaces every block of the partition used by `generation` according to `rule`.
+    fn apply(&mut self, rule: &BlockRule, generation: usize) {
+        let offset = ((generation + 1) % 2) as i32;
+        for y in (offset..self.height + offset).step_by(2) {
+            for x in (offset..self.width + offset).step_by(2) {
+                let cells = [(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)]
+                    .map(|(x, y)| (y % self.height * self.width + x % self.width) as usize);
+                let state = cells
+                    .iter()
+                    .enumerate()
+                    .fold(0, |state, (bit, &i)| state | (self.alive[i] as u8) << bit);
+                let next = rule[state as usize];
+                for (bit, &i) in cells.iter().enumerate() {
+                    self.alive[i] = next >> bit & 1 == 1;
+                }
+            }
+        }
+    }
+}
+
+fn find_rule(name: &str) -> Result<&'static BlockRule, String> {
+    RULES
+        .iter()
+        .find(|(rule, _)| rule.eq_ignore_ascii_case(name))
+        .map(|(_, table)| table)
+        .ok_or_else(|| {
+            let names: Vec<&str> = RULES.iter().map(|(rule, _)| *rule).collect();
+            format!(
+                "Unknown block rule '{}' (known: {})",
+                name,
+                names.join(", ")
+            )
+        })
+}
+
+impl Simulation for Margolus {
+    fn name(&self) -> &'static str {
+        "margolus"
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Computes the next generation, or when running backwards undoes the last one by
+    /// applying the inverse rule to the previous partition; running backwards stops at the
+    /// first generation.
+    fn step(&mut self) {
+        let Ok(&rule) = find_rule(&self.rule) else {
+            return;
+        };
+        if !self.backwards {
+            self.apply(&rule, self.generation);
+            self.generation += 1;
+        } else if self.generation > 1 {
+            let mut inverse = [0; 16];
+            for (state, &next) in rule.iter().enumerate() {
+                inverse[next as usize] = state as u8;
+            }
+            self.generation -= 1;
+            self.apply(&inverse, self.generation);
+        }
+    }
+
+    fn bounds(&self) -> Option<(Cell, Cell)> {
+        Some((Cell(0, 0), Cell(self.width - 1, self.height - 1)))
+    }
+
+    fn draw(&self, image: &mut CellImage) {
+        for (i, _) in self.alive.iter().enumerate().filter(|(_, &alive)| alive) {
+            let i = i as i32;
+            image.set(Cell(i % self.width, i / self.width), [255, 255, 255, 255]);
+        }
+    }
+
+    fn occupied(&self, cell: Cell) -> bool {
+        self.index(cell).is_some_and(|i| self.alive[i])
+    }
+
+    fn handle_input(&mut self, input: Input) -> bool {
+        match input {
+            Input::Paint { cell, erase } => self.set(cell, !erase),
+            Input::Activate(_) => false,
+        }
+    }
+
+    fn status(&self) -> Option<String> {
+        let direction = if self.backwards {
+            "running backwards"
+        } else {
+            "running forwards"
+        };
+        Some(format!("{}, {}", self.rule, direction))
+    }
+
+    fn reverse(&mut self) -> Option<bool> {
+        self.backwards = !self.backwards;
+        Some(self.backwards)
+    }
+
+    fn blank(&self) -> Box<dyn Simulation> {
+        let mut blank = self.clone();
+        blank.alive.iter_mut().for_each(|alive| *alive = false);
+        blank.backwards = false;
+        blank.generation = 1;
+        Box::new(blank)
+    }
+
+    fn save(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    fn restore(&mut self, state: Value) -> Result<(), String> {
+        let restored: Self = serde_json::from_value(state)
+            .map_err(|err| format!("Invalid block automaton state: {}", err))?;
+        find_rule(&restored.rule)?;
+        if restored.width % 2 != 0
+            || restored.height % 2 != 0
+            || restored.alive.len() != grid_cells(restored.width, restored.height)?
+        {
+            return Err("Block automaton state doesn't match its size".to_string());
+        }
+        *self = restored;
+        Ok(())
+    }
+}
@@ -0,0 +1,484 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::{bounding_box, Cell};
+
+/// Longest line written to RLE files, as recommended by the format description.
+const RLE_LINE_LENGTH: usize = 70;
+
+/// Most cells a pattern file may hold, so a malformed or hostile file can't exhaust memory.
+pub const MAX_PATTERN_CELLS: usize = 5_000_000;
+
+/// File extensions `load_pattern` understands.
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["rle", "cells", "mc"];
+
+/// Cells read from a pattern file, plus the rule it was written for if the format records one.
+pub struct Pattern {
+    pub cells: HashSet<Cell>,
+    /// Decaying cells of Generations rules and their state
+    pub dying: Vec<(Cell, u8)>,
+    pub rules: Option<String>,
+    /// Whether the cells are at absolute positions (saved states) rather than relative to
+    /// the pattern's top-left corner.
+    pub absolute: bool,
+}
+
+impl Pattern {
+    /// Moves a relative pattern so its top-left corner lands on `origin`, dropping any cells
+    /// that would land past the edge of the universe.
+    pub fn place_at(&mut self, origin: Cell) {
+        if self.absolute {
+            return;
+        }
+        let shift = |cell: &Cell| cell.offset(origin.0, origin.1);
+        self.cells = self.cells.iter().filter_map(shift).collect();
+        self.dying = self
+            .dying
+            .iter()
+            .filter_map(|(cell, state)| Some((shift(cell)?, *state)))
+            .collect();
+        self.absolute = true;
+    }
+
+    /// Moves a relative pattern so the middle of its bounding box lands on `center`.
+    pub fn center_at(&mut self, center: Cell) {
+        let (min, max) = bounding_box(&self.cells).unwrap_or((Cell(0, 0), Cell(0, 0)));
+        let middle = |low: i32, high: i32| ((low as i64 + high as i64) / 2) as i32;
+        let half = Cell(middle(min.0, max.0), middle(min.1, max.1));
+        self.place_at(Cell(
+            center.0.saturating_sub(half.0),
+            center.1.saturating_sub(half.1),
+        ));
+    }
+}
+
+pub fn is_pattern_file(path: &Path) -> bool {
+    extension(path).is_some_and(|ext| SUPPORTED_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// The file's extension in lowercase, if it has one.
+pub fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+}
+
+/// Loads a pattern, picking the format from the file extension.
+pub fn load_pattern(path: &Path) -> Result<Pattern, String> {
+    if extension(path).as_deref() == Some("mc") {
+        // Macrocell files can be huge, so they're parsed straight from the reader
+        let file =
+            File::open(path).map_err(|err| format!("Failed to read pattern from file: {}", err))?;
+        return parse_macrocell(BufReader::new(file));
+    }
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read pattern from file: {}", err))?;
+    match extension(path).as_deref() {
+        Some("rle") => parse_rle(&contents),
+        Some("cells") => parse_cells(&contents),
+        Some(other) => Err(format!("Unsupported pattern format '.{}'", other)),
+        None => Err("Pattern file has no extension to tell its format by".to_string()),
+    }
+}
+
+/// Parses Run Length Encoded patterns as written by Golly and the LifeWiki.
+///
+/// The optional `x = .., y = .., rule = ..` header supplies the rule; `#` comment lines are
+/// skipped. Cells are placed with the pattern's top-left corner at the origin. In multi-state
+/// patterns `A` is a live cell and `B` onwards are the decaying states of Generations rules.
+/// Patterns running past the edge of the universe or holding more than `MAX_PATTERN_CELLS`
+/// cells are rejected.
+pub fn parse_rle(text: &str) -> Result<Pattern, String> {
+    let mut rules = None;
+    let mut body = String::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if body.is_empty() && line.starts_with('x') {
+            rules = rle_header_rule(line);
+            continue;
+        }
+        body.push_str(line);
+    }
+
+    let mut cells = HashSet::new();
+    let mut dying = Vec::new();
+    let (mut x, mut y) = (0i32, 0i32);
+    let mut count = 0i32;
+    let too_long = || "RLE run too long".to_string();
+    for c in body.chars() {
+        if let Some(digit) = c.to_digit(10) {
+            count = count
+                .checked_mul(10)
+                .and_then(|count| count.checked_add(digit as i32))
+                .ok_or_else(too_long)?;
+            continue;
+        }
+        let run = count.max(1);
+        count = 0;
+        if matches!(c, 'o' | 'A' | 'B'..='X')
+            && cells.len() + dying.len() + run as usize > MAX_PATTERN_CELLS
+        {
+            return Err(format!(
+                "RLE pattern has more than {} cells",
+                MAX_PATTERN_CELLS
+            ));
+        }
+        match c {
+            'b' | '.' => x = x.checked_add(run).ok_or_else(too_long)?,
+            'o' | 'A' => {
+                x.checked_add(run).ok_or_else(too_long)?;
+                for _ in 0..run {
+                    cells.insert(Cell(x, y));
+                    x += 1;
+                }
+            }
+            'B'..='X' => {
+                x.checked_add(run).ok_or_else(too_long)?;
+                let state = c as u8 - b'A' + 1;
+                for _ in 0..run {
+                    dying.push((Cell(x, y), state));
+                    x += 1;
+                }
+            }
+            '$' => {
+                y = y.checked_add(run).ok_or_else(too_long)?;
+                x = 0;
+            }
+            '!' => break,
+            c if c.is_whitespace() => {}
+            c => return Err(format!("Unexpected character '{}' in RLE", c)),
+        }
+    }
+    Ok(Pattern {
+        cells,
+        dying,
+        rules,
+        absolute: false,
+    })
+}
+
+/// Parses the plaintext `.cells` format used on conwaylife.com: `!` starts a comment line,
+/// `.` is a dead cell and `O` a live one. The format always means Conway's Life.
+pub fn parse_cells(text: &str) -> Result<Pattern, String> {
+    let mut cells = HashSet::new();
+    let rows = text.lines().filter(|line| !line.starts_with('!'));
+    for (y, row) in rows.enumerate() {
+        for (x, c) in row.trim_end().chars().enumerate() {
+            match c {
+                'O' | '*' => {
+                    if cells.len() == MAX_PATTERN_CELLS {
+                        return Err(format!(
+                            ".cells pattern has more than {} cells",
+                            MAX_PATTERN_CELLS
+                        ));
+                    }
+                    match (i32::try_from(x), i32::try_from(y)) {
+                        (Ok(x), Ok(y)) => cells.insert(Cell(x, y)),
+                        _ => return Err(".cells pattern is too large to load".to_string()),
+                    };
+                }
+                '.' => {}
+                c => return Err(format!("Unexpected character '{}' in .cells pattern", c)),
+            }
+        }
+    }
+    Ok(Pattern {
+        cells,
+        dying: Vec::new(),
+        rules: Some("B3/S23".to_string()),
+        absolute: false,
+    })
+}
+
+/// Parses a pattern pasted as text, in either RLE or plaintext `.cells` format.
+pub fn parse_text(text: &str) -> Result<Pattern, String> {
+    // Plaintext is the stricter format, so anything it rejects is tried as RLE. Only syntax
+    // errors mean the text isn't a pattern; RLE that's too large says so.
+    let pattern = parse_cells(text)
+        .or_else(|_| parse_rle(text))
+        .map_err(|err| {
+            if err.starts_with("Unexpected character") {
+                "Text is neither an RLE nor a plaintext pattern".to_string()
+            } else {
+                err
+            }
+        })?;
+    if pattern.cells.is_empty() && pattern.dying.is_empty() {
+        return Err("Pattern has no live cells".to_string());
+    }
+    Ok(pattern)
+}
+
+fn rle_header_rule(header: &str) -> Option<String> {
+    header.split(',').find_map(|field| {
+        let (key, value) = field.split_once('=')?;
+        (key.trim() == "rule").then(|| normalize_rule(value.trim()))
+    })
+}
+
+/// Brings the rule spellings found in the wild into the `B<number>/S<number>` form `Rules`
+/// parses: lowercase `b3/s23` and the older survival-first `23/3` and `2/3/4` (Generations)
+/// notations.
+fn normalize_rule(rule: &str) -> String {
+    let upper = rule.to_ascii_uppercase();
+    if upper.contains('B') || upper.contains('S') {
+        return upper;
+    }
+    let parts: Vec<&str> = upper.split('/').collect();
+    match parts.as_slice() {
+        [survival, birth] => format!("B{}/S{}", birth, survival),
+        [survival, birth, states] => format!("B{}/S{}/C{}", birth, survival, states),
+        _ => upper,
+    }
+}
+
+/// Encodes cells as RLE, normalized so the bounding box's top-left corner is the origin.
+pub fn to_rle(cells: &HashSet<Cell>, rules: &str) -> String {
+    let Some((min, max)) = bounding_box(cells) else {
+        return format!("x = 0, y = 0, rule = {}\n!\n", rules);
+    };
+    let mut rows: BTreeMap<i32, Vec<i32>> = BTreeMap::new();
+    for cell in cells {
+        rows.entry(cell.1).or_default().push(cell.0);
+    }
+
+    let mut tokens = Vec::new();
+    let mut y = min.1;
+    for (&row, xs) in &mut rows {
+        if row > y {
+            tokens.push(rle_run(row as i64 - y as i64, '$'));
+            y = row;
+        }
+        xs.sort_unstable();
+        // Columns are counted in i64, as a row can span the whole i32 range
+        let mut x = min.0 as i64;
+        let mut i = 0;
+        while i < xs.len() {
+            let start = xs[i] as i64;
+            while i + 1 < xs.len() && xs[i + 1] as i64 == xs[i] as i64 + 1 {
+                i += 1;
+            }
+            if start > x {
+                tokens.push(rle_run(start - x, 'b'));
+            }
+            tokens.push(rle_run(xs[i] as i64 - start + 1, 'o'));
+            x = xs[i] as i64 + 1;
+            i += 1;
+        }
+    }
+    tokens.push("!".to_string());
+
+    let mut rle = format!(
+        "x = {}, y = {}, rule = {}\n",
+        max.0 as i64 - min.0 as i64 + 1,
+        max.1 as i64 - min.1 as i64 + 1,
+        rules
+    );
+    let mut line_length = 0;
+    for token in tokens {
+        if line_length + token.len() > RLE_LINE_LENGTH {
+            rle.push('\n');
+            line_length = 0;
+        }
+        line_length += token.len();
+        rle.push_str(&token);
+    }
+    rle.push('\n');
+    rle
+}
+
+fn rle_run(length: i64, tag: char) -> String {
+    if length == 1 {
+        tag.to_string()
+    } else {
+        format!("{}{}", length, tag)
+    }
+}
+
+/// A node of a macrocell quadtree.
+enum MacrocellNode {
+    /// An 8x8 block, one bit per cell in row-major order.
+    Leaf(u64),
+    /// A 2x2 block of (multi-state) cell values, in `nw ne sw se` order.
+    Level1([u32; 4]),
+    /// A `2^level` square split into quadrants, in `nw ne sw se` order (0 is empty).
+    Branch { level: u32, children: [usize; 4] },
+}
+
+/// Parses Golly's macrocell format, reading one node line at a time.
+///
+/// Nodes are numbered from 1 in the order they appear and the last one is the root, which
+/// Golly centers on the origin. The result is normalized like RLE, so its bounding box's
+/// top-left corner is at the origin.
+pub fn parse_macrocell(reader: impl BufRead) -> Result<Pattern, String> {
+    let mut nodes = Vec::new();
+    let mut rules = None;
+    let mut lines = reader.lines();
+    match lines.next() {
+        Some(Ok(header)) if header.starts_with("[M2]") => {}
+        _ => return Err("Not a macrocell file: missing '[M2]' header".to_string()),
+    }
+    for line in lines {
+        let line = line.map_err(|err| format!("Failed to read macrocell: {}", err))?;
+        let line = line.trim();
+        if let Some(rule) = line.strip_prefix("#R") {
+            rules = Some(normalize_rule(rule.trim()));
+        } else if line.is_empty() || line.starts_with('#') {
+            continue;
+        } else if line.starts_with(['.', '*', '$']) {
+            nodes.push(parse_macrocell_leaf(line)?);
+        } else {
+            nodes.push(parse_macrocell_branch(line, nodes.len())?);
+        }
+    }
+
+    let Some(root) = nodes.len().checked_sub(1) else {
+        return Ok(Pattern {
+            cells: HashSet::new(),
+            dying: Vec::new(),
+            rules,
+            absolute: false,
+        });
+    };
+    let half = match nodes[root] {
+        MacrocellNode::Leaf(_) => 4,
+        MacrocellNode::Level1(_) => 1,
+        MacrocellNode::Branch { level, .. } => 1i64 << (level - 1),
+    };
+    // Shared subtrees can describe far more cells than the file has lines, so the total is
+    // counted before anything is expanded
+    let populations = macrocell_populations(&nodes);
+    if populations[root] > MAX_PATTERN_CELLS as u64 {
+        return Err(format!(
+            "Macrocell pattern has more than {} cells",
+            MAX_PATTERN_CELLS
+        ));
+    }
+    let mut positions = Vec::new();
+    expand_macrocell(&nodes, &populations, root, -half, -half, &mut positions);
+
+    let min_x = positions.iter().map(|&(x, _)| x).min().unwrap_or(0);
+    let min_y = positions.iter().map(|&(_, y)| y).min().unwrap_or(0);
+    let cells = positions
+        .into_iter()
+        .map(
+            |(x, y)| match (i32::try_from(x - min_x), i32::try_from(y - min_y)) {
+                (Ok(x), Ok(y)) => Ok(Cell(x, y)),
+                _ => Err("Macrocell pattern is too large to load".to_string()),
+            },
+        )
+        .collect::<Result<_, _>>()?;
+    Ok(Pattern {
+        cells,
+        dying: Vec::new(),
+        rules,
+        absolute: false,
+    })
+}
+
+fn parse_macrocell_leaf(line: &str) -> Result<MacrocellNode, String> {
+    let mut bits = 0u64;
+    let (mut x, mut y) = (0, 0);
+    for c in line.chars() {
+        match c {
+            '.' => x += 1,
+            '*' => {
+                if x >= 8 || y >= 8 {
+                    return Err(format!("Macrocell leaf larger than 8x8: '{}'", line));
+                }
+                bits |= 1 << (y * 8 + x);
+                x += 1;
+            }
+            '$' => {
+                y += 1;
+                x = 0;
+            }
+            c => return Err(format!("Unexpected character '{}' in macrocell leaf", c)),
+        }
+    }
+    Ok(MacrocellNode::Leaf(bits))
+}
+
+fn parse_macrocell_branch(line: &str, defined: usize) -> Result<MacrocellNode, String> {
+    let invalid = || format!("Invalid macrocell node: '{}'", line);
+    let numbers: Vec<usize> = line
+        .split_whitespace()
+        .map(|part| part.parse::<usize>().map_err(|_| invalid()))
+        .collect::<Result<_, _>>()?;
+    if numbers.len() != 5 {
+        return Err(invalid());
+    }
+    let level = numbers[0] as u32;
+    let children = [numbers[1], numbers[2], numbers[3], numbers[4]];
+    if level == 1 {
+        return Ok(MacrocellNode::Level1(children.map(|state| state as u32)));
+    }
+    if !(2..=62).contains(&level) || children.iter().any(|&child| child > defined) {
+        return Err(invalid());
+    }
+    Ok(MacrocellNode::Branch { level, children })
+}
+
+/// Live cells under each node, saturating rather than overflowing for huge trees.
+fn macrocell_populations(nodes: &[MacrocellNode]) -> Vec<u64> {
+    let mut populations: Vec<u64> = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        let population = match *node {
+            MacrocellNode::Leaf(bits) => bits.count_ones() as u64,
+            MacrocellNode::Level1(states) => {
+                states.iter().filter(|&&state| state != 0).count() as u64
+            }
+            MacrocellNode::Branch { children, .. } => children
+                .iter()
+                .filter(|&&child| child != 0)
+                .fold(0u64, |total, &child| {
+                    total.saturating_add(populations[child - 1])
+                }),
+        };
+        populations.push(population);
+    }
+    populations
+}
+
+fn expand_macrocell(
+    nodes: &[MacrocellNode],
+    populations: &[u64],
+    index: usize,
+    x: i64,
+    y: i64,
+    positions: &mut Vec<(i64, i64)>,
+) {
+    match nodes[index] {
+        MacrocellNode::Leaf(bits) => {
+            for bit in 0..64 {
+                if bits & (1 << bit) != 0 {
+                    positions.push((x + bit % 8, y + bit / 8));
+                }
+            }
+        }
+        MacrocellNode::Level1(states) => {
+            let offsets = [(0, 0), (1, 0), (0, 1), (1, 1)];
+            for (state, (dx, dy)) in states.into_iter().zip(offsets) {
+                if state != 0 {
+                    positions.push((x + dx, y + dy));
+                }
+            }
+        }
+        MacrocellNode::Branch { level, children } => {
+            let half = 1i64 << (level - 1);
+            let offsets = [(0, 0), (half, 0), (0, half), (half, half)];
+            for (child, (dx, dy)) in children.into_iter().zip(offsets) {
+                // Children are numbered from 1; 0 is an empty quadrant, and so is a child
+                // whose cells are all dead
+                if child != 0 && populations[child - 1] > 0 {
+                    expand_macrocell(nodes, populations, child - 1, x + dx, y + dy, positions);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,321 @@
+/// Largest Larger-than-Life neighborhood radius accepted; counting cost grows with its square.
+pub const MAX_RANGE: i32 = 50;
+
+/// Which cells within `range` of a cell count as its neighbors.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// The full square (Chebyshev distance)
+    Moore,
+    /// The diamond (Manhattan distance)
+    VonNeumann,
+    /// Hexagonal cells emulated on the square grid by skipping the top-right and bottom-left
+    /// corners, as Golly does
+    Hexagonal,
+}
+
+impl Neighborhood {
+    pub fn from_string(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "moore" => Ok(Neighborhood::Moore),
+            "vonneumann" => Ok(Neighborhood::VonNeumann),
+            "hexagonal" => Ok(Neighborhood::Hexagonal),
+            _ => Err(
+                "Invalid neighborhood. Expected 'moore', 'vonneumann' or 'hexagonal'.".to_string(),
+            ),
+        }
+    }
+
+    /// Whether the offset lies within `range` of the center.
+    pub fn contains(self, dx: i32, dy: i32, range: i32) -> bool {
+        match self {
+            Neighborhood::Moore => dx.abs().max(dy.abs()) <= range,
+            Neighborhood::VonNeumann => dx.abs() + dy.abs() <= range,
+            Neighborhood::Hexagonal => dx.abs().max(dy.abs()).max((dx - dy).abs()) <= range,
+        }
+    }
+
+    /// Number of cells within `range`, the center included.
+    pub fn size(self, range: usize) -> usize {
+        match self {
+            Neighborhood::Moore => (2 * range + 1) * (2 * range + 1),
+            Neighborhood::VonNeumann => 2 * range * (range + 1) + 1,
+            Neighborhood::Hexagonal => 3 * range * (range + 1) + 1,
+        }
+    }
+
+    /// The letter marking the neighborhood at the end of a B/S rule (`B2/S13V`).
+    pub fn suffix(self) -> Option<char> {
+        match self {
+            Neighborhood::Moore => None,
+            Neighborhood::VonNeumann => Some('V'),
+            Neighborhood::Hexagonal => Some('H'),
+        }
+    }
+}
+
+/// How cells are born, survive and decay, parsed from a rulestring.
+#[derive(Clone)]
+pub struct Rules {
+    pub birth: Vec<usize>,
+    pub survival: Vec<usize>,
+    /// Number of cell states for Generations rules: live cells that don't survive decay through
+    /// states 2..states before dying. Plain B/S rules have 2.
+    pub states: u8,
+    /// Neighborhood radius; 1 for the classic 8-cell neighborhood.
+    pub range: i32,
+    pub neighborhood: Neighborhood,
+    /// Whether a cell counts itself among its neighbors (Larger-than-Life `M1`).
+    pub include_middle: bool,
+}
+
+impl Rules {
+    /// Parses B/S notation (`B3/S23`), Generations (`B2/S/C3`), a trailing `V` or `H` for the
+    /// von Neumann or hexagonal neighborhood, Larger-than-Life notation
+    /// (`R5,C0,M1,S34..58,B34..45,NM`), or the name of a preset such as `highlife`.
+    pub fn from_string(rule_str: &str) -> Result<Self, String> {
+        let rule_str = rule_preset(rule_str).unwrap_or(rule_str);
+        if rule_str.starts_with('R') && rule_str.contains(',') {
+            return Self::from_ltl(rule_str)?.validated();
+        }
+        let (rule_str, neighborhood) = match rule_str.chars().last() {
+            Some('V') => (&rule_str[..rule_str.len() - 1], Neighborhood::VonNeumann),
+            Some('H') => (&rule_str[..rule_str.len() - 1], Neighborhood::Hexagonal),
+            _ => (rule_str, Neighborhood::Moore),
+        };
+        let parts: Vec<&str> = rule_str.split('/').collect();
+        if !(2..=3).contains(&parts.len())
+            || !parts[0].starts_with('B')
+            || !parts[1].starts_with('S')
+        {
+            return Err(
+                "Invalid rule format. Expected 'B<number>/S<number>' or 'B<number>/S<number>/C<states>'."
+                    .to_string(),
+            );
+        }
+        let states = match parts.get(2) {
+            Some(part) => match part.strip_prefix('C').and_then(|n| n.parse::<u8>().ok()) {
+                Some(states) if states >= 2 => states,
+                _ => {
+                    return Err(
+                        "Invalid state count. Expected 'C<states>' with at least 2 states."
+                            .to_string(),
+                    )
+                }
+            },
+            None => 2,
+        };
+        let birth = parts[0][1..]
+            .chars()
+            .filter_map(|c| c.to_digit(10))
+            .map(|d| d as usize)
+            .collect();
+
+        let survival = parts[1][1..]
+            .chars()
+            .filter_map(|c| c.to_digit(10))
+            .map(|d| d as usize)
+            .collect();
+
+        Self {
+            birth,
+            survival,
+            states,
+            range: 1,
+            neighborhood,
+            include_middle: false,
+        }
+        .validated()
+    }
+
+    /// Parses Larger-than-Life notation, e.g. Bosco's rule `R5,C0,M1,S34..58,B34..45,NM`.
+    fn from_ltl(rule_str: &str) -> Result<Self, String> {
+        const USAGE: &str =
+            "Invalid Larger-than-Life rule. Expected 'R<range>,C<states>,M<0|1>,S<min>..<max>,B<min>..<max>,N<M|N|H>'.";
+        let mut rules = Self {
+            birth: Vec::new(),
+            survival: Vec::new(),
+            states: 2,
+            range: 1,
+            neighborhood: Neighborhood::Moore,
+            include_middle: false,
+        };
+        // Counts are only filled in once the range they have to fit in is known
+        let (mut survival, mut birth) = (None, None);
+        for part in rule_str.split(',').map(str::trim) {
+            let (key, value) = part.split_at(part.chars().next().map_or(0, char::len_utf8));
+            match key {
+                "R" => match value.parse::<i32>() {
+                    Ok(range) if (1..=MAX_RANGE).contains(&range) => rules.range = range,
+                    _ => return Err(format!("Invalid range. Expected 1 to {}.", MAX_RANGE)),
+                },
+                // C0 and C1 both mean plain two-state life
+                "C" => rules.states = value.parse::<u8>().map_err(|_| USAGE.to_string())?.max(2),
+                "M" => rules.include_middle = value == "1",
+                "S" => survival = Some(parse_count_range(value).ok_or(USAGE)?),
+                "B" => birth = Some(parse_count_range(value).ok_or(USAGE)?),
+                "N" => {
+                    rules.neighborhood = match value {
+                        "M" => Neighborhood::Moore,
+                        "N" => Neighborhood::VonNeumann,
+                        "H" => Neighborhood::Hexagonal,
+                        _ => return Err(USAGE.to_string()),
+                    }
+                }
+                _ => return Err(USAGE.to_string()),
+            }
+        }
+        let cells = (2 * rules.range as usize + 1).pow(2);
+        if [survival, birth]
+            .iter()
+            .flatten()
+            .any(|&(_, max)| max > cells)
+        {
+            return Err(format!(
+                "Neighbor counts can't be above {} at range {}",
+                cells, rules.range
+            ));
+        }
+        let counts = |range: Option<(usize, usize)>| {
+            range.map_or_else(Vec::new, |(min, max)| (min..=max).collect())
+        };
+        rules.survival = counts(survival);
+        rules.birth = counts(birth);
+        Ok(rules)
+    }
+
+    /// Swaps in another neighborhood, checking the counts still fit in it.
+    pub fn with_neighborhood(mut self, neighborhood: Neighborhood) -> Result<Self, String> {
+        self.neighborhood = neighborhood;
+        self.validated()
+    }
+
+    /// Rejects birth/survival counts the neighborhood can never reach.
+    fn validated(self) -> Result<Self, String> {
+        let max = self.max_neighbors();
+        let impossible = self
+            .birth
+            .iter()
+            .chain(&self.survival)
+            .find(|&&count| count > max);
+        match impossible {
+            Some(count) => Err(format!(
+                "Neighbor count {} is impossible in a neighborhood of {} cells.",
+                count, max
+            )),
+            None => Ok(self),
+        }
+    }
+
+    /// Whether the rule needs Larger-than-Life notation to be written down.
+    pub fn is_ltl(&self) -> bool {
+        self.range > 1 || self.include_middle
+    }
+
+    /// Whether these are plain two-state rules on the 8-cell neighborhood, like B3/S23.
+    pub fn is_life_like(&self) -> bool {
+        !self.is_ltl() && self.states == 2 && self.neighborhood == Neighborhood::Moore
+    }
+
+    /// The most neighbors a cell can have, counting itself under `M1`.
+    pub fn max_neighbors(&self) -> usize {
+        let size = self.neighborhood.size(self.range as usize);
+        if self.include_middle {
+            size
+        } else {
+            size - 1
+        }
+    }
+
+    /// The rulestring `from_string` reads back as these rules.
+    pub fn as_string(&self) -> String {
+        if self.is_ltl() {
+            return format!(
+                "R{},C{},M{},S{},B{},N{}",
+                self.range,
+                if self.states > 2 { self.states } else { 0 },
+                self.include_middle as u8,
+                count_range_string(&self.survival),
+                count_range_string(&self.birth),
+                match self.neighborhood {
+                    Neighborhood::Moore => 'M',
+                    Neighborhood::VonNeumann => 'N',
+                    Neighborhood::Hexagonal => 'H',
+                }
+            );
+        }
+        let rule_str = format!(
+            "B{}/S{}",
+            self.birth.iter().map(|b| b.to_string()).collect::<String>(),
+            self.survival
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<String>()
+        );
+        let rule_str = if self.states > 2 {
+            format!("{}/C{}", rule_str, self.states)
+        } else {
+            rule_str
+        };
+        match self.neighborhood.suffix() {
+            Some(suffix) => format!("{}{}", rule_str, suffix),
+            None => rule_str,
+        }
+    }
+}
+
+/// Parses an inclusive `<min>..<max>` neighbor count range (a bare number is a range of one)
+/// into its two ends.
+fn parse_count_range(range_str: &str) -> Option<(usize, usize)> {
+    let (min, max) = range_str.split_once("..").unwrap_or((range_str, range_str));
+    let min = min.parse::<usize>().ok()?;
+    let max = max.parse::<usize>().ok()?;
+    (min <= max).then_some((min, max))
+}
+
+/// Writes neighbor counts back as a `<min>..<max>` range.
+fn count_range_string(counts: &[usize]) -> String {
+    match (counts.iter().min(), counts.iter().max()) {
+        (Some(min), Some(max)) => format!("{}..{}", min, max),
+        _ => String::new(),
+    }
+}
+
+/// Named rules `Rules::from_string` accepts in place of a rulestring: name, rule and a short description.
+pub const RULE_PRESETS: &[(&str, &str, &str)] = &[
+    ("life", "B3/S23", "Conway's Game of Life"),
+    (
+        "highlife",
+        "B36/S23",
+        "Life plus a small self-replicating pattern",
+    ),
+    ("seeds", "B2/S", "Nothing survives; sparse patterns explode"),
+    (
+        "daynight",
+        "B3678/S34678",
+        "Live and dead cells behave symmetrically",
+    ),
+    ("maze", "B3/S12345", "Grows into maze-like corridors"),
+    (
+        "replicator",
+        "B1357/S1357",
+        "Every pattern eventually copies itself",
+    ),
+    ("2x2", "B36/S125", "Blocks of 2x2 cells act as larger cells"),
+];
+
+/// The rulestring of a named preset, ignoring case.
+pub fn rule_preset(name: &str) -> Option<&'static str> {
+    RULE_PRESETS
+        .iter()
+        .find(|(preset, _, _)| preset.eq_ignore_ascii_case(name))
+        .map(|&(_, rule, _)| rule)
+}
+
+/// One line per preset, e.g. for listing them on the command line.
+pub fn rule_catalog() -> String {
+    RULE_PRESETS
+        .iter()
+        .map(|(name, rule, description)| format!("{:<12}{:<16}{}", name, rule, description))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,231 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::random::Random;
+use crate::simulation::{grid_cells, CellImage, Input, Options, Simulation};
+use crate::Cell;
+
+const EMPTY: u8 = 0;
+const SAND: u8 = 1;
+const WATER: u8 = 2;
+const WALL: u8 = 3;
+const FIRE: u8 = 4;
+
+/// Elements painting can place, in the order adjusting cycles through them.
+const ELEMENTS: [(u8, &str); 4] = [
+    (SAND, "sand"),
+    (WATER, "water"),
+    (WALL, "wall"),
+    (FIRE, "fire"),
+];
+/// Chance per step of a flame burning out.
+const BURN_OUT: f64 = 0.15;
+/// Chance per step of sliding sideways or diagonally when a move is open, which keeps piles
+/// and pools from growing in the block grid's pattern.
+const SLIDE: f64 = 0.5;
+
+/// A falling-sand toy on a bounded grid, updated in Margolus 2x2 blocks whose grid shifts by
+/// one cell every step: sand falls and piles up, water falls and spreads sideways, walls stay
+/// put, and fire flickers upwards until it burns out or meets water.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FallingSand {
+    width: i32,
+    height: i32,
+    /// Element painting places
+    selected: usize,
+    /// Elements row by row
+    cells: Vec<u8>,
+    random: Random,
+    generation: usize,
+}
+
+/// How heavy an element is: heavier ones sink through lighter ones, and walls never move.
+fn weight(element: u8) -> u8 {
+    match element {
+        SAND => 2,
+        WATER => 1,
+        WALL => u8::MAX,
+        _ => 0,
+    }
+}
+
+fn falls(element: u8) -> bool {
+    matches!(element, SAND | WATER)
+}
+
+impl FallingSand {
+    pub fn new(options: &Options) -> Result<Self, String> {
+        let (width, height) = (options.width.max(1), options.height.max(1));
+        Ok(Self {
+            width,
+            height,
+            selected: 0,
+            cells: vec![EMPTY; grid_cells(width, height)?],
+            random: Random::new(options.seed),
+            generation: 1,
+        })
+    }
+
+    fn index(&self, cell: Cell) -> Option<usize> {
+        ((0..self.width).contains(&cell.0) && (0..self.height).contains(&cell.1))
+            .then(|| (cell.1 * self.width + cell.0) as usize)
+    }
+
+    fn set(&mut self, cell: Cell, element: u8) -> bool {
+        match self.index(cell) {
+            Some(i) if self.cells[i] != element => {
+                self.cells[i] = element;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Updates one 2x2 block, given as the indices of its top-left, top-right, bottom-left
+    /// and bottom-right cells.
+    fn update_block(&mut self, block: [usize; 4]) {
+        let [top_left, top_right, bottom_left, bottom_right] = block;
+        // Flames burn out, are put out by water, and otherwise flicker upwards
+        let wet = block.iter().any(|&i| self.cells[i] == WATER);
+        for i in block {
+            if self.cells[i] == FIRE && (wet || self.random.chance(BURN_OUT)) {
+                self.cells[i] = EMPTY;
+            }
+        }
+        for (top, bottom) in [(top_left, bottom_left), (top_right, bottom_right)] {
+            if self.cells[bottom] == FIRE && self.cells[top] == EMPTY {
+                self.cells.swap(top, bottom);
+            }
+        }
+        // Falling straight down, sinking through anything lighter
+        let mut fell = false;
+        for (top, bottom) in [(top_left, bottom_left), (top_right, bottom_right)] {
+            let (above, below) = (self.cells[top], self.cells[bottom]);
+            if falls(above) && weight(above) > weight(below) {
+                self.cells.swap(top, bottom);
+                fell = true;
+            }
+        }
+        if fell {
+            return;
+        }
+        // Sliding off the top of a pile, diagonally down
+        for (top, across, below) in [
+            (top_left, bottom_right, bottom_left),
+            (top_right, bottom_left, bottom_right),
+        ] {
+            let element = self.cells[top];
+            if falls(element)
+                && weight(self.cells[across]) < weight(element)
+                && weight(self.cells[below]) >= weight(element)
+                && self.random.chance(SLIDE)
+            {
+                self.cells.swap(top, across);
+                return;
+            }
+        }
+        // Water that can't fall spreads sideways
+        for (left, right) in [(top_left, top_right), (bottom_left, bottom_right)] {
+            let (a, b) = (self.cells[left], self.cells[right]);
+            if (a == WATER && b == EMPTY || a == EMPTY && b == WATER) && self.random.chance(SLIDE) {
+                self.cells.swap(left, right);
+            }
+        }
+    }
+}
+
+impl Simulation for FallingSand {
+    fn name(&self) -> &'static str {
+        "sand"
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Updates every block of the grid that starts on an even cell one step, and on an odd
+    /// cell the next, so elements cross from block to block. Cells beyond the edges act as
+    /// walls.
+    fn step(&mut self) {
+        let offset = (self.generation % 2) as i32;
+        for y in (offset..self.height - 1).step_by(2) {
+            for x in (offset..self.width - 1).step_by(2) {
+                let i = (y * self.width + x) as usize;
+                let below = i + self.width as usize;
+                self.update_block([i, i + 1, below, below + 1]);
+            }
+        }
+        self.generation += 1;
+    }
+
+    fn bounds(&self) -> Option<(Cell, Cell)> {
+        Some((Cell(0, 0), Cell(self.width - 1, self.height - 1)))
+    }
+
+    /// Sand, water and walls in flat colors, and flames flickering between orange and yellow.
+    fn draw(&self, image: &mut CellImage) {
+        for (i, &element) in self.cells.iter().enumerate() {
+            let color = match element {
+                SAND => [220, 190, 100, 255],
+                WATER => [40, 100, 230, 255],
+                WALL => [120, 120, 120, 255],
+                FIRE if (i + self.generation).is_multiple_of(3) => [255, 210, 60, 255],
+                FIRE => [255, 90, 20, 255],
+                _ => continue,
+            };
+            let i = i as i32;
+            image.set(Cell(i % self.width, i / self.width), color);
+        }
+    }
+
+    fn occupied(&self, cell: Cell) -> bool {
+        self.index(cell).is_some_and(|i| self.cells[i] != EMPTY)
+    }
+
+    /// Painting places the selected element and erasing clears cells.
+    fn handle_input(&mut self, input: Input) -> bool {
+        match input {
+            Input::Paint { cell, erase: false } => self.set(cell, ELEMENTS[self.selected].0),
+            Input::Paint { cell, erase: true } => self.set(cell, EMPTY),
+            Input::Activate(_) => false,
+        }
+    }
+
+    fn status(&self) -> Option<String> {
+        Some(format!("Painting: {}", ELEMENTS[self.selected].1))
+    }
+
+    /// Selects the next (or previous) element to paint.
+    fn adjust(&mut self, up: bool) -> Option<String> {
+        let count = ELEMENTS.len();
+        self.selected = if up {
+            (self.selected + 1) % count
+        } else {
+            (self.selected + count - 1) % count
+        };
+        Some(format!("Painting {}", ELEMENTS[self.selected].1))
+    }
+
+    fn blank(&self) -> Box<dyn Simulation> {
+        let mut blank = self.clone();
+        blank.cells.iter_mut().for_each(|element| *element = EMPTY);
+        blank.generation = 1;
+        Box::new(blank)
+    }
+
+    fn save(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    fn restore(&mut self, state: Value) -> Result<(), String> {
+        let restored: Self = serde_json::from_value(state)
+            .map_err(|err| format!("Invalid falling sand state: {}", err))?;
+        if restored.cells.len() != grid_cells(restored.width, restored.height)?
+            || restored.selected >= ELEMENTS.len()
+        {
+            return Err("Falling sand state doesn't match its size".to_string());
+        }
+        *self = restored;
+        Ok(())
+    }
+}
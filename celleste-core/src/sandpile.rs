@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::simulation::{grid_cells, CellImage, Input, Options, Simulation};
+use crate::Cell;
+
+/// Grains a cell holds before it topples, sending one to each orthogonal neighbor.
+const TOPPLE_AT: u32 = 4;
+/// Colors of stable cells by grain count, and of cells about to topple.
+const COLORS: [[u8; 4]; 4] = [
+    [0, 0, 0, 255],
+    [40, 80, 200, 255],
+    [240, 200, 40, 255],
+    [200, 40, 40, 255],
+];
+const UNSTABLE_COLOR: [u8; 4] = [255, 255, 255, 255];
+
+/// The Bak–Tang–Wiesenfeld abelian sandpile on a bounded grid: every cell holding four or
+/// more grains gives one to each neighbor per step, and grains toppling off the edge are lost.
+/// Dropping grains on the middle every step grows the well-known fractal.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Sandpile {
+    width: i32,
+    height: i32,
+    /// Grains dropped on the middle cell before each step
+    drop: u32,
+    /// Grains a single activation piles up
+    pile: u32,
+    /// Grain counts row by row
+    grains: Vec<u32>,
+    generation: usize,
+}
+
+impl Sandpile {
+    /// An empty `options.width` x `options.height` grid, dropping `--param drop=N` grains on
+    /// its middle every step.
+    pub fn new(options: &Options) -> Result<Self, String> {
+        let (width, height) = (options.width.max(1), options.height.max(1));
+        Ok(Self {
+            width,
+            height,
+            drop: options.param("drop", 0)?,
+            pile: options.param("pile", 10_000)?,
+            grains: vec![0; grid_cells(width, height)?],
+            generation: 1,
+        })
+    }
+
+    fn index(&self, cell: Cell) -> Option<usize> {
+        ((0..self.width).contains(&cell.0) && (0..self.height).contains(&cell.1))
+            .then(|| (cell.1 * self.width + cell.0) as usize)
+    }
+
+    fn add(&mut self, cell: Cell, grains: u32) -> bool {
+        match self.index(cell) {
+            Some(i) if grains > 0 => {
+                self.grains[i] = self.grains[i].saturating_add(grains);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Simulation for Sandpile {
+    fn name(&self) -> &'static str {
+        "sandpile"
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Topples every unstable cell at once. Tall piles give away all the grains they can in
+    /// one go, which ends up the same as toppling them one at a time.
+    fn step(&mut self) {
+        self.add(Cell(self.width / 2, self.height / 2), self.drop);
+        let mut next = self.grains.clone();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let i = (y * self.width + x) as usize;
+                let given = self.grains[i] / TOPPLE_AT;
+                if given == 0 {
+                    continue;
+                }
+                next[i] -= given * TOPPLE_AT;
+                for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                    if let Some(j) = self.index(Cell(x + dx, y + dy)) {
+                        next[j] = next[j].saturating_add(given);
+                    }
+                }
+            }
+        }
+        self.grains = next;
+        self.generation += 1;
+    }
+
+    fn bounds(&self) -> Option<(Cell, Cell)> {
+        Some((Cell(0, 0), Cell(self.width - 1, self.height - 1)))
+    }
+
+    fn draw(&self, image: &mut CellImage) {
+        for (i, &grains) in self.grains.iter().enumerate() {
+            let color = COLORS.get(grains as usize).unwrap_or(&UNSTABLE_COLOR);
+            let i = i as i32;
+            image.set(Cell(i % self.width, i / self.width), *color);
+        }
+    }
+
+    /// Strokes always add grains rather than erasing; clearing empties the grid.
+    fn occupied(&self, _cell: Cell) -> bool {
+        false
+    }
+
+    /// Painting adds a grain to each cell it passes over, and activating piles up `pile`
+    /// grains on one cell.
+    fn handle_input(&mut self, input: Input) -> bool {
+        match input {
+            Input::Paint { cell, erase: true } => match self.index(cell) {
+                Some(i) if self.grains[i] > 0 => {
+                    self.grains[i] = 0;
+                    true
+                }
+                _ => false,
+            },
+            Input::Paint { cell, erase: false } => self.add(cell, 1),
+            Input::Activate(cell) => self.add(cell, self.pile),
+        }
+    }
+
+    fn status(&self) -> Option<String> {
+        let grains: u64 = self.grains.iter().map(|&grains| grains as u64).sum();
+        let unstable = self
+            .grains
+            .iter()
+            .filter(|&&grains| grains >= TOPPLE_AT)
+            .count();
+        Some(format!("Grains: {}, toppling: {}", grains, unstable))
+    }
+
+    fn blank(&self) -> Box<dyn Simulation> {
+        let mut blank = self.clone();
+        blank.grains.iter_mut().for_each(|grains| *grains = 0);
+        blank.generation = 1;
+        Box::new(blank)
+    }
+
+    fn save(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    fn restore(&mut self, state: Value) -> Result<(), String> {
+        let restored: Self = serde_json::from_value(state)
+            .map_err(|err| format!("Invalid sandpile state: {}", err))?;
+        if restored.grains.len() != grid_cells(restored.width, restored.height)? {
+            return Err("Sandpile state doesn't match its size".to_string());
+        }
+        *self = restored;
+        Ok(())
+    }
+}
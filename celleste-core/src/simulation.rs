@@ -0,0 +1,556 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use crate::{
+    battle::Battle, bz::Bz, epidemic::Epidemic, ising::Ising, layers::Layers, lenia::Lenia,
+    margolus::Margolus, sand::FallingSand, sandpile::Sandpile, smoothlife::SmoothLife,
+    wator::WaTor, wireworld::WireWorld, Cell, Grid, Rules, Torus,
+};
+
+/// Something the user did to a simulation's cells.
+#[derive(Clone, Copy, Debug)]
+pub enum Input {
+    /// A brush stroke passing over `cell`, filling it in or, with `erase`, clearing it
+    Paint { cell: Cell, erase: bool },
+    /// A single click on `cell` for the simulation's own action, e.g. starting an electron on
+    /// a WireWorld wire
+    Activate(Cell),
+}
+
+/// Colors for a rectangle of cells, one RGBA pixel per cell, row by row.
+pub struct CellImage {
+    /// The cell in the top-left corner
+    pub origin: Cell,
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl CellImage {
+    /// An image of `width` x `height` cells from `origin`, cleared to opaque black.
+    pub fn new(origin: Cell, width: usize, height: usize) -> Self {
+        let mut pixels = vec![0; width * height * 4];
+        for alpha in pixels.iter_mut().skip(3).step_by(4) {
+            *alpha = 255;
+        }
+        Self {
+            origin,
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    fn index(&self, cell: Cell) -> Option<usize> {
+        let x = usize::try_from(cell.0 - self.origin.0).ok()?;
+        let y = usize::try_from(cell.1 - self.origin.1).ok()?;
+        (x < self.width && y < self.height).then_some((y * self.width + x) * 4)
+    }
+
+    /// Colors `cell`; cells outside the image are ignored.
+    pub fn set(&mut self, cell: Cell, color: [u8; 4]) {
+        if let Some(i) = self.index(cell) {
+            self.pixels[i..i + 4].copy_from_slice(&color);
+        }
+    }
+
+    pub fn get(&self, cell: Cell) -> Option<[u8; 4]> {
+        let i = self.index(cell)?;
+        Some([
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ])
+    }
+}
+
+/// One of the populations a simulation charts, such as a species or a compartment.
+pub struct Population {
+    pub name: &'static str,
+    pub count: usize,
+    /// Its line's color in the graph
+    pub color: [u8; 4],
+}
+
+/// An automaton the frontend can run, draw and edit without knowing how it works. Adding a
+/// new one takes an implementation of this trait and an entry in `SIMULATIONS`.
+pub trait Simulation: Send {
+    /// The name `--model` selects it by.
+    fn name(&self) -> &'static str;
+
+    /// Number of the current generation; the starting state is generation 1.
+    fn generation(&self) -> usize;
+
+    /// Advances one generation.
+    fn step(&mut self);
+
+    /// The smallest and largest cell of a bounded simulation, or `None` if it's unbounded.
+    fn bounds(&self) -> Option<(Cell, Cell)>;
+
+    /// Colors the cells `image` covers, on its black background.
+    fn draw(&self, image: &mut CellImage);
+
+    /// Whether `cell` holds anything, which decides if a stroke starting on it erases.
+    fn occupied(&self, cell: Cell) -> bool;
+
+    /// Applies an edit, returning whether it changed anything.
+    fn handle_input(&mut self, input: Input) -> bool;
+
+    /// A line for the HUD, e.g. counts or parameters, if the simulation has anything to say.
+    fn status(&self) -> Option<String> {
+        None
+    }
+
+    /// Turns the simulation's adjustable setting, such as the element painting places, one
+    /// notch up (or down), describing its new value; `None` if it has nothing to adjust.
+    fn adjust(&mut self, _up: bool) -> Option<String> {
+        None
+    }
+
+    /// Flips which way time runs, returning whether steps now run backwards; `None` if the
+    /// simulation's rule can't be undone.
+    fn reverse(&mut self) -> Option<bool> {
+        None
+    }
+
+    /// How the simulation ended once it has run its course, e.g. who won a battle; running
+    /// pauses when there is one.
+    fn outcome(&self) -> Option<String> {
+        None
+    }
+
+    /// Shows or hides layer `layer`, describing what it did; `None` if there's no such layer.
+    fn toggle_layer(&mut self, _layer: usize) -> Option<String> {
+        None
+    }
+
+    /// Current sizes of the populations to chart over time; empty if there's nothing to chart.
+    fn populations(&self) -> Vec<Population> {
+        Vec::new()
+    }
+
+    /// An empty simulation with the same size and settings.
+    fn blank(&self) -> Box<dyn Simulation>;
+
+    /// The whole state, for saves.
+    fn save(&self) -> Value;
+
+    /// Brings back a state `save` wrote.
+    fn restore(&mut self, state: Value) -> Result<(), String>;
+
+    /// The Life grid underneath, for frontends that step, analyse and record Life grids
+    /// themselves; `None` for every other simulation.
+    fn grid(&self) -> Option<&Grid> {
+        None
+    }
+}
+
+/// Most cells a bounded simulation's grid may have, e.g. 8192x8192.
+pub const MAX_GRID_CELLS: usize = 1 << 26;
+
+/// Cells in a `width` x `height` grid, or an error if a side isn't positive or the grid is
+/// too large to index with `i32`s or to hold in memory.
+pub fn grid_cells(width: i32, height: i32) -> Result<usize, String> {
+    if width <= 0 || height <= 0 {
+        return Err(format!("A {}x{} grid has no cells", width, height));
+    }
+    width
+        .checked_mul(height)
+        .map(|cells| cells as usize)
+        .filter(|&cells| cells <= MAX_GRID_CELLS)
+        .ok_or_else(|| {
+            format!(
+                "A {}x{} grid is too large (at most {} cells)",
+                width, height, MAX_GRID_CELLS
+            )
+        })
+}
+
+/// Settings simulations are created from; each takes the ones that apply to it.
+#[derive(Clone)]
+pub struct Options {
+    /// Size of bounded simulations, in cells
+    pub width: i32,
+    pub height: i32,
+    pub rules: Rules,
+    /// Torus for simulations that are otherwise unbounded, from `--torus`
+    pub torus: Option<Torus>,
+    /// Simulation-specific settings by name, from `--param NAME=VALUE`
+    pub params: HashMap<String, String>,
+    /// Seed for stochastic simulations
+    pub seed: u64,
+}
+
+impl Options {
+    /// The parameter called `name`, or `default` if it wasn't given.
+    pub fn param<T: FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        match self.params.get(name) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| format!("Invalid value '{}' for parameter {}", value, name)),
+            None => Ok(default),
+        }
+    }
+}
+
+/// A simulation `create` can make by name.
+pub struct Registration {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Parameters it reads from `Options::params`, with what each one sets
+    pub params: &'static [(&'static str, &'static str)],
+    pub create: fn(&Options) -> Result<Box<dyn Simulation>, String>,
+}
+
+/// Every simulation that can be run by name.
+pub const SIMULATIONS: &[Registration] = &[
+    Registration {
+        name: "life",
+        description: "Life-like, Generations and Larger-than-Life rules on an unbounded grid",
+        params: &[],
+        create: |options| {
+            let mut grid = Grid::new(HashSet::new(), options.rules.clone());
+            grid.torus = options.torus;
+            Ok(Box::new(grid))
+        },
+    },
+    Registration {
+        name: "wireworld",
+        description: "Electrons running along hand-drawn wires",
+        params: &[],
+        create: |options| Ok(Box::new(WireWorld::new(options.width, options.height)?)),
+    },
+    Registration {
+        name: "smoothlife",
+        description: "Rafler's continuous Life, with disk neighborhoods and smooth transitions",
+        params: &[
+            (
+                "inner",
+                "Radius of the inner disk each cell reads its own state from (4)",
+            ),
+            (
+                "outer",
+                "Radius of the ring around it the neighborhood is read from (12; at most half the shorter side)",
+            ),
+            (
+                "birth1",
+                "Neighborhood filling at which dead space comes alive, from (0.278)",
+            ),
+            ("birth2", "... up to (0.365)"),
+            (
+                "death1",
+                "Neighborhood filling that living cells survive in, from (0.267)",
+            ),
+            ("death2", "... up to (0.445)"),
+            ("dt", "Time step; 1 jumps straight to the next state (1)"),
+        ],
+        create: |options| Ok(Box::new(SmoothLife::new(options)?)),
+    },
+    Registration {
+        name: "lenia",
+        description: "Bert Chan's continuous automaton of smooth, gliding lifeforms",
+        params: &[
+            (
+                "creature",
+                "Lifeform to start from and drop, or none (orbium)",
+            ),
+            ("radius", "Radius of the kernel's ring (the creature's; at most half the shorter side)"),
+            ("time_scale", "Steps per unit of time (the creature's)"),
+            (
+                "mu",
+                "Neighborhood sum that grows cells the most (the creature's)",
+            ),
+            (
+                "sigma",
+                "Width of the range of sums that grow cells (the creature's)",
+            ),
+        ],
+        create: |options| Ok(Box::new(Lenia::new(options)?)),
+    },
+    Registration {
+        name: "bz",
+        description: "The Belousov-Zhabotinsky reaction: three chemicals eating each other in spirals",
+        params: &[
+            ("alpha", "Rate at which A eats B (1)"),
+            ("beta", "Rate at which B eats C (1)"),
+            ("gamma", "Rate at which C eats A (1)"),
+        ],
+        create: |options| Ok(Box::new(Bz::new(options)?)),
+    },
+    Registration {
+        name: "sandpile",
+        description: "The abelian sandpile: cells with four grains topple onto their neighbors",
+        params: &[
+            (
+                "drop",
+                "Grains dropped on the middle every step, growing the fractal (0)",
+            ),
+            (
+                "pile",
+                "Grains Shift+Right Click piles up on a cell (10000)",
+            ),
+        ],
+        create: |options| Ok(Box::new(Sandpile::new(options)?)),
+    },
+    Registration {
+        name: "sir",
+        description: "A stochastic epidemic spreading between neighbors, charted as it goes",
+        params: &[
+            (
+                "infection",
+                "Chance per step of catching it from each infected neighbor (0.2)",
+            ),
+            (
+                "recovery",
+                "Chance per step of an infected cell recovering (0.1)",
+            ),
+            (
+                "immunity_loss",
+                "Chance per step of a recovered cell becoming susceptible (0)",
+            ),
+        ],
+        create: |options| Ok(Box::new(Epidemic::new(options)?)),
+    },
+    Registration {
+        name: "wator",
+        description: "Dewdney's Wa-Tor: fish and the sharks that hunt them on a toroidal sea",
+        params: &[
+            (
+                "fish",
+                "Fraction of the sea stocked with fish at the start (0.3)",
+            ),
+            (
+                "sharks",
+                "Fraction of the sea stocked with sharks at the start (0.03)",
+            ),
+            ("fish_breed", "Generations a fish takes to breed (3)"),
+            ("shark_breed", "Generations a shark takes to breed (10)"),
+            (
+                "shark_starve",
+                "Generations a shark survives without eating (3)",
+            ),
+        ],
+        create: |options| Ok(Box::new(WaTor::new(options)?)),
+    },
+    Registration {
+        name: "sand",
+        description: "A falling-sand toy of sand, water, walls and fire",
+        params: &[],
+        create: |options| Ok(Box::new(FallingSand::new(options)?)),
+    },
+    Registration {
+        name: "margolus",
+        description: "Reversible block automata updating alternating 2x2 partitions",
+        params: &[(
+            "rule",
+            "Block rule: critters, tron or bbm, the billiard ball machine (critters)",
+        )],
+        create: |options| Ok(Box::new(Margolus::new(options)?)),
+    },
+    Registration {
+        name: "ising",
+        description: "The 2D Ising model of ferromagnetic spins, with an adjustable temperature",
+        params: &[
+            (
+                "temperature",
+                "Starting temperature; spins order below about 2.27 (2.27)",
+            ),
+            (
+                "dynamics",
+                "How spins flip: metropolis or glauber (metropolis)",
+            ),
+        ],
+        create: |options| Ok(Box::new(Ising::new(options)?)),
+    },
+    Registration {
+        name: "battle",
+        description: "Two players seed the halves of a Life field and the most survivors win",
+        params: &[
+            (
+                "generations",
+                "Generations the battle runs before it's scored (500)",
+            ),
+            (
+                "soup",
+                "Fraction of each half seeded at random at the start (0)",
+            ),
+        ],
+        create: |options| Ok(Box::new(Battle::new(options)?)),
+    },
+    Registration {
+        name: "layers",
+        description: "Two stacked Life-like layers, with layer A steering where layer B grows",
+        params: &[
+            ("rule_a", "Layer A's rule (the --rules)"),
+            ("rule_b", "Layer B's rule (B3/S23)"),
+            (
+                "coupling",
+                "How A's cells sway B's births: none, require, inhibit or count (inhibit)",
+            ),
+            (
+                "soup",
+                "Fraction of each layer filled at random at the start (0.25)",
+            ),
+        ],
+        create: |options| Ok(Box::new(Layers::new(options)?)),
+    },
+];
+
+/// The registered simulation called `name`.
+pub fn find(name: &str) -> Option<&'static Registration> {
+    SIMULATIONS
+        .iter()
+        .find(|registration| registration.name.eq_ignore_ascii_case(name))
+}
+
+/// Creates the simulation called `name`, rejecting parameters it doesn't take.
+pub fn create(name: &str, options: &Options) -> Result<Box<dyn Simulation>, String> {
+    let registration = find(name).ok_or_else(|| {
+        let names: Vec<&str> = SIMULATIONS.iter().map(|sim| sim.name).collect();
+        format!(
+            "Unknown simulation '{}' (known: {})",
+            name,
+            names.join(", ")
+        )
+    })?;
+    for param in options.params.keys() {
+        if !registration.params.iter().any(|(known, _)| known == param) {
+            return Err(format!(
+                "{} has no parameter '{}' (see --list-models)",
+                registration.name, param
+            ));
+        }
+    }
+    (registration.create)(options)
+}
+
+/// How a `Grid` saves itself.
+#[derive(Deserialize)]
+struct GridState {
+    cells: HashSet<Cell>,
+    #[serde(default)]
+    dying: Vec<(Cell, u8)>,
+    rules: String,
+    generation: usize,
+    /// Width and height of the torus, if the grid is one
+    #[serde(default)]
+    torus: Option<(i32, i32)>,
+}
+
+impl Simulation for Grid {
+    fn name(&self) -> &'static str {
+        "life"
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn step(&mut self) {
+        Grid::step(self);
+    }
+
+    fn bounds(&self) -> Option<(Cell, Cell)> {
+        self.torus
+            .map(|torus| (Cell(0, 0), Cell(torus.width - 1, torus.height - 1)))
+    }
+
+    fn draw(&self, image: &mut CellImage) {
+        for (&cell, &state) in &self.dying_cells {
+            // Decaying cells fade from teal to dark blue
+            let t = state.saturating_sub(1) as f32 / (self.rules.states.max(2) - 1) as f32;
+            let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0) as u8;
+            let color = [
+                channel(0.2 * (1.0 - t)),
+                channel(0.6 * (1.0 - t) + 0.1),
+                channel(1.0 - 0.7 * t),
+                255,
+            ];
+            image.set(cell, color);
+        }
+        for &cell in &self.alive_cells {
+            image.set(cell, [255, 255, 255, 255]);
+        }
+    }
+
+    fn occupied(&self, cell: Cell) -> bool {
+        self.alive_cells.contains(&cell)
+    }
+
+    fn handle_input(&mut self, input: Input) -> bool {
+        let wrap = |cell: Cell| self.torus.map_or(cell, |torus| torus.wrap(cell));
+        let (cell, alive) = match input {
+            Input::Paint { cell, erase } => (wrap(cell), !erase),
+            Input::Activate(cell) => (wrap(cell), !self.alive_cells.contains(&wrap(cell))),
+        };
+        self.dying_cells.remove(&cell);
+        if alive {
+            self.alive_cells.insert(cell)
+        } else {
+            self.alive_cells.remove(&cell)
+        }
+    }
+
+    fn blank(&self) -> Box<dyn Simulation> {
+        let mut grid = Grid::new(HashSet::new(), self.rules.clone());
+        grid.torus = self.torus;
+        Box::new(grid)
+    }
+
+    fn save(&self) -> Value {
+        json!({
+            "cells": self.alive_cells,
+            "dying": self.dying_cells.iter().collect::<Vec<_>>(),
+            "rules": self.rules.as_string(),
+            "generation": self.generation,
+            "torus": self.torus.map(|torus| (torus.width, torus.height)),
+        })
+    }
+
+    fn restore(&mut self, state: Value) -> Result<(), String> {
+        let state: GridState =
+            serde_json::from_value(state).map_err(|err| format!("Invalid life state: {}", err))?;
+        let rules = Rules::from_string(&state.rules)?;
+        let torus = match state.torus {
+            Some((width, height)) if width > 0 && height > 0 => Some(Torus { width, height }),
+            Some((width, height)) => {
+                return Err(format!("Invalid life state: a {}x{} torus", width, height))
+            }
+            None => None,
+        };
+        if let Some(&(cell, decay)) = state
+            .dying
+            .iter()
+            .find(|&&(_, decay)| decay < 2 || decay >= rules.states)
+        {
+            return Err(format!(
+                "Invalid life state: cell {},{} decays in state {}, which {} doesn't have",
+                cell.0,
+                cell.1,
+                decay,
+                rules.as_string()
+            ));
+        }
+        // Cells saved off the torus come back on it, as edits and pastes do
+        let wrap = |cell: Cell| torus.map_or(cell, |torus| torus.wrap(cell));
+        self.alive_cells = state.cells.into_iter().map(wrap).collect();
+        self.dying_cells = state
+            .dying
+            .into_iter()
+            .map(|(cell, decay)| (wrap(cell), decay))
+            .filter(|(cell, _)| !self.alive_cells.contains(cell))
+            .collect();
+        self.rules = rules;
+        self.torus = torus;
+        self.generation = state.generation.max(1);
+        Ok(())
+    }
+
+    fn grid(&self) -> Option<&Grid> {
+        Some(self)
+    }
+}
@@ -0,0 +1,261 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::simulation::{grid_cells, CellImage, Input, Options, Simulation};
+use crate::Cell;
+
+/// Steepness of the transition at the edges of the birth and death intervals.
+const ALPHA_N: f32 = 0.028;
+/// Steepness of the switch from the birth to the death interval as a cell fills in.
+const ALPHA_M: f32 = 0.147;
+
+/// Stephan Rafler's SmoothLife on a torus: each cell holds a value between 0 and 1, reads how
+/// full the disk around it (`inner`) and the ring beyond that (`outer`) are, and comes alive
+/// or dies by smooth versions of Life's birth and survival intervals.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SmoothLife {
+    width: i32,
+    height: i32,
+    inner: f32,
+    outer: f32,
+    birth: (f32, f32),
+    death: (f32, f32),
+    dt: f32,
+    /// Cell values row by row
+    field: Vec<f32>,
+    generation: usize,
+    /// Offsets within the outer radius, with their weight in the disk and in the ring
+    #[serde(skip)]
+    kernel: Vec<(i32, i32, f32, f32)>,
+}
+
+impl SmoothLife {
+    /// An empty field of `options.width` x `options.height` cells with the radii, intervals and
+    /// time step given as parameters.
+    pub fn new(options: &Options) -> Result<Self, String> {
+        let (width, height) = (options.width.max(1), options.height.max(1));
+        let mut smoothlife = Self {
+            width,
+            height,
+            inner: options.param("inner", 4.0)?,
+            outer: options.param("outer", 12.0)?,
+            birth: (
+                options.param("birth1", 0.278)?,
+                options.param("birth2", 0.365)?,
+            ),
+            death: (
+                options.param("death1", 0.267)?,
+                options.param("death2", 0.445)?,
+            ),
+            dt: options.param("dt", 1.0)?,
+            field: Vec::new(),
+            generation: 1,
+            kernel: Vec::new(),
+        };
+        smoothlife.check_parameters()?;
+        smoothlife.field = vec![0.0; grid_cells(width, height)?];
+        smoothlife.build_kernel();
+        Ok(smoothlife)
+    }
+
+    /// Rejects radii that aren't finite or don't fit the torus, and intervals or time steps
+    /// outside 0 to 1.
+    fn check_parameters(&self) -> Result<(), String> {
+        let max_outer = self.width.min(self.height) as f32 / 2.0;
+        if !(self.inner.is_finite() && self.outer.is_finite())
+            || self.inner <= 0.0
+            || self.outer <= self.inner
+            || self.outer > max_outer
+        {
+            return Err(format!(
+                "SmoothLife needs 0 < inner < outer <= {}, half the field's shorter side",
+                max_outer
+            ));
+        }
+        let interval = |(low, high): (f32, f32)| 0.0 <= low && low <= high && high <= 1.0;
+        if !interval(self.birth) || !interval(self.death) {
+            return Err(
+                "SmoothLife needs 0 <= birth1 <= birth2 <= 1 and 0 <= death1 <= death2 <= 1"
+                    .to_string(),
+            );
+        }
+        if !(self.dt > 0.0 && self.dt <= 1.0) {
+            return Err("SmoothLife's dt must be above 0 and at most 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Weighs every offset by how much of it lies inside the disk and the ring, anti-aliasing
+    /// their edges so small radii still behave smoothly.
+    fn build_kernel(&mut self) {
+        let reach = (self.outer + 0.5).ceil() as i32;
+        self.kernel.clear();
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let distance = (dx as f32).hypot(dy as f32);
+                let disk = (self.inner + 0.5 - distance).clamp(0.0, 1.0);
+                let ring = (self.outer + 0.5 - distance).clamp(0.0, 1.0) - disk;
+                if disk > 0.0 || ring > 0.0 {
+                    self.kernel.push((dx, dy, disk, ring));
+                }
+            }
+        }
+    }
+
+    fn index(&self, cell: Cell) -> Option<usize> {
+        ((0..self.width).contains(&cell.0) && (0..self.height).contains(&cell.1))
+            .then(|| (cell.1 * self.width + cell.0) as usize)
+    }
+
+    /// The next value of a cell whose disk is `m` full and whose ring is `n` full.
+    fn transition(&self, n: f32, m: f32) -> f32 {
+        let sigmoid = |x: f32, a: f32, alpha: f32| 1.0 / (1.0 + (-(x - a) * 4.0 / alpha).exp());
+        let alive = sigmoid(m, 0.5, ALPHA_M);
+        let mix = |birth: f32, death: f32| birth * (1.0 - alive) + death * alive;
+        let (low, high) = (
+            mix(self.birth.0, self.death.0),
+            mix(self.birth.1, self.death.1),
+        );
+        sigmoid(n, low, ALPHA_N) * (1.0 - sigmoid(n, high, ALPHA_N))
+    }
+
+    fn set(&mut self, cell: Cell, value: f32) -> bool {
+        match self.index(cell) {
+            Some(i) if self.field[i] != value => {
+                self.field[i] = value;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Simulation for SmoothLife {
+    fn name(&self) -> &'static str {
+        "smoothlife"
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn step(&mut self) {
+        let (disk_area, ring_area) = self
+            .kernel
+            .iter()
+            .fold((0.0, 0.0), |(disk, ring), &(_, _, d, r)| {
+                (disk + d, ring + r)
+            });
+        // A copy of the field with a wrapped-around margin, so the kernel reads it by plain
+        // offsets instead of wrapping every coordinate
+        let reach = self
+            .kernel
+            .iter()
+            .map(|&(dx, dy, _, _)| dx.abs().max(dy.abs()))
+            .max()
+            .unwrap_or(0);
+        let padded_width = self.width + 2 * reach;
+        let mut padded = Vec::with_capacity((padded_width * (self.height + 2 * reach)) as usize);
+        for y in -reach..self.height + reach {
+            let row = y.rem_euclid(self.height) * self.width;
+            for x in -reach..self.width + reach {
+                padded.push(self.field[(row + x.rem_euclid(self.width)) as usize]);
+            }
+        }
+        let offsets: Vec<(isize, f32, f32)> = self
+            .kernel
+            .iter()
+            .map(|&(dx, dy, disk, ring)| ((dy * padded_width + dx) as isize, disk, ring))
+            .collect();
+        let mut next = vec![0.0; self.field.len()];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let center = ((y + reach) * padded_width + x + reach) as isize;
+                let (mut m, mut n) = (0.0, 0.0);
+                for &(offset, disk, ring) in &offsets {
+                    let value = padded[(center + offset) as usize];
+                    m += disk * value;
+                    n += ring * value;
+                }
+                let i = (y * self.width + x) as usize;
+                let target = self.transition(n / ring_area, m / disk_area);
+                next[i] = if self.dt >= 1.0 {
+                    target
+                } else {
+                    (self.field[i] + self.dt * (2.0 * target - 1.0)).clamp(0.0, 1.0)
+                };
+            }
+        }
+        self.field = next;
+        self.generation += 1;
+    }
+
+    fn bounds(&self) -> Option<(Cell, Cell)> {
+        Some((Cell(0, 0), Cell(self.width - 1, self.height - 1)))
+    }
+
+    /// A grayscale field, from black for empty to white for fully alive.
+    fn draw(&self, image: &mut CellImage) {
+        for (i, &value) in self.field.iter().enumerate() {
+            let gray = (value * 255.0) as u8;
+            let i = i as i32;
+            image.set(
+                Cell(i % self.width, i / self.width),
+                [gray, gray, gray, 255],
+            );
+        }
+    }
+
+    fn occupied(&self, cell: Cell) -> bool {
+        self.index(cell).is_some_and(|i| self.field[i] > 0.5)
+    }
+
+    /// Painting fills cells in completely; activating drops a filled disk as wide as the outer
+    /// radius, about the size of blob that comes alive.
+    fn handle_input(&mut self, input: Input) -> bool {
+        match input {
+            Input::Paint { cell, erase } => self.set(cell, if erase { 0.0 } else { 1.0 }),
+            Input::Activate(center) => {
+                let radius = (self.outer / 2.0).round() as i32;
+                let mut changed = false;
+                for dy in -radius..=radius {
+                    for dx in -radius..=radius {
+                        if dx * dx + dy * dy <= radius * radius {
+                            changed |= self.set(Cell(center.0 + dx, center.1 + dy), 1.0);
+                        }
+                    }
+                }
+                changed
+            }
+        }
+    }
+
+    fn status(&self) -> Option<String> {
+        Some(format!("Mass: {:.0}", self.field.iter().sum::<f32>()))
+    }
+
+    fn blank(&self) -> Box<dyn Simulation> {
+        let mut blank = self.clone();
+        blank.field.iter_mut().for_each(|value| *value = 0.0);
+        blank.generation = 1;
+        Box::new(blank)
+    }
+
+    fn save(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    fn restore(&mut self, state: Value) -> Result<(), String> {
+        let mut restored: Self = serde_json::from_value(state)
+            .map_err(|err| format!("Invalid SmoothLife state: {}", err))?;
+        if restored.field.len() != grid_cells(restored.width, restored.height)? {
+            return Err("SmoothLife state doesn't match its size".to_string());
+        }
+        restored
+            .check_parameters()
+            .map_err(|err| format!("Invalid SmoothLife state: {}", err))?;
+        restored.build_kernel();
+        *self = restored;
+        Ok(())
+    }
+}
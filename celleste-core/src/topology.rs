@@ -0,0 +1,32 @@
+use crate::Cell;
+
+/// A bounded grid of `width` x `height` cells starting at the origin, whose opposite edges
+/// are joined so patterns leaving one side come back on the other.
+#[derive(Clone, Copy)]
+pub struct Torus {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Torus {
+    /// Parses `WIDTHxHEIGHT`, e.g. `1000x1000`.
+    pub fn from_string(size_str: &str) -> Result<Self, String> {
+        let (width, height) = size_str
+            .split_once(['x', 'X'])
+            .ok_or_else(|| "Invalid torus size. Expected 'WIDTHxHEIGHT'.".to_string())?;
+        match (width.trim().parse::<i32>(), height.trim().parse::<i32>()) {
+            (Ok(width), Ok(height)) if width > 0 && height > 0 => Ok(Self { width, height }),
+            _ => {
+                Err("Invalid torus size. Expected 'WIDTHxHEIGHT' with positive sizes.".to_string())
+            }
+        }
+    }
+
+    /// The cell inside the grid that `cell` lands on once wrapped around the edges.
+    pub fn wrap(&self, cell: Cell) -> Cell {
+        Cell(
+            cell.0.rem_euclid(self.width),
+            cell.1.rem_euclid(self.height),
+        )
+    }
+}
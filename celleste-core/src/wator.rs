@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::random::Random;
+use crate::simulation::{grid_cells, CellImage, Input, Options, Population, Simulation};
+use crate::Cell;
+
+const FISH_COLOR: [u8; 4] = [60, 170, 255, 255];
+const SHARK_COLOR: [u8; 4] = [255, 140, 30, 255];
+/// Water, so empty cells read as sea rather than void
+const WATER_COLOR: [u8; 4] = [0, 20, 50, 255];
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum Creature {
+    Water,
+    /// Generations since it last bred
+    Fish {
+        age: u32,
+    },
+    /// Generations since it last bred, and since it last ate
+    Shark {
+        age: u32,
+        hunger: u32,
+    },
+}
+
+/// A. K. Dewdney's Wa-Tor on a torus: fish swim about and breed every `fish_breed`
+/// generations; sharks hunt neighboring fish, breed every `shark_breed` generations and
+/// starve after `shark_starve` generations without a meal.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WaTor {
+    width: i32,
+    height: i32,
+    fish_breed: u32,
+    shark_breed: u32,
+    shark_starve: u32,
+    /// Creatures row by row
+    sea: Vec<Creature>,
+    random: Random,
+    generation: usize,
+}
+
+impl WaTor {
+    /// An `options.width` x `options.height` sea stocked at random with `--param fish` and
+    /// `--param sharks` of its cells.
+    pub fn new(options: &Options) -> Result<Self, String> {
+        let timer = |name: &str, default: u32| {
+            let generations: u32 = options.param(name, default)?;
+            if generations > 0 {
+                Ok(generations)
+            } else {
+                Err(format!("{} must be at least 1 generation", name))
+            }
+        };
+        let fish: f64 = options.param("fish", 0.3)?;
+        let sharks: f64 = options.param("sharks", 0.03)?;
+        if !(0.0..=1.0).contains(&fish) || !(0.0..=1.0).contains(&sharks) {
+            return Err("The fish and shark densities must be between 0 and 1".to_string());
+        }
+        let (width, height) = (options.width.max(1), options.height.max(1));
+        let mut wator = Self {
+            width,
+            height,
+            fish_breed: timer("fish_breed", 3)?,
+            shark_breed: timer("shark_breed", 10)?,
+            shark_starve: timer("shark_starve", 3)?,
+            sea: vec![Creature::Water; grid_cells(width, height)?],
+            random: Random::new(options.seed),
+            generation: 1,
+        };
+        for i in 0..wator.sea.len() {
+            let roll = wator.random.unit();
+            wator.sea[i] = if roll < sharks {
+                Creature::Shark { age: 0, hunger: 0 }
+            } else if roll < sharks + fish {
+                Creature::Fish { age: 0 }
+            } else {
+                Creature::Water
+            };
+        }
+        Ok(wator)
+    }
+
+    fn index(&self, cell: Cell) -> Option<usize> {
+        ((0..self.width).contains(&cell.0) && (0..self.height).contains(&cell.1))
+            .then(|| (cell.1 * self.width + cell.0) as usize)
+    }
+
+    /// Indices of the four cells next to `i`, wrapping around the edges.
+    fn neighbors(&self, i: usize) -> [usize; 4] {
+        let (x, y) = (i as i32 % self.width, i as i32 / self.width);
+        let at = |dx: i32, dy: i32| {
+            let nx = (x + dx).rem_euclid(self.width);
+            let ny = (y + dy).rem_euclid(self.height);
+            (ny * self.width + nx) as usize
+        };
+        [at(1, 0), at(-1, 0), at(0, 1), at(0, -1)]
+    }
+
+    /// A random one of the cells next to `i` holding something `wanted` accepts.
+    fn pick_neighbor(&mut self, i: usize, wanted: impl Fn(Creature) -> bool) -> Option<usize> {
+        let choices: Vec<usize> = self
+            .neighbors(i)
+            .into_iter()
+            .filter(|&j| wanted(self.sea[j]))
+            .collect();
+        if choices.is_empty() {
+            return None;
+        }
+        let pick = (self.random.unit() * choices.len() as f64) as usize;
+        Some(choices[pick.min(choices.len() - 1)])
+    }
+
+    fn count(&self, fish: bool) -> usize {
+        self.sea
+            .iter()
+            .filter(|creature| match creature {
+                Creature::Fish { .. } => fish,
+                Creature::Shark { .. } => !fish,
+                Creature::Water => false,
+            })
+            .count()
+    }
+
+    fn set(&mut self, cell: Cell, creature: Creature) -> bool {
+        match self.index(cell) {
+            Some(i) if self.sea[i] != creature => {
+                self.sea[i] = creature;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Moves the fish at `i`, leaving a newborn behind if it's old enough to breed.
+    fn move_fish(&mut self, i: usize, age: u32, moved: &mut [bool]) {
+        let age = age + 1;
+        let Some(j) = self.pick_neighbor(i, |creature| creature == Creature::Water) else {
+            self.sea[i] = Creature::Fish { age };
+            return;
+        };
+        let breeds = age >= self.fish_breed;
+        self.sea[j] = Creature::Fish {
+            age: if breeds { 0 } else { age },
+        };
+        self.sea[i] = if breeds {
+            Creature::Fish { age: 0 }
+        } else {
+            Creature::Water
+        };
+        moved[j] = true;
+    }
+
+    /// Moves the shark at `i` onto a neighboring fish if there is one, otherwise into open
+    /// water, starving it if it has gone too long without eating.
+    fn move_shark(&mut self, i: usize, age: u32, hunger: u32, moved: &mut [bool]) {
+        let (age, mut hunger) = (age + 1, hunger + 1);
+        let prey = self.pick_neighbor(i, |creature| matches!(creature, Creature::Fish { .. }));
+        if prey.is_some() {
+            hunger = 0;
+        } else if hunger >= self.shark_starve {
+            self.sea[i] = Creature::Water;
+            return;
+        }
+        let target = prey.or_else(|| self.pick_neighbor(i, |creature| creature == Creature::Water));
+        let Some(j) = target else {
+            self.sea[i] = Creature::Shark { age, hunger };
+            return;
+        };
+        let breeds = age >= self.shark_breed;
+        self.sea[j] = Creature::Shark {
+            age: if breeds { 0 } else { age },
+            hunger,
+        };
+        self.sea[i] = if breeds {
+            Creature::Shark { age: 0, hunger: 0 }
+        } else {
+            Creature::Water
+        };
+        moved[j] = true;
+    }
+}
+
+impl Simulation for WaTor {
+    fn name(&self) -> &'static str {
+        "wator"
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Fish all move first, then sharks, each visiting the sea in a fresh random order so no
+    /// direction is favored. Creatures that moved this generation aren't moved again.
+    fn step(&mut self) {
+        let mut order: Vec<usize> = (0..self.sea.len()).collect();
+        for i in (1..order.len()).rev() {
+            let j = (self.random.unit() * (i + 1) as f64) as usize;
+            order.swap(i, j.min(i));
+        }
+        let mut moved = vec![false; self.sea.len()];
+        for &i in &order {
+            if let (Creature::Fish { age }, false) = (self.sea[i], moved[i]) {
+                self.move_fish(i, age, &mut moved);
+            }
+        }
+        moved.iter_mut().for_each(|moved| *moved = false);
+        for &i in &order {
+            if let (Creature::Shark { age, hunger }, false) = (self.sea[i], moved[i]) {
+                self.move_shark(i, age, hunger, &mut moved);
+            }
+        }
+        self.generation += 1;
+    }
+
+    fn bounds(&self) -> Option<(Cell, Cell)> {
+        Some((Cell(0, 0), Cell(self.width - 1, self.height - 1)))
+    }
+
+    fn draw(&self, image: &mut CellImage) {
+        for (i, creature) in self.sea.iter().enumerate() {
+            let color = match creature {
+                Creature::Fish { .. } => FISH_COLOR,
+                Creature::Shark { .. } => SHARK_COLOR,
+                Creature::Water => WATER_COLOR,
+            };
+            let i = i as i32;
+            image.set(Cell(i % self.width, i / self.width), color);
+        }
+    }
+
+    fn occupied(&self, cell: Cell) -> bool {
+        self.index(cell)
+            .is_some_and(|i| self.sea[i] != Creature::Water)
+    }
+
+    /// Painting releases fish and erasing clears cells; activating releases a shark.
+    fn handle_input(&mut self, input: Input) -> bool {
+        match input {
+            Input::Paint { cell, erase: false } => self.set(cell, Creature::Fish { age: 0 }),
+            Input::Paint { cell, erase: true } => self.set(cell, Creature::Water),
+            Input::Activate(cell) => self.set(cell, Creature::Shark { age: 0, hunger: 0 }),
+        }
+    }
+
+    fn status(&self) -> Option<String> {
+        Some(format!(
+            "Fish: {}, sharks: {}",
+            self.count(true),
+            self.count(false)
+        ))
+    }
+
+    fn populations(&self) -> Vec<Population> {
+        vec![
+            Population {
+                name: "Fish",
+                count: self.count(true),
+                color: FISH_COLOR,
+            },
+            Population {
+                name: "Sharks",
+                count: self.count(false),
+                color: SHARK_COLOR,
+            },
+        ]
+    }
+
+    fn blank(&self) -> Box<dyn Simulation> {
+        let mut blank = self.clone();
+        blank
+            .sea
+            .iter_mut()
+            .for_each(|creature| *creature = Creature::Water);
+        blank.generation = 1;
+        Box::new(blank)
+    }
+
+    fn save(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    fn restore(&mut self, state: Value) -> Result<(), String> {
+        let restored: Self = serde_json::from_value(state)
+            .map_err(|err| format!("Invalid Wa-Tor state: {}", err))?;
+        if restored.sea.len() != grid_cells(restored.width, restored.height)? {
+            return Err("Wa-Tor state doesn't match its size".to_string());
+        }
+        *self = restored;
+        Ok(())
+    }
+}
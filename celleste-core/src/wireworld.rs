@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::simulation::{grid_cells, CellImage, Input, Simulation};
+use crate::Cell;
+
+const EMPTY: u8 = 0;
+const HEAD: u8 = 1;
+const TAIL: u8 = 2;
+const WIRE: u8 = 3;
+
+/// Brian Silverman's WireWorld on a bounded grid: electron heads turn into tails, tails back
+/// into wire, and wire next to one or two heads becomes a head, so signals flow along wires
+/// drawn by hand.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WireWorld {
+    width: i32,
+    height: i32,
+    /// States row by row
+    states: Vec<u8>,
+    generation: usize,
+}
+
+impl WireWorld {
+    /// An empty `width` x `height` grid.
+    pub fn new(width: i32, height: i32) -> Result<Self, String> {
+        let (width, height) = (width.max(1), height.max(1));
+        Ok(Self {
+            width,
+            height,
+            states: vec![EMPTY; grid_cells(width, height)?],
+            generation: 1,
+        })
+    }
+
+    fn index(&self, cell: Cell) -> Option<usize> {
+        ((0..self.width).contains(&cell.0) && (0..self.height).contains(&cell.1))
+            .then(|| (cell.1 * self.width + cell.0) as usize)
+    }
+
+    fn state(&self, cell: Cell) -> u8 {
+        self.index(cell).map_or(EMPTY, |i| self.states[i])
+    }
+
+    fn set_state(&mut self, cell: Cell, state: u8) -> bool {
+        match self.index(cell) {
+            Some(i) if self.states[i] != state => {
+                self.states[i] = state;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn heads_around(&self, cell: Cell) -> usize {
+        let mut heads = 0;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if (dx, dy) != (0, 0) && self.state(Cell(cell.0 + dx, cell.1 + dy)) == HEAD {
+                    heads += 1;
+                }
+            }
+        }
+        heads
+    }
+}
+
+impl Simulation for WireWorld {
+    fn name(&self) -> &'static str {
+        "wireworld"
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn step(&mut self) {
+        let mut next = self.states.clone();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let i = (y * self.width + x) as usize;
+                next[i] = match self.states[i] {
+                    HEAD => TAIL,
+                    TAIL => WIRE,
+                    WIRE if matches!(self.heads_around(Cell(x, y)), 1 | 2) => HEAD,
+                    state => state,
+                };
+            }
+        }
+        self.states = next;
+        self.generation += 1;
+    }
+
+    fn bounds(&self) -> Option<(Cell, Cell)> {
+        Some((Cell(0, 0), Cell(self.width - 1, self.height - 1)))
+    }
+
+    fn draw(&self, image: &mut CellImage) {
+        // Golly's WireWorld colors
+        for (i, &state) in self.states.iter().enumerate() {
+            let color = match state {
+                HEAD => [0, 128, 255, 255],
+                TAIL => [255, 255, 255, 255],
+                WIRE => [255, 128, 0, 255],
+                _ => continue,
+            };
+            let i = i as i32;
+            image.set(Cell(i % self.width, i / self.width), color);
+        }
+    }
+
+    fn occupied(&self, cell: Cell) -> bool {
+        self.state(cell) != EMPTY
+    }
+
+    /// Painting lays wire and erasing clears cells; activating wire sends an electron down it.
+    fn handle_input(&mut self, input: Input) -> bool {
+        match input {
+            Input::Paint { cell, erase: true } => self.set_state(cell, EMPTY),
+            Input::Paint { cell, erase: false } if self.state(cell) == EMPTY => {
+                self.set_state(cell, WIRE)
+            }
+            Input::Paint { .. } => false,
+            Input::Activate(cell) => match self.state(cell) {
+                WIRE => self.set_state(cell, HEAD),
+                HEAD | TAIL => self.set_state(cell, WIRE),
+                _ => false,
+            },
+        }
+    }
+
+    fn status(&self) -> Option<String> {
+        let electrons = self.states.iter().filter(|&&state| state == HEAD).count();
+        Some(format!("Electrons: {}", electrons))
+    }
+
+    fn blank(&self) -> Box<dyn Simulation> {
+        let mut blank = self.clone();
+        blank.states.iter_mut().for_each(|state| *state = EMPTY);
+        blank.generation = 1;
+        Box::new(blank)
+    }
+
+    fn save(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    fn restore(&mut self, state: Value) -> Result<(), String> {
+        let restored: Self = serde_json::from_value(state)
+            .map_err(|err| format!("Invalid WireWorld state: {}", err))?;
+        if restored.states.len() != grid_cells(restored.width, restored.height)? {
+            return Err("WireWorld state doesn't match its size".to_string());
+        }
+        *self = restored;
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+//! Reading the RLE, plaintext and macrocell formats, including the malformed files that must
+//! be turned away rather than crash or exhaust memory.
+
+use std::collections::HashSet;
+
+use celleste_core::patterns::{self, MAX_PATTERN_CELLS};
+use celleste_core::Cell;
+
+fn cells(list: &[(i32, i32)]) -> HashSet<Cell> {
+    list.iter().map(|&(x, y)| Cell(x, y)).collect()
+}
+
+#[test]
+fn rle_reads_runs_rows_and_the_header_rule() {
+    let pattern =
+        patterns::parse_rle("#N glider\nx = 3, y = 3, rule = b3/s23\nbo$2bo$3o!").unwrap();
+    assert_eq!(
+        pattern.cells,
+        cells(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)])
+    );
+    assert_eq!(pattern.rules.as_deref(), Some("B3/S23"));
+    assert!(!pattern.absolute);
+}
+
+#[test]
+fn rle_reads_generations_states_and_old_rule_notation() {
+    let pattern = patterns::parse_rle("x = 3, y = 1, rule = /2/3\nA2B!").unwrap();
+    assert_eq!(pattern.cells, cells(&[(0, 0)]));
+    assert_eq!(pattern.dying, vec![(Cell(1, 0), 2), (Cell(2, 0), 2)]);
+    assert_eq!(pattern.rules.as_deref(), Some("B2/S/C3"));
+}
+
+#[test]
+fn rle_rejects_unknown_characters() {
+    assert!(patterns::parse_rle("x = 1, y = 1\no?!").is_err());
+}
+
+#[test]
+fn rle_rejects_runs_that_overflow() {
+    for rle in [
+        "x = 1, y = 1\n99999999999o!",
+        "2000000000$2000000000$o!",
+        "2000000000b2000000000bo!",
+    ] {
+        assert_eq!(
+            patterns::parse_rle(rle).err().as_deref(),
+            Some("RLE run too long"),
+            "{}",
+            rle
+        );
+    }
+}
+
+#[test]
+fn rle_rejects_patterns_with_too_many_cells() {
+    let rle = format!("{}o!", MAX_PATTERN_CELLS + 1);
+    assert!(patterns::parse_rle(&rle).is_err());
+    // Decaying cells count towards the cap too
+    let rle = format!("o${}B!", MAX_PATTERN_CELLS);
+    assert!(patterns::parse_rle(&rle).is_err());
+}
+
+#[test]
+fn to_rle_round_trips_through_parse_rle() {
+    let glider = cells(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    let rle = patterns::to_rle(&glider, "B3/S23");
+    assert_eq!(patterns::parse_rle(&rle).unwrap().cells, glider);
+}
+
+#[test]
+fn cells_reads_plaintext_and_skips_comments() {
+    let pattern = patterns::parse_cells("!Name: blinker\n...\nOOO\n").unwrap();
+    assert_eq!(pattern.cells, cells(&[(0, 1), (1, 1), (2, 1)]));
+    assert_eq!(pattern.rules.as_deref(), Some("B3/S23"));
+    assert!(patterns::parse_cells(".O.\nOxO\n").is_err());
+}
+
+#[test]
+fn text_falls_back_from_plaintext_to_rle() {
+    let from_cells = patterns::parse_text(".O\nO.").unwrap();
+    let from_rle = patterns::parse_text("bo$o!").unwrap();
+    assert_eq!(from_cells.cells, from_rle.cells);
+    assert!(patterns::parse_text("...").is_err());
+    assert!(patterns::parse_text("not a pattern").is_err());
+}
+
+#[test]
+fn text_too_large_to_paste_says_so() {
+    let err = patterns::parse_text("99999999999o!").err().unwrap();
+    assert_eq!(err, "RLE run too long");
+}
+
+#[test]
+fn macrocell_reads_leaves_and_branches() {
+    // An 8x8 leaf holding a glider, placed in the south-east quadrant of a 16x16 root
+    let mc = "[M2] (golly 4.0)\n#R B3/S23\n.*$..*$***$\n4 0 0 0 1\n";
+    let pattern = patterns::parse_macrocell(mc.as_bytes()).unwrap();
+    assert_eq!(
+        pattern.cells,
+        cells(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)])
+    );
+    assert_eq!(pattern.rules.as_deref(), Some("B3/S23"));
+}
+
+#[test]
+fn macrocell_rejects_bad_headers_and_nodes() {
+    assert!(patterns::parse_macrocell("4 0 0 0 0\n".as_bytes()).is_err());
+    assert!(patterns::parse_macrocell("[M2]\n4 0 0 0 7\n".as_bytes()).is_err());
+    assert!(patterns::parse_macrocell("[M2]\n*********\n".as_bytes()).is_err());
+}
+
+#[test]
+fn macrocell_rejects_shared_subtrees_with_too_many_cells() {
+    // Every level doubles the pattern's width and height by reusing the node below it
+    let mut mc = String::from(
+        "[M2]\n********$********$********$********$********$********$********$********$\n",
+    );
+    for level in 4..=20 {
+        let child = level - 3;
+        mc.push_str(&format!(
+            "{} {} {} {} {}\n",
+            level, child, child, child, child
+        ));
+    }
+    assert!(patterns::parse_macrocell(mc.as_bytes()).is_err());
+}
+
+#[test]
+fn macrocell_skips_deep_empty_subtrees() {
+    let mut mc = String::from("[M2]\n$\n");
+    for level in 4..=62 {
+        let child = level - 3;
+        mc.push_str(&format!(
+            "{} {} {} {} {}\n",
+            level, child, child, child, child
+        ));
+    }
+    let pattern = patterns::parse_macrocell(mc.as_bytes()).unwrap();
+    assert!(pattern.cells.is_empty());
+}
@@ -0,0 +1,247 @@
+//! Known patterns run through the public stepping API: each fixture must come back to its own
+//! shape after exactly its period, moved by its displacement.
+
+use std::collections::HashSet;
+
+use celleste_core::{census, patterns, Cell, Grid, Rules};
+
+struct Fixture {
+    name: &'static str,
+    rle: &'static str,
+    period: u64,
+    /// How far the pattern moves each period
+    displacement: (i32, i32),
+}
+
+const FIXTURES: &[Fixture] = &[
+    Fixture {
+        name: "block",
+        rle: include_str!("fixtures/block.rle"),
+        period: 1,
+        displacement: (0, 0),
+    },
+    Fixture {
+        name: "blinker",
+        rle: include_str!("fixtures/blinker.rle"),
+        period: 2,
+        displacement: (0, 0),
+    },
+    Fixture {
+        name: "pulsar",
+        rle: include_str!("fixtures/pulsar.rle"),
+        period: 3,
+        displacement: (0, 0),
+    },
+    Fixture {
+        name: "pentadecathlon",
+        rle: include_str!("fixtures/pentadecathlon.rle"),
+        period: 15,
+        displacement: (0, 0),
+    },
+    Fixture {
+        name: "glider",
+        rle: include_str!("fixtures/glider.rle"),
+        period: 4,
+        displacement: (1, 1),
+    },
+    Fixture {
+        name: "lightweight spaceship",
+        rle: include_str!("fixtures/lwss.rle"),
+        period: 4,
+        displacement: (-2, 0),
+    },
+];
+
+fn load(fixture: &Fixture) -> Grid {
+    Grid::from_rle(fixture.rle).unwrap_or_else(|err| panic!("{}: {}", fixture.name, err))
+}
+
+fn shifted(cells: &HashSet<Cell>, (dx, dy): (i32, i32)) -> HashSet<Cell> {
+    cells
+        .iter()
+        .map(|cell| Cell(cell.0 + dx, cell.1 + dy))
+        .collect()
+}
+
+/// Cells of a random soup filling a `size` x `size` square at roughly 50% density.
+fn soup(size: i32, seed: u64) -> HashSet<Cell> {
+    let mut state = seed;
+    let mut cells = HashSet::new();
+    for y in 0..size {
+        for x in 0..size {
+            // 64-bit LCG from Knuth's MMIX; the high bit is the most random
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            if state >> 63 == 1 {
+                cells.insert(Cell(x, y));
+            }
+        }
+    }
+    cells
+}
+
+#[test]
+fn fixtures_repeat_after_exactly_their_period() {
+    for fixture in FIXTURES {
+        let start = load(fixture);
+        let mut grid = start.clone();
+        for generation in 1..fixture.period {
+            grid.step();
+            assert_ne!(
+                grid.canonical_hash(),
+                start.canonical_hash(),
+                "{} repeated after {} generations instead of {}",
+                fixture.name,
+                generation,
+                fixture.period
+            );
+        }
+        grid.step();
+        assert_eq!(
+            grid.alive_cells,
+            shifted(&start.alive_cells, fixture.displacement),
+            "{} didn't return to its shape after {} generations",
+            fixture.name,
+            fixture.period
+        );
+        assert_eq!(grid.generation, 1 + fixture.period as usize);
+    }
+}
+
+#[test]
+fn step_n_matches_stepping_one_generation_at_a_time() {
+    for fixture in FIXTURES {
+        let mut one_by_one = load(fixture);
+        for _ in 0..37 {
+            one_by_one.step();
+        }
+        let mut batched = load(fixture);
+        batched.step_n(37);
+        assert_eq!(
+            batched.alive_cells, one_by_one.alive_cells,
+            "{}",
+            fixture.name
+        );
+        assert_eq!(batched.generation, one_by_one.generation);
+    }
+}
+
+#[test]
+fn stepping_is_deterministic() {
+    let rules = Rules::from_string("B3/S23").unwrap();
+    let mut first = Grid::new(soup(40, 1), rules.clone());
+    let mut second = Grid::new(soup(40, 1), rules);
+    first.step_n(200);
+    second.step_n(200);
+    assert_eq!(first.state_hash(), second.state_hash());
+    assert_eq!(first.alive_cells, second.alive_cells);
+}
+
+#[test]
+fn state_hash_follows_position_but_canonical_hash_does_not() {
+    let glider = &FIXTURES[4];
+    let start = load(glider);
+    let mut grid = start.clone();
+    grid.step_n(glider.period);
+    assert_ne!(grid.state_hash(), start.state_hash());
+    assert_eq!(grid.canonical_hash(), start.canonical_hash());
+}
+
+#[test]
+fn dense_soups_step_the_same_as_bitboards_and_cell_by_cell() {
+    let mut grid = Grid::new(soup(100, 7), Rules::from_string("B3/S23").unwrap());
+    for _ in 0..10 {
+        assert!(
+            grid.use_bitboard(),
+            "the soup thinned out too early to compare"
+        );
+        let (cell_by_cell, _) = grid.next_state(grid.neighbor_counts());
+        grid.step();
+        assert_eq!(grid.alive_cells, cell_by_cell);
+    }
+}
+
+#[test]
+fn generations_cells_decay_through_their_states() {
+    // A lone cell can't survive under Brian's Brain, so it decays and then disappears
+    let mut grid = Grid::new(
+        [Cell(0, 0)].into_iter().collect(),
+        Rules::from_string("B2/S/C3").unwrap(),
+    );
+    grid.step();
+    assert!(grid.alive_cells.is_empty());
+    assert_eq!(grid.dying_cells.get(&Cell(0, 0)), Some(&2));
+    let decaying = grid.state_hash();
+    grid.step();
+    assert!(grid.dying_cells.is_empty());
+    assert_ne!(grid.state_hash(), decaying);
+}
+
+#[test]
+fn gliders_crash_into_the_edge_of_the_universe_instead_of_wrapping() {
+    let glider = &FIXTURES[4];
+    let start = load(glider);
+    let corner = Cell(i32::MAX - 10, i32::MAX - 10);
+    let mut grid = Grid::new(
+        shifted(&start.alive_cells, (corner.0, corner.1)),
+        start.rules.clone(),
+    );
+    grid.step_n(100);
+    assert!(grid
+        .alive_cells
+        .iter()
+        .all(|cell| cell.0 >= corner.0 && cell.1 >= corner.1));
+}
+
+#[test]
+fn dense_soups_at_the_edge_of_the_universe_step_the_same_both_ways() {
+    // Filling the corner where the right edge meets the top one
+    let mut grid = Grid::new(
+        shifted(&soup(80, 7), (i32::MAX - 79, i32::MIN)),
+        Rules::from_string("B3/S23").unwrap(),
+    );
+    for _ in 0..10 {
+        assert!(grid.use_bitboard());
+        let (cell_by_cell, _) = grid.next_state(grid.neighbor_counts());
+        grid.step();
+        assert_eq!(grid.alive_cells, cell_by_cell);
+    }
+}
+
+#[test]
+fn census_names_objects_at_the_edge_of_the_universe() {
+    // A block in the far right column and a blinker in the far left one
+    let (max, min) = (i32::MAX, i32::MIN);
+    let cells: HashSet<Cell> = [
+        Cell(max - 1, 0),
+        Cell(max, 0),
+        Cell(max - 1, 1),
+        Cell(max, 1),
+        Cell(min, min + 1),
+        Cell(min + 1, min + 1),
+        Cell(min + 2, min + 1),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(
+        census::census(&cells),
+        vec![("blinker".to_string(), 1), ("block".to_string(), 1)]
+    );
+}
+
+#[test]
+fn rle_spans_the_whole_universe() {
+    // Opposite corners are further apart than an i32 can count
+    let (max, min) = (i32::MAX, i32::MIN);
+    let cells: HashSet<Cell> = [Cell(min, 0), Cell(max, 0), Cell(0, min), Cell(0, max)]
+        .into_iter()
+        .collect();
+    let rle = patterns::to_rle(&cells, "B3/S23");
+    assert!(rle.starts_with("x = 4294967296, y = 4294967296, rule = B3/S23\n"));
+    let body: String = rle.lines().skip(1).collect();
+    assert_eq!(
+        body,
+        "2147483648bo2147483648$o4294967294bo2147483647$2147483648bo!"
+    );
+}
@@ -0,0 +1,179 @@
+//! Creating simulations by name: parameters that would hang, overflow or fill the field with
+//! NaN are turned away.
+
+use std::collections::HashMap;
+
+use serde_json::json;
+
+use celleste_core::simulation::{self, Input, Options};
+use celleste_core::{Cell, Rules, Torus};
+
+fn options(width: i32, height: i32, params: &[(&str, &str)]) -> Options {
+    Options {
+        width,
+        height,
+        rules: Rules::from_string("B3/S23").unwrap(),
+        torus: None,
+        params: params
+            .iter()
+            .map(|&(name, value)| (name.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>(),
+        seed: 1,
+    }
+}
+
+#[test]
+fn smoothlife_accepts_its_defaults() {
+    assert!(simulation::create("smoothlife", &options(64, 64, &[])).is_ok());
+}
+
+#[test]
+fn smoothlife_rejects_radii_that_are_not_finite_or_do_not_fit() {
+    for params in [
+        [("inner", "NaN"), ("outer", "12")],
+        [("inner", "4"), ("outer", "NaN")],
+        [("inner", "4"), ("outer", "inf")],
+        [("inner", "4"), ("outer", "100000")],
+        [("inner", "4"), ("outer", "33")],
+        [("inner", "12"), ("outer", "4")],
+    ] {
+        assert!(
+            simulation::create("smoothlife", &options(64, 64, &params)).is_err(),
+            "{:?}",
+            params
+        );
+    }
+}
+
+#[test]
+fn smoothlife_rejects_intervals_out_of_order_or_range() {
+    for params in [
+        [("birth1", "0.4"), ("birth2", "0.3")],
+        [("death1", "0.5"), ("death2", "0.4")],
+        [("birth1", "-0.1"), ("birth2", "0.3")],
+        [("death1", "0.2"), ("death2", "1.5")],
+        [("birth1", "NaN"), ("birth2", "0.3")],
+    ] {
+        assert!(
+            simulation::create("smoothlife", &options(64, 64, &params)).is_err(),
+            "{:?}",
+            params
+        );
+    }
+}
+
+#[test]
+fn lenia_accepts_its_creature() {
+    assert!(simulation::create("lenia", &options(64, 64, &[])).is_ok());
+}
+
+#[test]
+fn lenia_rejects_parameters_that_are_not_finite_or_do_not_fit() {
+    for params in [
+        ("radius", "NaN"),
+        ("radius", "inf"),
+        ("radius", "33"),
+        ("radius", "1000000"),
+        ("time_scale", "NaN"),
+        ("time_scale", "inf"),
+        ("sigma", "NaN"),
+        ("sigma", "0"),
+        ("mu", "NaN"),
+        ("mu", "-0.1"),
+        ("mu", "2"),
+    ] {
+        assert!(
+            simulation::create("lenia", &options(64, 64, &[params])).is_err(),
+            "{:?}",
+            params
+        );
+    }
+}
+
+#[test]
+fn life_restores_onto_its_torus_what_it_saved() {
+    let mut options = options(64, 64, &[]);
+    options.torus = Some(Torus {
+        width: 8,
+        height: 8,
+    });
+    let mut life = simulation::create("life", &options).unwrap();
+    life.handle_input(Input::Paint {
+        cell: Cell(9, -1),
+        erase: false,
+    });
+    assert!(life.occupied(Cell(1, 7)));
+
+    let mut restored = life.blank();
+    restored.restore(life.save()).unwrap();
+    assert_eq!(restored.save(), life.save());
+
+    let mut state = life.save();
+    state["cells"] = json!([[12, 3]]);
+    restored.restore(state).unwrap();
+    assert!(restored.occupied(Cell(4, 3)));
+}
+
+#[test]
+fn life_rejects_states_its_rule_does_not_have() {
+    let mut life = simulation::create("life", &options(64, 64, &[])).unwrap();
+    for state in [
+        json!({"cells": [], "dying": [[[0, 0], 2]], "rules": "B3/S23", "generation": 1}),
+        json!({"cells": [], "dying": [[[0, 0], 0]], "rules": "B2/S/C3", "generation": 1}),
+        json!({"cells": [], "rules": "B3/S23", "generation": 1, "torus": [0, 8]}),
+    ] {
+        assert!(life.restore(state.clone()).is_err(), "{}", state);
+    }
+}
+
+#[test]
+fn bz_rejects_rates_that_are_not_finite_or_are_negative() {
+    assert!(simulation::create("bz", &options(64, 64, &[])).is_ok());
+    for params in [("alpha", "NaN"), ("beta", "inf"), ("gamma", "-1")] {
+        assert!(
+            simulation::create("bz", &options(64, 64, &[params])).is_err(),
+            "{:?}",
+            params
+        );
+    }
+}
+
+#[test]
+fn sandpile_topples_four_grains_onto_the_neighbors() {
+    let mut sandpile = simulation::create("sandpile", &options(3, 3, &[("pile", "4")])).unwrap();
+    assert!(sandpile.handle_input(Input::Activate(Cell(1, 1))));
+    assert_eq!(
+        sandpile.save()["grains"],
+        json!([0, 0, 0, 0, 4, 0, 0, 0, 0])
+    );
+    sandpile.step();
+    assert_eq!(
+        sandpile.save()["grains"],
+        json!([0, 1, 0, 1, 0, 1, 0, 1, 0])
+    );
+    assert_eq!(sandpile.generation(), 2);
+
+    // Grains toppling off the edge are lost
+    sandpile.handle_input(Input::Activate(Cell(0, 0)));
+    sandpile.step();
+    assert_eq!(
+        sandpile.save()["grains"],
+        json!([0, 2, 0, 2, 0, 1, 0, 1, 0])
+    );
+}
+
+#[test]
+fn critters_run_backwards_to_where_they_started() {
+    let mut critters = simulation::create("margolus", &options(16, 16, &[])).unwrap();
+    let start = critters.save();
+    for _ in 0..20 {
+        critters.step();
+    }
+    assert_ne!(critters.save()["alive"], start["alive"]);
+    assert_eq!(critters.reverse(), Some(true));
+    for _ in 0..25 {
+        critters.step();
+    }
+    assert_eq!(critters.generation(), 1);
+    assert_eq!(critters.save()["alive"], start["alive"]);
+}
@@ -0,0 +1,208 @@
+//! Python bindings for `celleste-core`, built into the `celleste` module with maturin:
+//!
+//! ```python
+//! import celleste
+//!
+//! grid = celleste.Grid.from_rle("bo$2bo$3o!")
+//! grid.step(4)
+//! grid.cells()  # numpy array of (x, y) rows
+//! ```
+
+use numpy::ndarray::Array2;
+use numpy::{IntoPyArray, PyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use std::collections::HashSet;
+
+use celleste_core::simulation::MAX_GRID_CELLS;
+use celleste_core::{Cell, Rules, Torus};
+
+/// A numpy array of cells with the `(x, y)` of its top-left corner.
+type PlacedArray<'py> = (Bound<'py, PyArray2<u8>>, (i32, i32));
+
+/// A grid of cells evolving under a rule, like the one the Celleste window runs.
+#[pyclass]
+struct Grid {
+    grid: celleste_core::Grid,
+}
+
+#[pymethods]
+impl Grid {
+    /// Makes a grid holding `cells`, a sequence of `(x, y)` pairs, under `rule` (a preset
+    /// name or any rulestring `--rules` takes). With `torus=(width, height)` the grid is
+    /// bounded and wraps around its edges.
+    #[new]
+    #[pyo3(signature = (rule = "B3/S23", cells = Vec::new(), torus = None))]
+    fn new(rule: &str, cells: Vec<(i32, i32)>, torus: Option<(i32, i32)>) -> PyResult<Self> {
+        let rules = Rules::from_string(rule).map_err(PyValueError::new_err)?;
+        let cells = cells.into_iter().map(|(x, y)| Cell(x, y)).collect();
+        let mut grid = Self {
+            grid: celleste_core::Grid::new(cells, rules),
+        };
+        if let Some((width, height)) = torus {
+            grid.set_torus(width, height)?;
+        }
+        Ok(grid)
+    }
+
+    /// Reads an RLE pattern, running the rule from its header or Conway's Life.
+    #[staticmethod]
+    fn from_rle(text: &str) -> PyResult<Self> {
+        let grid = celleste_core::Grid::from_rle(text).map_err(PyValueError::new_err)?;
+        Ok(Self { grid })
+    }
+
+    /// The rule as a rulestring; setting it switches rules without touching the cells.
+    #[getter]
+    fn rule(&self) -> String {
+        self.grid.rules.as_string()
+    }
+
+    #[setter]
+    fn set_rule(&mut self, rule: &str) -> PyResult<()> {
+        self.grid.rules = Rules::from_string(rule).map_err(PyValueError::new_err)?;
+        Ok(())
+    }
+
+    /// Number of the current generation; the starting pattern is generation 1.
+    #[getter]
+    fn generation(&self) -> usize {
+        self.grid.generation
+    }
+
+    #[getter]
+    fn population(&self) -> usize {
+        self.grid.alive_cells.len()
+    }
+
+    /// Bounds the grid to `width` x `height` cells from the origin, wrapping cells outside it.
+    fn set_torus(&mut self, width: i32, height: i32) -> PyResult<()> {
+        if width <= 0 || height <= 0 {
+            return Err(PyValueError::new_err("Torus sizes must be positive"));
+        }
+        let torus = Torus { width, height };
+        self.grid.torus = Some(torus);
+        let cells = std::mem::take(&mut self.grid.alive_cells);
+        self.grid.alive_cells = cells.into_iter().map(|cell| torus.wrap(cell)).collect();
+        Ok(())
+    }
+
+    /// Advances `n` generations.
+    #[pyo3(signature = (n = 1))]
+    fn step(&mut self, py: Python<'_>, n: u64) {
+        // Long runs don't hold up other Python threads
+        py.allow_threads(|| self.grid.step_n(n));
+    }
+
+    /// Makes the cell at `(x, y)` live, or with `alive=False` dead.
+    #[pyo3(signature = (x, y, alive = true))]
+    fn set_cell(&mut self, x: i32, y: i32, alive: bool) {
+        let cell = self.wrapped(x, y);
+        self.grid.dying_cells.remove(&cell);
+        if alive {
+            self.grid.alive_cells.insert(cell);
+        } else {
+            self.grid.alive_cells.remove(&cell);
+        }
+    }
+
+    /// Whether the cell at `(x, y)` is alive, wrapping it onto the torus like `set_cell`.
+    fn is_alive(&self, x: i32, y: i32) -> bool {
+        self.grid.alive_cells.contains(&self.wrapped(x, y))
+    }
+
+    /// The live cells as an `(n, 2)` int32 array of `(x, y)` rows, sorted by row then column.
+    fn cells<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<i32>> {
+        let mut cells: Vec<Cell> = self.grid.alive_cells.iter().copied().collect();
+        cells.sort_unstable_by_key(|cell| (cell.1, cell.0));
+        let flat = cells.iter().flat_map(|cell| [cell.0, cell.1]).collect();
+        Array2::from_shape_vec((cells.len(), 2), flat)
+            .expect("two coordinates per cell")
+            .into_pyarray(py)
+    }
+
+    /// The bounding box of the live cells as a uint8 array indexed `[y, x]`, holding 1 for
+    /// live cells and a decaying cell's state under Generations rules, along with the
+    /// `(x, y)` of its top-left corner. Empty grids give a 0 x 0 array at the origin, and
+    /// bounding boxes of more than `MAX_GRID_CELLS` cells raise `ValueError`.
+    fn to_array<'py>(&self, py: Python<'py>) -> PyResult<PlacedArray<'py>> {
+        let cells: HashSet<Cell> = self
+            .grid
+            .alive_cells
+            .iter()
+            .chain(self.grid.dying_cells.keys())
+            .copied()
+            .collect();
+        let Some((min, max)) = celleste_core::bounding_box(&cells) else {
+            return Ok((Array2::zeros((0, 0)).into_pyarray(py), (0, 0)));
+        };
+        // Far-apart cells span more than an i32 can count, so the box is measured in i64
+        let width = max.0 as i64 - min.0 as i64 + 1;
+        let height = max.1 as i64 - min.1 as i64 + 1;
+        if width
+            .checked_mul(height)
+            .is_none_or(|cells| cells > MAX_GRID_CELLS as i64)
+        {
+            return Err(PyValueError::new_err(format!(
+                "The live cells span {}x{}, more than the {} cells an array may hold",
+                width, height, MAX_GRID_CELLS
+            )));
+        }
+        let shape = (height as usize, width as usize);
+        let mut array = Array2::zeros(shape);
+        let index = |cell: &Cell| {
+            (
+                (cell.1 as i64 - min.1 as i64) as usize,
+                (cell.0 as i64 - min.0 as i64) as usize,
+            )
+        };
+        for (cell, &state) in &self.grid.dying_cells {
+            array[index(cell)] = state;
+        }
+        for cell in &self.grid.alive_cells {
+            array[index(cell)] = 1;
+        }
+        Ok((array.into_pyarray(py), (min.0, min.1)))
+    }
+
+    /// Hash of the cells where they are, equal for equal states however they were reached.
+    fn state_hash(&self) -> u64 {
+        self.grid.state_hash()
+    }
+
+    /// Like `state_hash`, but the same wherever the pattern has moved to.
+    fn canonical_hash(&self) -> u64 {
+        self.grid.canonical_hash()
+    }
+
+    fn __len__(&self) -> usize {
+        self.grid.alive_cells.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Grid(rule='{}', generation={}, population={})",
+            self.grid.rules.as_string(),
+            self.grid.generation,
+            self.grid.alive_cells.len()
+        )
+    }
+}
+
+impl Grid {
+    /// The cell at `(x, y)`, wrapped onto the torus if the grid has one.
+    fn wrapped(&self, x: i32, y: i32) -> Cell {
+        match self.grid.torus {
+            Some(torus) => torus.wrap(Cell(x, y)),
+            None => Cell(x, y),
+        }
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "celleste")]
+fn celleste_py(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Grid>()?;
+    Ok(())
+}
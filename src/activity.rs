@@ -0,0 +1,54 @@
+use std::collections::{HashMap, HashSet};
+
+use celleste_core::Cell;
+
+/// Share of a cell's activity left after each generation.
+const DECAY: f32 = 0.95;
+/// Activity below which a cell is forgotten.
+const FORGET_BELOW: f32 = 0.05;
+/// Activity drawn at full heat.
+const FULL_HEAT: f32 = 4.0;
+
+/// How busy each cell has been lately: every birth or death adds one to the cell's counter,
+/// and all counters fade a little each generation, so the heat follows the chaotic parts of
+/// a pattern and cools off where it settles down.
+#[derive(Clone)]
+pub struct ActivityMap {
+    heat: HashMap<Cell, f32>,
+    /// The live cells last recorded, to find births and deaths against
+    previous: HashSet<Cell>,
+}
+
+impl ActivityMap {
+    /// A cold map, measuring changes from `cells` on.
+    pub fn new(cells: &HashSet<Cell>) -> Self {
+        Self {
+            heat: HashMap::new(),
+            previous: cells.clone(),
+        }
+    }
+
+    /// Fades every counter, then counts the births and deaths since the last record.
+    pub fn record(&mut self, cells: &HashSet<Cell>) {
+        self.heat.retain(|_, heat| {
+            *heat *= DECAY;
+            *heat >= FORGET_BELOW
+        });
+        for &cell in cells.symmetric_difference(&self.previous) {
+            *self.heat.entry(cell).or_default() += 1.0;
+        }
+        self.previous.clone_from(cells);
+    }
+
+    /// Starts over from `cells`, e.g. after loading a pattern.
+    pub fn restart(&mut self, cells: &HashSet<Cell>) {
+        *self = Self::new(cells);
+    }
+
+    /// Each warm cell with its heat from 0 (cold) to 1.
+    pub fn cells(&self) -> impl Iterator<Item = (Cell, f32)> + '_ {
+        self.heat
+            .iter()
+            .map(|(&cell, &heat)| (cell, (heat / FULL_HEAT).min(1.0)))
+    }
+}
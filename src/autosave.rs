@@ -0,0 +1,88 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+/// When an autosave is due.
+#[derive(Clone, Copy)]
+enum Interval {
+    Generations(usize),
+    Time(Duration),
+}
+
+/// Periodically written copies of the save state, rotating through a fixed number of files
+/// so the oldest is overwritten first.
+#[derive(Clone)]
+pub struct Autosave {
+    interval: Interval,
+    keep: usize,
+    last_generation: usize,
+    last_time: Instant,
+    /// The state written last time, so an idle session doesn't rotate away older saves
+    last_json: Option<String>,
+}
+
+impl Autosave {
+    /// Parses an interval of `N` generations or `Ns` seconds, e.g. `500` or `60s`.
+    pub fn from_string(interval_str: &str, keep: usize) -> Result<Self, String> {
+        let interval = match interval_str.trim().strip_suffix('s') {
+            Some(seconds) => seconds
+                .parse::<u64>()
+                .ok()
+                .filter(|&seconds| seconds > 0)
+                .map(|seconds| Interval::Time(Duration::from_secs(seconds))),
+            None => interval_str
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|&generations| generations > 0)
+                .map(Interval::Generations),
+        }
+        .ok_or_else(|| {
+            "Invalid autosave interval. Expected generations (e.g. 500) or seconds (e.g. 60s)."
+                .to_string()
+        })?;
+        Ok(Self {
+            interval,
+            keep: keep.max(1),
+            last_generation: 0,
+            last_time: Instant::now(),
+            last_json: None,
+        })
+    }
+
+    pub fn due(&self, generation: usize) -> bool {
+        match self.interval {
+            // The counter goes back down when the pattern is reset
+            Interval::Generations(n) => generation.abs_diff(self.last_generation) >= n,
+            Interval::Time(interval) => self.last_time.elapsed() >= interval,
+        }
+    }
+
+    /// Writes `json` to the next file in the rotation, unless it's unchanged since the last
+    /// autosave. Returns the path written.
+    pub fn save(&mut self, generation: usize, json: String) -> Result<Option<PathBuf>, String> {
+        self.last_generation = generation;
+        self.last_time = Instant::now();
+        if self.last_json.as_ref() == Some(&json) {
+            return Ok(None);
+        }
+        let path = self.next_path();
+        fs::write(&path, &json)
+            .map_err(|err| format!("Failed to autosave to {}: {}", path.display(), err))?;
+        self.last_json = Some(json);
+        Ok(Some(path))
+    }
+
+    /// The first missing file in the rotation, or else the one written longest ago.
+    fn next_path(&self) -> PathBuf {
+        let modified = |path: &PathBuf| {
+            fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        };
+        (1..=self.keep)
+            .map(|k| PathBuf::from(format!("celleste_autosave_{}.json", k)))
+            .min_by_key(|path| modified(path).unwrap_or(SystemTime::UNIX_EPOCH))
+            .unwrap_or_else(|| PathBuf::from("celleste_autosave_1.json"))
+    }
+}
@@ -0,0 +1,64 @@
+use crate::Cell;
+
+/// Largest brush radius, in cells.
+const MAX_RADIUS: i32 = 50;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BrushShape {
+    Square,
+    Circle,
+}
+
+/// The footprint painting and erasing cover around each cell under the mouse.
+#[derive(Clone, Copy)]
+pub struct Brush {
+    pub shape: BrushShape,
+    /// Cells from the center to the edge; 0 paints single cells
+    pub radius: i32,
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self {
+            shape: BrushShape::Square,
+            radius: 0,
+        }
+    }
+}
+
+impl Brush {
+    pub fn grow(&mut self) {
+        self.radius = (self.radius + 1).min(MAX_RADIUS);
+    }
+
+    pub fn shrink(&mut self) {
+        self.radius = (self.radius - 1).max(0);
+    }
+
+    pub fn toggle_shape(&mut self) {
+        self.shape = match self.shape {
+            BrushShape::Square => BrushShape::Circle,
+            BrushShape::Circle => BrushShape::Square,
+        };
+    }
+
+    pub fn describe(&self) -> String {
+        let shape = match self.shape {
+            BrushShape::Square => "square",
+            BrushShape::Circle => "circle",
+        };
+        format!("Brush: {} of radius {}", shape, self.radius)
+    }
+
+    /// The cells the brush covers when centered on `center`, short of the edge of the universe.
+    pub fn cells_at(&self, center: Cell) -> impl Iterator<Item = Cell> + '_ {
+        let r = self.radius;
+        (-r..=r)
+            .flat_map(move |dy| (-r..=r).map(move |dx| (dx, dy)))
+            // The half-cell margin rounds the circle out instead of leaving single-cell nubs
+            .filter(move |&(dx, dy)| {
+                self.shape == BrushShape::Square || dx * dx + dy * dy <= r * r + r
+            })
+            .filter_map(move |(dx, dy)| center.offset(dx, dy))
+    }
+}
@@ -0,0 +1,111 @@
+use ggez::graphics::Rect;
+use serde::{Deserialize, Serialize};
+
+use crate::Cell;
+
+/// Where the grid is seen from: how large a cell is on screen and where the origin cell's
+/// top-left corner lands, in pixels.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Camera {
+    pub offset_x: f32,
+    pub offset_y: f32,
+    pub cell_size: f32,
+}
+
+impl Camera {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            offset_x: 0.0,
+            offset_y: 0.0,
+            cell_size,
+        }
+    }
+
+    /// Screen position of `cell`'s top-left corner.
+    pub fn cell_to_screen(&self, cell: Cell) -> (f32, f32) {
+        (
+            cell.0 as f32 * self.cell_size + self.offset_x,
+            cell.1 as f32 * self.cell_size + self.offset_y,
+        )
+    }
+
+    /// The cell under a screen position.
+    pub fn screen_to_cell(&self, x: f32, y: f32) -> Cell {
+        let grid_x = ((x - self.offset_x) / self.cell_size).floor() as i32;
+        let grid_y = ((y - self.offset_y) / self.cell_size).floor() as i32;
+        Cell(grid_x, grid_y)
+    }
+
+    /// The screen rectangle `cell` covers.
+    pub fn cell_rect(&self, cell: Cell) -> Rect {
+        self.region_rect(cell, cell)
+    }
+
+    /// The screen rectangle covering every cell between two corners.
+    pub fn region_rect(&self, min: Cell, max: Cell) -> Rect {
+        let (x, y) = self.cell_to_screen(min);
+        Rect::new(
+            x,
+            y,
+            span(min.0, max.0) * self.cell_size,
+            span(min.1, max.1) * self.cell_size,
+        )
+    }
+
+    /// Whether any part of `cell` lands inside a `width` x `height` window.
+    pub fn on_screen(&self, cell: Cell, width: f32, height: f32) -> bool {
+        let (min, max) = (
+            self.screen_to_cell(0.0, 0.0),
+            self.screen_to_cell(width, height),
+        );
+        (min.0..=max.0).contains(&cell.0) && (min.1..=max.1).contains(&cell.1)
+    }
+
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        self.offset_x += dx;
+        self.offset_y += dy;
+    }
+
+    /// Scales cells by `factor`, keeping the point of the grid under `(x, y)` where it is.
+    pub fn zoom_at(&mut self, factor: f32, x: f32, y: f32) {
+        self.offset_x = x - (x - self.offset_x) * factor;
+        self.offset_y = y - (y - self.offset_y) * factor;
+        self.cell_size *= factor;
+    }
+
+    /// Zooms and centers on the cells between two corners, so they fill `fill` of the view.
+    pub fn fit(&mut self, min: Cell, max: Cell, width: f32, height: f32, fill: f32) {
+        let columns = span(min.0, max.0);
+        let rows = span(min.1, max.1);
+        self.cell_size = (width * fill / columns).min(height * fill / rows);
+        self.offset_x = width / 2.0 - (min.0 as f32 + columns / 2.0) * self.cell_size;
+        self.offset_y = height / 2.0 - (min.1 as f32 + rows / 2.0) * self.cell_size;
+    }
+
+    /// Pans so the middle of `cell` is in the middle of a `width` x `height` view.
+    pub fn center_on(&mut self, cell: Cell, width: f32, height: f32) {
+        self.offset_x = width / 2.0 - (cell.0 as f32 + 0.5) * self.cell_size;
+        self.offset_y = height / 2.0 - (cell.1 as f32 + 0.5) * self.cell_size;
+    }
+
+    /// Pans just far enough to bring all of `cell` into a `width` x `height` view.
+    pub fn keep_in_view(&mut self, cell: Cell, width: f32, height: f32) {
+        let (x, y) = self.cell_to_screen(cell);
+        if x < 0.0 {
+            self.offset_x -= x;
+        } else if x + self.cell_size > width {
+            self.offset_x -= x + self.cell_size - width;
+        }
+        if y < 0.0 {
+            self.offset_y -= y;
+        } else if y + self.cell_size > height {
+            self.offset_y -= y + self.cell_size - height;
+        }
+    }
+}
+
+/// Cells from `min` to `max` inclusive, counted in `f64` since a span across the whole `i32`
+/// range overflows it.
+fn span(min: i32, max: i32) -> f32 {
+    (max as f64 - min as f64 + 1.0) as f32
+}
@@ -0,0 +1,174 @@
+use ggez::graphics::{Image, ImageFormat};
+use ggez::Context;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{paste::Paste, patterns, raster::Frame, Celleste, Rules};
+
+/// Width and height of each thumbnail, in pixels.
+pub const THUMBNAIL_SIZE: u32 = 128;
+/// Space each entry takes up, with its name and rule under the thumbnail.
+pub const CARD_WIDTH: f32 = 160.0;
+pub const CARD_HEIGHT: f32 = 180.0;
+/// Room left above the cards for the title.
+pub const TOP_MARGIN: f32 = 60.0;
+const SIDE_MARGIN: f32 = 20.0;
+/// Fraction of a thumbnail the pattern's bounding box is scaled to fill.
+const THUMBNAIL_FILL: f32 = 0.9;
+/// Longest name shown under a thumbnail before it's cut short.
+const MAX_NAME_CHARS: usize = 18;
+
+/// A pattern file found in the catalog directory.
+pub struct Entry {
+    pub name: String,
+    /// Rules the file asks for; stamping a pattern leaves the current rules alone
+    pub rules: Option<String>,
+    pub paste: Paste,
+    thumbnail: Frame,
+    /// The thumbnail uploaded for drawing, once it has been shown
+    image: Option<Image>,
+}
+
+impl Entry {
+    fn load(path: &Path) -> Result<Self, String> {
+        let (pattern, _) = patterns::load_pattern(path)?;
+        let rules = Rules::from_string(pattern.rules.as_deref().unwrap_or("B3/S23"))?;
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("pattern")
+            .to_string();
+        let paste = Paste::new(pattern.cells.iter().copied());
+        let mut universe = Celleste::new(pattern.cells.into_iter().collect(), 1.0, rules, true);
+        let size = THUMBNAIL_SIZE as f32;
+        universe.fit_to_pattern(size, size, THUMBNAIL_FILL);
+        Ok(Self {
+            name,
+            rules: pattern.rules,
+            paste,
+            thumbnail: universe.render_frame(THUMBNAIL_SIZE, THUMBNAIL_SIZE),
+            image: None,
+        })
+    }
+
+    /// The name as it fits under the thumbnail.
+    pub fn label(&self) -> String {
+        if self.name.chars().count() <= MAX_NAME_CHARS {
+            return self.name.clone();
+        }
+        let mut label: String = self.name.chars().take(MAX_NAME_CHARS - 1).collect();
+        label.push('…');
+        label
+    }
+}
+
+/// The pattern files of a directory, shown as a grid of thumbnails to pick one to stamp.
+pub struct Catalog {
+    pub dir: PathBuf,
+    pub entries: Vec<Entry>,
+    pub selected: usize,
+    /// First row of cards on screen, moved along to keep the selection in view
+    first_row: usize,
+}
+
+impl Catalog {
+    /// Reads and renders every pattern in `dir`, skipping the ones that fail to load.
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+            .map_err(|err| format!("Failed to read {}: {}", dir.display(), err))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| patterns::is_pattern_file(path))
+            .collect();
+        paths.sort();
+        let entries = paths
+            .iter()
+            .filter_map(|path| match Entry::load(path) {
+                Ok(entry) => Some(entry),
+                Err(err) => {
+                    eprintln!("Skipping {}: {}", path.display(), err);
+                    None
+                }
+            })
+            .collect();
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            entries,
+            selected: 0,
+            first_row: 0,
+        })
+    }
+
+    pub fn selected(&self) -> Option<&Entry> {
+        self.entries.get(self.selected)
+    }
+
+    /// Cards per row in a view `width` pixels wide.
+    pub fn columns(width: f32) -> usize {
+        (((width - 2.0 * SIDE_MARGIN) / CARD_WIDTH) as usize).max(1)
+    }
+
+    fn visible_rows(height: f32) -> usize {
+        (((height - TOP_MARGIN) / CARD_HEIGHT) as usize).max(1)
+    }
+
+    /// Moves the selection `dx` cards across and `dy` rows down, stopping at the ends.
+    pub fn move_selection(&mut self, dx: i32, dy: i32, width: f32, height: f32) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let columns = Self::columns(width) as i64;
+        let target = self.selected as i64 + dx as i64 + dy as i64 * columns;
+        self.selected = target.clamp(0, self.entries.len() as i64 - 1) as usize;
+        let row = self.selected / columns as usize;
+        let rows = Self::visible_rows(height);
+        if row < self.first_row {
+            self.first_row = row;
+        } else if row >= self.first_row + rows {
+            self.first_row = row + 1 - rows;
+        }
+    }
+
+    /// Indices of the entries on screen, with the top-left corner of each card.
+    pub fn visible(&self, width: f32, height: f32) -> Vec<(usize, [f32; 2])> {
+        let columns = Self::columns(width);
+        let first = self.first_row * columns;
+        let last = (first + Self::visible_rows(height) * columns).min(self.entries.len());
+        (first..last)
+            .map(|index| {
+                let (row, column) = ((index - first) / columns, (index - first) % columns);
+                let x = SIDE_MARGIN + column as f32 * CARD_WIDTH;
+                let y = TOP_MARGIN + row as f32 * CARD_HEIGHT;
+                (index, [x, y])
+            })
+            .collect()
+    }
+
+    /// The entry whose card is under the screen position `(x, y)`.
+    pub fn entry_at(&self, x: f32, y: f32, width: f32, height: f32) -> Option<usize> {
+        self.visible(width, height)
+            .into_iter()
+            .find(|(_, [left, top])| {
+                (*left..left + CARD_WIDTH).contains(&x) && (*top..top + CARD_HEIGHT).contains(&y)
+            })
+            .map(|(index, _)| index)
+    }
+
+    /// The thumbnail of the `index`th entry, uploaded the first time it's needed.
+    pub fn thumbnail(&mut self, ctx: &Context, index: usize) -> Image {
+        let entry = &mut self.entries[index];
+        entry
+            .image
+            .get_or_insert_with(|| {
+                let frame = &entry.thumbnail;
+                Image::from_pixels(
+                    ctx,
+                    &frame.pixels,
+                    ImageFormat::Rgba8UnormSrgb,
+                    frame.width,
+                    frame.height,
+                )
+            })
+            .clone()
+    }
+}
@@ -0,0 +1,31 @@
+use arboard::Clipboard;
+
+/// The desktop's clipboard, opened on first use. On X11 and Wayland copied text is only
+/// offered to other programs while a handle is open, so it's kept for the session.
+#[derive(Default)]
+pub struct SystemClipboard {
+    clipboard: Option<Clipboard>,
+}
+
+impl SystemClipboard {
+    fn open(&mut self) -> Result<&mut Clipboard, String> {
+        if self.clipboard.is_none() {
+            let clipboard =
+                Clipboard::new().map_err(|err| format!("System clipboard unavailable: {}", err))?;
+            self.clipboard = Some(clipboard);
+        }
+        Ok(self.clipboard.as_mut().unwrap())
+    }
+
+    pub fn text(&mut self) -> Result<String, String> {
+        self.open()?
+            .get_text()
+            .map_err(|err| format!("Failed to read the clipboard: {}", err))
+    }
+
+    pub fn set_text(&mut self, text: String) -> Result<(), String> {
+        self.open()?
+            .set_text(text)
+            .map_err(|err| format!("Failed to write the clipboard: {}", err))
+    }
+}
@@ -0,0 +1,161 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use std::time::{Duration, Instant};
+
+use crate::{library, patterns, Cell};
+
+enum DemoPattern {
+    /// A pattern in plaintext `.cells` form.
+    Plaintext(&'static str),
+    /// A random square of the given side and fill density.
+    Soup { size: i32, density: f64 },
+    /// A simulation from the core registry, started from its defaults on a
+    /// `SIMULATION_SIZE` grid in place of the Life grid.
+    Simulation(&'static str),
+}
+
+/// Width and height in cells of the grids simulation entries run on.
+pub const SIMULATION_SIZE: (i32, i32) = (160, 120);
+
+pub struct DemoEntry {
+    pub caption: &'static str,
+    /// Rules the pattern runs under; simulation entries bring their own
+    pub rules: &'static str,
+    pattern: DemoPattern,
+}
+
+impl DemoEntry {
+    /// The registered simulation this entry runs, if it isn't a pattern.
+    pub fn simulation(&self) -> Option<&'static str> {
+        match self.pattern {
+            DemoPattern::Simulation(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    pub fn cells(&self, seed: u64) -> Vec<Cell> {
+        match self.pattern {
+            DemoPattern::Plaintext(text) => patterns::parse_cells(text)
+                .map(|pattern| pattern.cells.into_iter().collect())
+                .unwrap_or_default(),
+            DemoPattern::Soup { size, density } => {
+                let mut rng = StdRng::seed_from_u64(seed);
+                (0..size)
+                    .flat_map(|y| (0..size).map(move |x| Cell(x, y)))
+                    .filter(|_| rng.gen_bool(density))
+                    .collect()
+            }
+            DemoPattern::Simulation(_) => Vec::new(),
+        }
+    }
+}
+
+/// The tour, in order.
+const ENTRIES: &[DemoEntry] = &[
+    DemoEntry {
+        caption: "Gosper glider gun - Conway's Life (B3/S23)",
+        rules: "B3/S23",
+        pattern: DemoPattern::Plaintext(library::GOSPER_GLIDER_GUN),
+    },
+    DemoEntry {
+        caption: "Acorn - a methuselah that runs for 5206 generations (B3/S23)",
+        rules: "B3/S23",
+        pattern: DemoPattern::Plaintext(library::ACORN),
+    },
+    DemoEntry {
+        caption: "Replicator - HighLife (B36/S23)",
+        rules: "B36/S23",
+        pattern: DemoPattern::Plaintext("..OOO\n.O..O\nO...O\nO..O.\nOOO.."),
+    },
+    DemoEntry {
+        caption: "Random soup - Day & Night (B3678/S34678)",
+        rules: "B3678/S34678",
+        pattern: DemoPattern::Soup {
+            size: 96,
+            density: 0.5,
+        },
+    },
+    DemoEntry {
+        caption: "Random soup - Brian's Brain (B2/S/C3)",
+        rules: "B2/S/C3",
+        pattern: DemoPattern::Soup {
+            size: 64,
+            density: 0.3,
+        },
+    },
+    DemoEntry {
+        caption: "BZ reaction spirals - Turner's Belousov-Zhabotinsky model",
+        rules: "",
+        pattern: DemoPattern::Simulation("bz"),
+    },
+    DemoEntry {
+        caption: "Random soup - Star Wars (B2/S345/C4)",
+        rules: "B2/S345/C4",
+        pattern: DemoPattern::Soup {
+            size: 64,
+            density: 0.35,
+        },
+    },
+    DemoEntry {
+        caption: "Random soup - Bosco's rule, Larger than Life (R5,C0,M1,S34..58,B34..45,NM)",
+        rules: "R5,C0,M1,S34..58,B34..45,NM",
+        pattern: DemoPattern::Soup {
+            size: 48,
+            density: 0.5,
+        },
+    },
+    DemoEntry {
+        caption: "Random soup - Maze (B3/S12345)",
+        rules: "B3/S12345",
+        pattern: DemoPattern::Soup {
+            size: 24,
+            density: 0.35,
+        },
+    },
+    DemoEntry {
+        caption: "Random soup - Seeds (B2/S)",
+        rules: "B2/S",
+        pattern: DemoPattern::Soup {
+            size: 8,
+            density: 0.4,
+        },
+    },
+];
+
+/// Cycles through the tour entries, moving on every `interval`.
+pub struct Demo {
+    index: usize,
+    started: Instant,
+    interval: Duration,
+    shown: usize,
+}
+
+impl Demo {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            index: 0,
+            started: Instant::now(),
+            interval,
+            shown: 0,
+        }
+    }
+
+    pub fn current(&self) -> &'static DemoEntry {
+        &ENTRIES[self.index]
+    }
+
+    /// Seed for the current entry's soup; changes every time round the tour.
+    pub fn seed(&self) -> u64 {
+        self.shown as u64
+    }
+
+    pub fn due(&self) -> bool {
+        self.started.elapsed() >= self.interval
+    }
+
+    pub fn advance(&mut self) {
+        self.index = (self.index + 1) % ENTRIES.len();
+        self.started = Instant::now();
+        self.shown += 1;
+    }
+}
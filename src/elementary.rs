@@ -0,0 +1,60 @@
+use std::collections::HashSet;
+
+use crate::Cell;
+
+/// Rows kept on screen; older ones are dropped so a long run doesn't grow without bound.
+const MAX_ROWS: i32 = 10_000;
+
+/// One of Wolfram's 256 elementary cellular automata. The row of cells wraps around at `width`,
+/// and each generation is written one row below the last, so the live cells hold the history.
+#[derive(Clone, Copy)]
+pub struct Elementary {
+    rule: u8,
+    width: i32,
+    /// The newest row, which the next generation is computed from
+    row: i32,
+}
+
+impl Elementary {
+    pub fn new(rule: u8, width: i32) -> Self {
+        Self {
+            rule,
+            width: width.max(1),
+            row: 0,
+        }
+    }
+
+    pub fn row(&self) -> i32 {
+        self.row
+    }
+
+    /// A single live cell in the middle of the first row, the classic starting point.
+    pub fn seed(&self) -> Cell {
+        Cell(self.width / 2, 0)
+    }
+
+    /// Starts over from the first row.
+    pub fn restart(&mut self) {
+        self.row = 0;
+    }
+
+    /// Computes the next row from the newest one and appends it below.
+    pub fn step(&mut self, alive_cells: &mut HashSet<Cell>) {
+        let alive = |x: i32| alive_cells.contains(&Cell(x.rem_euclid(self.width), self.row)) as u8;
+        let next: Vec<Cell> = (0..self.width)
+            .filter(|&x| {
+                let pattern = alive(x - 1) << 2 | alive(x) << 1 | alive(x + 1);
+                self.rule >> pattern & 1 == 1
+            })
+            .map(|x| Cell(x, self.row + 1))
+            .collect();
+        self.row += 1;
+        alive_cells.extend(next);
+        let oldest = self.row - MAX_ROWS;
+        if oldest >= 0 {
+            for x in 0..self.width {
+                alive_cells.remove(&Cell(x, oldest));
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use ggez::{
+    event::{
+        self,
+        winit_event::{ElementState, Event, KeyboardInput, MouseScrollDelta, WindowEvent},
+        EventHandler,
+    },
+    input::keyboard::KeyInput,
+    winit::{
+        dpi,
+        event_loop::{ControlFlow, EventLoop},
+    },
+    Context, GameResult,
+};
+
+use std::path::PathBuf;
+
+/// Events ggez's own loop doesn't pass on to an `EventHandler`.
+pub trait WindowEvents: EventHandler {
+    /// A file was dragged from the desktop and dropped onto the window.
+    fn file_dropped_event(&mut self, ctx: &mut Context, path: PathBuf) -> GameResult;
+}
+
+/// Runs the main loop like `ggez::event::run`, but also delivers dropped files.
+pub fn run<S: WindowEvents + 'static>(
+    mut ctx: Context,
+    event_loop: EventLoop<()>,
+    mut state: S,
+) -> ! {
+    event_loop.run(move |mut event, _, control_flow| {
+        let ctx = &mut ctx;
+        let state = &mut state;
+
+        if ctx.quit_requested {
+            ctx.quit_requested = false;
+            quit(ctx, state, control_flow);
+        }
+        if !ctx.continuing {
+            *control_flow = ControlFlow::Exit;
+            return;
+        }
+        *control_flow = ControlFlow::Poll;
+
+        event::process_event(ctx, &mut event);
+        let result = match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::Resized(size) => {
+                    state.resize_event(ctx, size.width as f32, size.height as f32)
+                }
+                WindowEvent::CloseRequested => {
+                    quit(ctx, state, control_flow);
+                    Ok(())
+                }
+                WindowEvent::Focused(gained) => state.focus_event(ctx, gained),
+                WindowEvent::ReceivedCharacter(ch) => state.text_input_event(ctx, ch),
+                WindowEvent::DroppedFile(path) => state.file_dropped_event(ctx, path),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: key_state,
+                            virtual_keycode: keycode,
+                            scancode,
+                            ..
+                        },
+                    ..
+                } => {
+                    let input = KeyInput {
+                        scancode,
+                        keycode,
+                        mods: ctx.keyboard.active_mods(),
+                    };
+                    match key_state {
+                        ElementState::Pressed => {
+                            let repeat = ctx.keyboard.is_key_repeated();
+                            state.key_down_event(ctx, input, repeat)
+                        }
+                        ElementState::Released => state.key_up_event(ctx, input),
+                    }
+                }
+                WindowEvent::MouseWheel { delta, .. } => {
+                    let (x, y) = match delta {
+                        MouseScrollDelta::LineDelta(x, y) => (x, y),
+                        MouseScrollDelta::PixelDelta(pos) => {
+                            let scale_factor = ctx.gfx.window().scale_factor();
+                            let dpi::LogicalPosition { x, y } = pos.to_logical::<f32>(scale_factor);
+                            (x, y)
+                        }
+                    };
+                    state.mouse_wheel_event(ctx, x, y)
+                }
+                WindowEvent::MouseInput {
+                    state: button_state,
+                    button,
+                    ..
+                } => {
+                    let position = ctx.mouse.position();
+                    match button_state {
+                        ElementState::Pressed => {
+                            state.mouse_button_down_event(ctx, button, position.x, position.y)
+                        }
+                        ElementState::Released => {
+                            state.mouse_button_up_event(ctx, button, position.x, position.y)
+                        }
+                    }
+                }
+                WindowEvent::CursorMoved { .. } => {
+                    let position = ctx.mouse.position();
+                    let delta = ctx.mouse.last_delta();
+                    state.mouse_motion_event(ctx, position.x, position.y, delta.x, delta.y)
+                }
+                _ => Ok(()),
+            },
+            Event::MainEventsCleared => {
+                ctx.time.tick();
+                let result = state
+                    .update(ctx)
+                    .and_then(|()| ctx.gfx.begin_frame())
+                    .and_then(|()| state.draw(ctx))
+                    .and_then(|()| ctx.gfx.end_frame());
+                // Mouse deltas accumulate over a frame, and key repeats compare against it
+                ctx.mouse.reset_delta();
+                ctx.keyboard.save_keyboard_state();
+                ctx.mouse.save_mouse_state();
+                result
+            }
+            _ => Ok(()),
+        };
+        if let Err(err) = result {
+            eprintln!("Error: {}", err);
+            *control_flow = ControlFlow::Exit;
+        }
+    })
+}
+
+/// Gives the state a chance to clean up, then ends the loop unless it asks to keep going.
+fn quit<S: WindowEvents>(ctx: &mut Context, state: &mut S, control_flow: &mut ControlFlow) {
+    match state.quit_event(ctx) {
+        Ok(false) => ctx.continuing = false,
+        Ok(true) => {}
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            *control_flow = ControlFlow::Exit;
+        }
+    }
+}
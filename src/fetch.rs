@@ -0,0 +1,139 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use celleste_core::patterns;
+
+/// Where LifeWiki's pattern collection serves its files, by name.
+const LIFEWIKI_PATTERNS: &str = "https://conwaylife.com/patterns/";
+/// Formats a pattern named without an extension is looked for in, in order.
+const FORMATS: &[&str] = &["rle", "cells"];
+
+/// The cache `--pattern` keeps downloads in: `$XDG_CACHE_HOME/celleste/patterns`, falling back
+/// to `~/.cache/celleste/patterns` and then `./celleste_patterns`.
+pub fn default_cache_dir() -> PathBuf {
+    env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+        .map(|cache| cache.join("celleste").join("patterns"))
+        .unwrap_or_else(|| PathBuf::from("./celleste_patterns"))
+}
+
+/// A file a pattern may be cached as, and where to download it from.
+struct Source {
+    file_name: String,
+    url: String,
+}
+
+/// Finds the pattern `spec` names in `cache_dir`, downloading it there first if it's missing
+/// and the `fetch` feature is enabled. `spec` is a LifeWiki pattern name such as
+/// `gosperglidergun` (or `gosperglidergun.cells` for a particular format), or the URL of any
+/// pattern file, which is cached under its file name. A local pattern file is used where it is.
+pub fn resolve(spec: &str, cache_dir: &Path) -> Result<PathBuf, String> {
+    let local = Path::new(spec);
+    if local.is_file() && patterns::is_pattern_file(local) {
+        return Ok(local.to_path_buf());
+    }
+    let sources = sources(spec)?;
+    if let Some(path) = sources
+        .iter()
+        .map(|source| cache_dir.join(&source.file_name))
+        .find(|path| path.is_file())
+    {
+        return Ok(path);
+    }
+    download(&sources, cache_dir)
+}
+
+fn sources(spec: &str) -> Result<Vec<Source>, String> {
+    if spec.starts_with("http://") || spec.starts_with("https://") {
+        let path = spec.split(['?', '#']).next().unwrap_or(spec);
+        let file_name = path.rsplit('/').next().unwrap_or_default();
+        if !patterns::is_pattern_file(Path::new(file_name)) {
+            return Err(format!("{} doesn't point to an .rle/.cells/.mc file", spec));
+        }
+        return Ok(vec![Source {
+            file_name: file_name.to_string(),
+            url: spec.to_string(),
+        }]);
+    }
+    // LifeWiki file names are the pattern's name in lowercase without spaces or punctuation
+    let (name, format) = match spec.rsplit_once('.') {
+        Some((name, format)) if patterns::is_pattern_file(Path::new(spec)) => {
+            (name, Some(format.to_ascii_lowercase()))
+        }
+        _ => (spec, None),
+    };
+    let name: String = name
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if name.is_empty() {
+        return Err(format!("'{}' isn't a pattern name", spec));
+    }
+    let formats = match &format {
+        Some(format) => vec![format.as_str()],
+        None => FORMATS.to_vec(),
+    };
+    Ok(formats
+        .into_iter()
+        .map(|format| {
+            let file_name = format!("{}.{}", name, format);
+            Source {
+                url: format!("{}{}", LIFEWIKI_PATTERNS, file_name),
+                file_name,
+            }
+        })
+        .collect())
+}
+
+/// Downloads the first of `sources` that exists into `cache_dir`, keeping it only if it
+/// loads as a pattern.
+#[cfg(feature = "fetch")]
+fn download(sources: &[Source], cache_dir: &Path) -> Result<PathBuf, String> {
+    use std::fs;
+    use std::time::Duration;
+
+    fs::create_dir_all(cache_dir)
+        .map_err(|err| format!("Failed to create {}: {}", cache_dir.display(), err))?;
+    let mut last_error = String::new();
+    for source in sources {
+        println!("Downloading {}", source.url);
+        let response = match ureq::get(&source.url)
+            .timeout(Duration::from_secs(30))
+            .call()
+        {
+            Ok(response) => response,
+            // Not in this format; try the next one
+            Err(ureq::Error::Status(404, _)) => {
+                last_error = format!("{} was not found", source.url);
+                continue;
+            }
+            // The error names the URL already
+            Err(err) => return Err(format!("Failed to download {}", err)),
+        };
+        let text = response
+            .into_string()
+            .map_err(|err| format!("Failed to download {}: {}", source.url, err))?;
+        let path = cache_dir.join(&source.file_name);
+        fs::write(&path, text)
+            .map_err(|err| format!("Failed to write {}: {}", path.display(), err))?;
+        // Error pages served with a success status mustn't stay in the cache
+        if let Err(err) = patterns::load_pattern(&path) {
+            let _ = fs::remove_file(&path);
+            return Err(format!("{} isn't a valid pattern: {}", source.url, err));
+        }
+        return Ok(path);
+    }
+    Err(last_error)
+}
+
+#[cfg(not(feature = "fetch"))]
+fn download(sources: &[Source], cache_dir: &Path) -> Result<PathBuf, String> {
+    Err(format!(
+        "{} is not in {}; build with `--features fetch` to download it from {}",
+        sources[0].file_name,
+        cache_dir.display(),
+        sources[0].url
+    ))
+}
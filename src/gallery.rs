@@ -0,0 +1,97 @@
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    Delay, RgbaImage,
+};
+use rayon::prelude::*;
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use crate::{patterns, raster::Frame, Celleste, Rules};
+
+/// Fraction of a thumbnail the pattern's bounding box is scaled to fill.
+const THUMBNAIL_FILL: f32 = 0.9;
+/// Animated previews leave more room, since patterns usually grow.
+const GIF_FILL: f32 = 0.5;
+const GIF_FRAME_MS: u32 = 100;
+
+pub struct GalleryOptions {
+    pub out_dir: PathBuf,
+    pub size: u32,
+    pub gif_generations: Option<usize>,
+}
+
+/// Renders every pattern file in `dir` into `options.out_dir`, in parallel.
+pub fn render_gallery(dir: &Path, options: &GalleryOptions) -> Result<(), String> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|err| format!("Failed to read {}: {}", dir.display(), err))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| patterns::is_pattern_file(path))
+        .collect();
+    paths.sort();
+    fs::create_dir_all(&options.out_dir)
+        .map_err(|err| format!("Failed to create {}: {}", options.out_dir.display(), err))?;
+
+    let failures = paths
+        .par_iter()
+        .filter(|path| match render_one(path, options) {
+            Ok(()) => {
+                println!("Rendered {}", path.display());
+                false
+            }
+            Err(err) => {
+                eprintln!("Skipping {}: {}", path.display(), err);
+                true
+            }
+        })
+        .count();
+    println!(
+        "Rendered {} of {} patterns into {}",
+        paths.len() - failures,
+        paths.len(),
+        options.out_dir.display()
+    );
+    Ok(())
+}
+
+fn render_one(path: &Path, options: &GalleryOptions) -> Result<(), String> {
+    let (pattern, _) = patterns::load_pattern(path)?;
+    let rules = Rules::from_string(pattern.rules.as_deref().unwrap_or("B3/S23"))?;
+    let mut universe = Celleste::new(pattern.cells.into_iter().collect(), 1.0, rules, true);
+    let size = options.size as f32;
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("pattern");
+
+    universe.fit_to_pattern(size, size, THUMBNAIL_FILL);
+    let thumbnail = to_image(universe.render_frame(options.size, options.size))?;
+    let png_path = options.out_dir.join(format!("{}.png", stem));
+    thumbnail.save(&png_path).map_err(|err| err.to_string())?;
+
+    if let Some(generations) = options.gif_generations {
+        universe.fit_to_pattern(size, size, GIF_FILL);
+        let mut frames = Vec::with_capacity(generations + 1);
+        for _ in 0..=generations {
+            let image = to_image(universe.render_frame(options.size, options.size))?;
+            let delay = Delay::from_numer_denom_ms(GIF_FRAME_MS, 1);
+            frames.push(image::Frame::from_parts(image, 0, 0, delay));
+            universe.step();
+        }
+        let gif_path = options.out_dir.join(format!("{}.gif", stem));
+        let file = File::create(&gif_path).map_err(|err| err.to_string())?;
+        let mut encoder = GifEncoder::new(file);
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(|err| err.to_string())?;
+        encoder
+            .encode_frames(frames)
+            .map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+fn to_image(frame: Frame) -> Result<RgbaImage, String> {
+    RgbaImage::from_raw(frame.width, frame.height, frame.pixels)
+        .ok_or_else(|| "Rendered frame has the wrong size".to_string())
+}
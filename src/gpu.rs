@@ -0,0 +1,290 @@
+use std::collections::HashSet;
+
+use crate::{topology::Torus, Cell};
+
+/// Evaluates a B/S rule for every cell of the torus, one invocation per cell.
+const SHADER: &str = "
+struct Params {
+    width: u32,
+    height: u32,
+    birth: u32,
+    survival: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var current: texture_2d<u32>;
+@group(0) @binding(2) var next: texture_storage_2d<r32uint, write>;
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.width || id.y >= params.height) {
+        return;
+    }
+    let width = i32(params.width);
+    let height = i32(params.height);
+    var count = 0u;
+    for (var dy = -1; dy <= 1; dy = dy + 1) {
+        for (var dx = -1; dx <= 1; dx = dx + 1) {
+            if (dx != 0 || dy != 0) {
+                let x = (i32(id.x) + dx + width) % width;
+                let y = (i32(id.y) + dy + height) % height;
+                count = count + textureLoad(current, vec2<i32>(x, y), 0).r;
+            }
+        }
+    }
+    let alive = textureLoad(current, vec2<i32>(id.xy), 0).r;
+    let counts = select(params.birth, params.survival, alive == 1u);
+    textureStore(next, vec2<i32>(id.xy), vec4<u32>((counts >> count) & 1u, 0u, 0u, 0u));
+}
+";
+
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Row pitch texture-to-buffer copies must be a multiple of.
+const COPY_ALIGNMENT: u32 = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+/// Steps a two-state Moore rule on a torus with a compute shader. The grid lives in a pair of
+/// `R32Uint` textures (one value per cell) that each generation reads from one and writes to
+/// the other.
+pub struct GpuStepper {
+    width: u32,
+    height: u32,
+    textures: [wgpu::Texture; 2],
+    /// `bind_groups[i]` reads `textures[i]` and writes the other one
+    bind_groups: [wgpu::BindGroup; 2],
+    params: wgpu::Buffer,
+    pipeline: wgpu::ComputePipeline,
+    readback: wgpu::Buffer,
+    padded_row: u32,
+    /// Index of the texture holding the newest generation
+    current: usize,
+}
+
+impl GpuStepper {
+    pub fn new(device: &wgpu::Device, torus: Torus) -> Result<Self, String> {
+        let (width, height) = (torus.width as u32, torus.height as u32);
+        let max = device.limits().max_texture_dimension_2d;
+        if width > max || height > max {
+            return Err(format!("The GPU handles grids up to {}x{} cells", max, max));
+        }
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = |label| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::R32Uint,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::STORAGE_BINDING
+                    | wgpu::TextureUsages::COPY_DST
+                    | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            })
+        };
+        let textures = [texture("celleste grid a"), texture("celleste grid b")];
+
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("celleste rule"),
+            size: 16,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let padded_row = (width * 4).div_ceil(COPY_ALIGNMENT) * COPY_ALIGNMENT;
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("celleste readback"),
+            size: padded_row as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("celleste step"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Uint,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: wgpu::TextureFormat::R32Uint,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let views = textures
+            .each_ref()
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()));
+        let bind_group = |from: usize| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("celleste step"),
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: params.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(&views[from]),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::TextureView(&views[1 - from]),
+                    },
+                ],
+            })
+        };
+        let bind_groups = [bind_group(0), bind_group(1)];
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("celleste step"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("celleste step"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("celleste step"),
+            layout: Some(&pipeline_layout),
+            module: &module,
+            entry_point: "main",
+        });
+
+        Ok(Self {
+            width,
+            height,
+            textures,
+            bind_groups,
+            params,
+            pipeline,
+            readback,
+            padded_row,
+            current: 0,
+        })
+    }
+
+    /// Sets the rule; bit `n` of `birth`/`survival` is set when `n` neighbors cause a birth or
+    /// let a cell survive.
+    pub fn set_rules(&self, queue: &wgpu::Queue, birth: u16, survival: u16) {
+        let params: Vec<u8> = [self.width, self.height, birth as u32, survival as u32]
+            .into_iter()
+            .flat_map(u32::to_le_bytes)
+            .collect();
+        queue.write_buffer(&self.params, 0, &params);
+    }
+
+    /// Replaces the grid on the GPU with `cells`, which must already lie inside the torus.
+    pub fn upload(&mut self, queue: &wgpu::Queue, cells: &HashSet<Cell>) {
+        let mut grid = vec![0u32; (self.width * self.height) as usize];
+        for cell in cells {
+            grid[(cell.1 as u32 * self.width + cell.0 as u32) as usize] = 1;
+        }
+        let bytes: Vec<u8> = grid.into_iter().flat_map(u32::to_le_bytes).collect();
+        queue.write_texture(
+            self.textures[self.current].as_image_copy(),
+            &bytes,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(self.width * 4),
+                rows_per_image: None,
+            },
+            self.size(),
+        );
+    }
+
+    /// Queues `generations` steps, ping-ponging between the two textures.
+    pub fn step(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, generations: usize) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("celleste step"),
+        });
+        for _ in 0..generations {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("celleste step"),
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.bind_groups[self.current], &[]);
+            pass.dispatch_workgroups(
+                self.width.div_ceil(WORKGROUP_SIZE),
+                self.height.div_ceil(WORKGROUP_SIZE),
+                1,
+            );
+            drop(pass);
+            self.current = 1 - self.current;
+        }
+        queue.submit(Some(encoder.finish()));
+    }
+
+    /// Copies the newest generation back, waiting for the GPU to finish.
+    pub fn download(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> HashSet<Cell> {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("celleste readback"),
+        });
+        encoder.copy_texture_to_buffer(
+            self.textures[self.current].as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &self.readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.padded_row),
+                    rows_per_image: None,
+                },
+            },
+            self.size(),
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let slice = self.readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| ());
+        device.poll(wgpu::Maintain::Wait);
+        let mut cells = HashSet::new();
+        {
+            let data = slice.get_mapped_range();
+            for (y, row) in data.chunks_exact(self.padded_row as usize).enumerate() {
+                for (x, value) in row.chunks_exact(4).take(self.width as usize).enumerate() {
+                    if value[0] != 0 {
+                        cells.insert(Cell(x as i32, y as i32));
+                    }
+                }
+            }
+        }
+        self.readback.unmap();
+        cells
+    }
+
+    fn size(&self) -> wgpu::Extent3d {
+        wgpu::Extent3d {
+            width: self.width,
+            height: self.height,
+            depth_or_array_layers: 1,
+        }
+    }
+}
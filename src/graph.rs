@@ -0,0 +1,55 @@
+use std::collections::VecDeque;
+
+/// Generations of population kept for the graph.
+const CAPACITY: usize = 1000;
+
+/// Population over the most recent generations, for the live chart overlay. Simulations with
+/// several populations record one count per population each generation.
+#[derive(Default)]
+pub struct PopulationGraph {
+    samples: VecDeque<Vec<usize>>,
+}
+
+impl PopulationGraph {
+    pub fn record(&mut self, population: usize) {
+        self.record_all(vec![population]);
+    }
+
+    /// Records the sizes of several populations charted together.
+    pub fn record_all(&mut self, populations: Vec<usize>) {
+        self.samples.push_back(populations);
+        if self.samples.len() > CAPACITY {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Starts over, e.g. after the generation counter is reset.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// The largest count recorded, across every population.
+    pub fn max(&self) -> usize {
+        self.samples.iter().flatten().copied().max().unwrap_or(0)
+    }
+
+    /// The samples of the `series`th population as a line through a `width` x `height` box
+    /// with its origin at the top left, oldest on the left and the largest count at the top.
+    /// Always spans the full width so the chart doesn't crawl in from the left while the
+    /// buffer fills.
+    pub fn points(&self, series: usize, width: f32, height: f32) -> Vec<[f32; 2]> {
+        let max = self.max().max(1) as f32;
+        let last = (self.samples.len().max(2) - 1) as f32;
+        self.samples
+            .iter()
+            .enumerate()
+            .map(|(i, sample)| {
+                let population = sample.get(series).copied().unwrap_or(0);
+                [
+                    i as f32 / last * width,
+                    height - population as f32 / max * height,
+                ]
+            })
+            .collect()
+    }
+}
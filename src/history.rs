@@ -0,0 +1,152 @@
+use parquet::{
+    basic::Compression,
+    data_type::{Int32Type, Int64Type},
+    errors::ParquetError,
+    file::{properties::WriterProperties, writer::SerializedFileWriter},
+    format::KeyValue,
+    schema::parser::parse_message_type,
+};
+
+use std::fs::File;
+use std::sync::Arc;
+
+use crate::Cell;
+
+/// Rows buffered before they are written out as one Parquet row group.
+const ROW_GROUP_ROWS: usize = 1 << 20;
+
+const SCHEMA: &str = "message history {
+    REQUIRED INT64 generation;
+    REQUIRED INT32 x;
+    REQUIRED INT32 y;
+}";
+
+/// Inclusive rectangle of cells that get recorded; everything outside it is ignored.
+#[derive(Clone, Copy)]
+pub struct Region {
+    min_x: i32,
+    min_y: i32,
+    max_x: i32,
+    max_y: i32,
+}
+
+impl Region {
+    /// Parses `X0,Y0,X1,Y1`, in either corner order.
+    pub fn from_string(region_str: &str) -> Result<Self, String> {
+        let coords: Vec<i32> = region_str
+            .split(',')
+            .map(|part| part.trim().parse::<i32>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| "Invalid region. Expected 'X0,Y0,X1,Y1'.".to_string())?;
+        if coords.len() != 4 {
+            return Err("Invalid region. Expected 'X0,Y0,X1,Y1'.".to_string());
+        }
+        Ok(Self {
+            min_x: coords[0].min(coords[2]),
+            min_y: coords[1].min(coords[3]),
+            max_x: coords[0].max(coords[2]),
+            max_y: coords[1].max(coords[3]),
+        })
+    }
+
+    /// The smallest and largest corner.
+    pub fn corners(&self) -> (Cell, Cell) {
+        (Cell(self.min_x, self.min_y), Cell(self.max_x, self.max_y))
+    }
+
+    fn contains(&self, cell: Cell) -> bool {
+        (self.min_x..=self.max_x).contains(&cell.0) && (self.min_y..=self.max_y).contains(&cell.1)
+    }
+}
+
+/// Streams one `(generation, x, y)` row per live cell per generation into a Parquet file.
+pub struct HistoryRecorder {
+    writer: SerializedFileWriter<File>,
+    region: Option<Region>,
+    generations: Vec<i64>,
+    xs: Vec<i32>,
+    ys: Vec<i32>,
+}
+
+impl HistoryRecorder {
+    pub fn create(path: &str, region: Option<Region>, rules: &str) -> Result<Self, String> {
+        let file = File::create(path).map_err(|err| err.to_string())?;
+        let schema = parse_message_type(SCHEMA).map_err(|err| err.to_string())?;
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_key_value_metadata(Some(vec![KeyValue::new(
+                "rules".to_string(),
+                rules.to_string(),
+            )]))
+            .build();
+        let writer = SerializedFileWriter::new(file, Arc::new(schema), Arc::new(properties))
+            .map_err(|err| err.to_string())?;
+        Ok(Self {
+            writer,
+            region,
+            generations: Vec::new(),
+            xs: Vec::new(),
+            ys: Vec::new(),
+        })
+    }
+
+    pub fn record<'a>(
+        &mut self,
+        generation: usize,
+        cells: impl IntoIterator<Item = &'a Cell>,
+    ) -> Result<(), String> {
+        for &cell in cells {
+            if self.region.is_some_and(|region| !region.contains(cell)) {
+                continue;
+            }
+            self.generations.push(generation as i64);
+            self.xs.push(cell.0);
+            self.ys.push(cell.1);
+        }
+        if self.generations.len() >= ROW_GROUP_ROWS {
+            self.flush().map_err(|err| err.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ParquetError> {
+        if self.generations.is_empty() {
+            return Ok(());
+        }
+        let mut row_group = self.writer.next_row_group()?;
+        let mut index = 0;
+        while let Some(mut column) = row_group.next_column()? {
+            match index {
+                0 => {
+                    column
+                        .typed::<Int64Type>()
+                        .write_batch(&self.generations, None, None)?;
+                }
+                1 => {
+                    column
+                        .typed::<Int32Type>()
+                        .write_batch(&self.xs, None, None)?;
+                }
+                _ => {
+                    column
+                        .typed::<Int32Type>()
+                        .write_batch(&self.ys, None, None)?;
+                }
+            }
+            column.close()?;
+            index += 1;
+        }
+        row_group.close()?;
+        self.generations.clear();
+        self.xs.clear();
+        self.ys.clear();
+        Ok(())
+    }
+
+    /// Writes any buffered rows and the file footer.
+    pub fn finish(mut self) -> Result<(), String> {
+        self.flush().map_err(|err| err.to_string())?;
+        self.writer.close().map_err(|err| err.to_string())?;
+        Ok(())
+    }
+}
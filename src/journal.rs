@@ -0,0 +1,188 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use crate::{bounding_box, state_hash, Cell};
+
+/// How long a notification stays in the on-screen feed.
+const FEED_LIFETIME: Duration = Duration::from_secs(6);
+const FEED_LENGTH: usize = 5;
+/// Number of past states remembered when looking for a cycle.
+const CYCLE_WINDOW: usize = 256;
+/// A record is only announced once it beats the last announced one by this factor,
+/// so steadily growing patterns don't produce a line every generation.
+const RECORD_STEP: f64 = 1.1;
+
+/// Watches a run for notable milestones and logs them to a file and an in-app feed.
+pub struct Journal {
+    file: Option<File>,
+    feed: VecDeque<(Instant, String)>,
+    max_population: usize,
+    announced_population: usize,
+    max_area: u64,
+    announced_area: u64,
+    recent_states: VecDeque<u64>,
+    seen_states: HashMap<u64, usize>,
+    /// Period of the cycle the pattern settled into (1 for a still life)
+    period: Option<usize>,
+    /// Whether `period` was found since `take_detection` last looked
+    detection_pending: bool,
+    extinct: bool,
+    /// Whether the pattern died out since `take_extinction` last looked
+    extinction_pending: bool,
+}
+
+impl Journal {
+    pub fn new() -> Self {
+        Self {
+            file: None,
+            feed: VecDeque::new(),
+            max_population: 0,
+            announced_population: 0,
+            max_area: 0,
+            announced_area: 0,
+            recent_states: VecDeque::new(),
+            seen_states: HashMap::new(),
+            period: None,
+            detection_pending: false,
+            extinct: false,
+            extinction_pending: false,
+        }
+    }
+
+    /// Appends journal lines to `path` in addition to the on-screen feed.
+    pub fn open_file(&mut self, path: &str) -> Result<(), String> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|err| err.to_string())?;
+        self.file = Some(file);
+        Ok(())
+    }
+
+    /// Forgets everything learned about the run, e.g. after loading a different pattern.
+    pub fn reset(&mut self) {
+        let file = self.file.take();
+        *self = Self::new();
+        self.file = file;
+    }
+
+    /// Forgets past states so a manual edit isn't mistaken for the pattern cycling.
+    pub fn forget_states(&mut self) {
+        self.recent_states.clear();
+        self.seen_states.clear();
+        self.period = None;
+        self.detection_pending = false;
+        self.extinction_pending = false;
+    }
+
+    pub fn period(&self) -> Option<usize> {
+        self.period
+    }
+
+    /// The period, the first time it's asked for after being detected.
+    pub fn take_detection(&mut self) -> Option<usize> {
+        if !self.detection_pending {
+            return None;
+        }
+        self.detection_pending = false;
+        self.period
+    }
+
+    /// Whether the pattern died out, the first time it's asked after that happened.
+    pub fn take_extinction(&mut self) -> bool {
+        std::mem::take(&mut self.extinction_pending)
+    }
+
+    pub fn observe(&mut self, generation: usize, cells: &HashSet<Cell>) {
+        let population = cells.len();
+        if population == 0 {
+            if !self.extinct {
+                self.extinct = true;
+                self.extinction_pending = true;
+                self.log(generation, "Extinction".to_string());
+            }
+            return;
+        }
+        self.extinct = false;
+
+        if population > self.max_population {
+            self.max_population = population;
+            if population as f64 >= self.announced_population as f64 * RECORD_STEP {
+                self.announced_population = population;
+                self.log(generation, format!("New max population: {}", population));
+            }
+        }
+
+        if let Some((min, max)) = bounding_box(cells) {
+            let (width, height) = ((max.0 - min.0 + 1) as u64, (max.1 - min.1 + 1) as u64);
+            let area = width * height;
+            if area > self.max_area {
+                self.max_area = area;
+                if area as f64 >= self.announced_area as f64 * RECORD_STEP {
+                    self.announced_area = area;
+                    self.log(
+                        generation,
+                        format!("New bounding box record: {}x{}", width, height),
+                    );
+                }
+            }
+        }
+
+        let hash = state_hash(cells);
+        if let Some(&previous) = self.seen_states.get(&hash) {
+            if self.period.is_none() {
+                let period = generation - previous;
+                self.period = Some(period);
+                self.detection_pending = true;
+                let message = if period == 1 {
+                    "Stabilized into a still life".to_string()
+                } else {
+                    format!("Period {} detected", period)
+                };
+                self.log(generation, message);
+            }
+        }
+        self.seen_states.insert(hash, generation);
+        self.recent_states.push_back(hash);
+        if self.recent_states.len() > CYCLE_WINDOW {
+            if let Some(old) = self.recent_states.pop_front() {
+                if self
+                    .seen_states
+                    .get(&old)
+                    .is_some_and(|&g| g + CYCLE_WINDOW <= generation)
+                {
+                    self.seen_states.remove(&old);
+                }
+            }
+        }
+    }
+
+    fn log(&mut self, generation: usize, message: String) {
+        let line = format!("Generation {}: {}", generation, message);
+        if let Some(file) = &mut self.file {
+            if let Err(err) = writeln!(file, "{}", line) {
+                eprintln!("Failed to write journal, closing it: {}", err);
+                self.file = None;
+            }
+        }
+        self.feed.push_back((Instant::now(), line));
+        if self.feed.len() > FEED_LENGTH {
+            self.feed.pop_front();
+        }
+    }
+
+    /// Notifications recent enough to still be shown, oldest first.
+    pub fn feed(&mut self) -> Vec<&str> {
+        while self
+            .feed
+            .front()
+            .is_some_and(|(at, _)| at.elapsed() > FEED_LIFETIME)
+        {
+            self.feed.pop_front();
+        }
+        self.feed.iter().map(|(_, line)| line.as_str()).collect()
+    }
+}
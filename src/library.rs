@@ -0,0 +1,49 @@
+use crate::{paste::Paste, patterns};
+
+pub const GLIDER: &str = "\
+.O
+..O
+OOO";
+
+pub const LWSS: &str = "\
+.O..O
+O....
+O...O
+OOOO.";
+
+pub const GOSPER_GLIDER_GUN: &str = "\
+........................O...........
+......................O.O...........
+............OO......OO............OO
+...........O...O....OO............OO
+OO........O.....O...OO..............
+OO........O...O.OO....O.O...........
+..........O.....O.......O...........
+...........O...O....................
+............OO......................";
+
+pub const R_PENTOMINO: &str = "\
+.OO
+OO.
+.O.";
+
+pub const ACORN: &str = "\
+.O.....
+...O...
+OO..OOO";
+
+/// Classic Life patterns, stamped with the number keys in this order.
+pub const STAMPS: &[(&str, &str)] = &[
+    ("Glider", GLIDER),
+    ("Lightweight spaceship", LWSS),
+    ("Gosper glider gun", GOSPER_GLIDER_GUN),
+    ("R-pentomino", R_PENTOMINO),
+    ("Acorn", ACORN),
+];
+
+/// The `index`th stamp, ready to hold on the cursor.
+pub fn stamp(index: usize) -> Option<(&'static str, Paste)> {
+    let (name, cells) = STAMPS.get(index)?;
+    let pattern = patterns::parse_cells(cells).ok()?;
+    Some((name, Paste::new(pattern.cells)))
+}
@@ -1,17 +1,129 @@
-use clap::Parser;
+mod activity;
+mod autosave;
+mod brush;
+mod camera;
+mod catalog;
+mod clipboard;
+mod demo;
+mod elementary;
+mod event_loop;
+mod fetch;
+mod gallery;
+mod gpu;
+mod graph;
+mod history;
+mod journal;
+mod library;
+mod memory;
+mod paste;
+mod patterns;
+mod profiler;
+mod raster;
+mod recording;
+mod remote;
+mod replay;
+mod ruletable;
+mod search;
+mod shapes;
+mod trails;
+mod turmite;
+mod undo;
+mod y4m;
+
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
 use ggez::{
-    event::{self, EventHandler},
-    graphics::{self, Canvas, Color, DrawMode, DrawParam, Mesh, Text},
-    input::keyboard::{KeyCode, KeyInput},
+    event::EventHandler,
+    graphics::{self, Canvas, Color, DrawMode, DrawParam, Image, ImageFormat, Mesh, Sampler, Text},
+    input::keyboard::{KeyCode, KeyInput, KeyMods},
     input::mouse::MouseButton,
     Context, ContextBuilder, GameResult,
 };
 
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
 use std::collections::{HashSet, HashMap};
 use std::fs;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use celleste_core::{
+    bitboard, bounding_box, census,
+    grid::StepPhase,
+    simulation::{self, CellImage, Input, Simulation},
+    state_hash, topology, Cell, Grid, Neighborhood, Rules,
+};
+
+use activity::ActivityMap;
+use autosave::Autosave;
+use brush::Brush;
+use camera::Camera;
+use catalog::Catalog;
+use clipboard::SystemClipboard;
+use demo::Demo;
+use elementary::Elementary;
+use event_loop::WindowEvents;
+use gallery::GalleryOptions;
+use gpu::GpuStepper;
+use graph::PopulationGraph;
+use history::{HistoryRecorder, Region};
+use journal::Journal;
+use paste::Paste;
+use profiler::{Phase, Profiler};
+use raster::Frame;
+use recording::{GifOptions, GifRecorder, VideoRecorder};
+use remote::RemoteServer;
+use replay::{Event, Playback, SessionRecorder, Snapshot};
+use ruletable::RuleTable;
+use search::SearchOptions;
+use shapes::Tool;
+use topology::Torus;
+use trails::Trails;
+use turmite::Turmites;
+use undo::{Edit, UndoStack};
+use y4m::Y4mWriter;
+
+const STREAM_FPS: u32 = 30;
+const DEFAULT_SPEED: f64 = 60.0;
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 100_000.0;
+/// Longest a single frame may spend catching up on simulation time before the backlog is dropped.
+const MAX_CATCH_UP: Duration = Duration::from_millis(50);
+/// Time each frame spends stepping while jumping to a generation, leaving the rest for drawing.
+const FAST_FORWARD_BUDGET: Duration = Duration::from_millis(30);
+const DEFAULT_SOUP_DENSITY: f64 = 0.3;
+/// Pixels per cell at startup, unless `--cell-size` says otherwise
+const DEFAULT_CELL_SIZE: f32 = 10.0;
+/// Pixels the view moves per arrow key press.
+const PAN_STEP: f32 = 40.0;
+/// Fraction of the window the F hotkey makes the pattern fill.
+const FIT_FILL: f32 = 0.9;
+/// Size of the population graph overlay, in pixels.
+const GRAPH_WIDTH: f32 = 300.0;
+const GRAPH_HEIGHT: f32 = 100.0;
+/// Largest turbo setting, in generations per frame.
+const MAX_GENS_PER_FRAME: usize = 65_536;
+/// Live cells past which running pauses, unless `--max-population` says otherwise
+const DEFAULT_MAX_POPULATION: usize = 5_000_000;
+/// Longest clipboard text a paste will try to read as a pattern.
+const MAX_PASTE_BYTES: usize = 16 * 1024 * 1024;
+/// Most generations the GPU runs between two copies back to the CPU.
+const MAX_GPU_BATCH: usize = 4096;
+/// Largest area a random fill will cover, so a zoomed-out view can't freeze the program.
+const MAX_SOUP_AREA: i64 = 4_000_000;
+/// Widest or tallest stretch of a simulation drawn at once; views zoomed out further show
+/// only the part around the middle of the simulation.
+const MAX_SIMULATION_SPAN: i32 = 2048;
+/// Most turmites `--agents` may start.
+const MAX_AGENTS: u64 = 1000;
+/// Largest GIF size relative to the window.
+const MAX_GIF_SCALE: f32 = 4.0;
+const DEFAULT_VIDEO_FPS: u32 = 30;
+/// Dim green of cells that have been alive, as in Golly's LifeHistory
+const ENVELOPE_COLOR: Color = Color::new(0.1, 0.3, 0.15, 1.0);
 
 #[derive(Parser)]
 #[command(
@@ -19,12 +131,97 @@ use std::fs;
     version,
     about = "Celleste - A 2D cellular automaton",
     long_about = "Celleste - A 2D cellular automaton\n\n\
-The rules can be customized using B<number>/S<number> notation. Default is Conway's Game of Life (B3/S23).\n\n\
+The rules can be customized using B<number>/S<number> notation. Default is Conway's Game of Life (B3/S23).\n\
+Named presets such as highlife or daynight work too; --list-rules prints them all.\n\
+Generations rules with decaying cells are written B<number>/S<number>/C<states>, e.g. Brian's Brain (B2/S/C3).\n\
+A trailing V or H switches to the von Neumann or hexagonal neighborhood, e.g. B2/S13V (or pass -N).\n\
+Larger neighborhoods use Larger-than-Life notation, e.g. Bosco's rule (R5,C0,M1,S34..58,B34..45,NM).\n\
+--model ant --spec RL runs Langton's ant (or any L/R/N/U turn string) instead of the rules, and\n\
+--model turmite --spec '{{{1,2,0},{0,8,0}}}' a general turmite table.\n\
+--model elementary --rule 110 runs a 1D elementary automaton, one row per generation scrolling down.\n\
+--model wireworld runs WireWorld on a --size 160x120 grid: painting lays wire, Shift+Right Click\n\
+sends an electron down it. --list-models prints every simulation --model can run.\n\
+--model smoothlife runs Rafler's continuous SmoothLife; Shift+Right Click drops a blob, and --param\n\
+sets its radii and intervals, e.g. --param inner=4 --param outer=12 --param birth1=0.278.\n\
+--model lenia runs Lenia from an Orbium (--param creature=none starts empty); Shift+Right Click\n\
+drops another one.\n\
+--model sandpile topples cells of four grains; right-clicks add grains and --param drop=1 drops one\n\
+on the middle every step to grow the sandpile fractal.\n\
+--model sir spreads an epidemic between neighbors (--param infection=0.2 --param recovery=0.1),\n\
+charting the susceptible, infected and recovered; painting infects, Shift+Right Click immunizes.\n\
+--model wator runs Wa-Tor's fish and sharks, charting both (--param fish_breed=3 --param shark_breed=10\n\
+--param shark_starve=3); painting releases fish, Shift+Right Click a shark.\n\
+--model sand is a falling-sand toy: painting places sand, water, walls or fire (T picks which).\n\
+--model margolus runs reversible block rules (--param rule=critters, tron or bbm); U runs time\n\
+backwards, undoing generations back to the first.\n\
+--model ising runs the Ising model's spins (--param dynamics=metropolis or glauber), showing the\n\
+magnetization and energy; T / Shift+T warm and cool it from --param temperature=2.27.\n\
+--model battle is a two-player Life battle: red paints the left half, blue the right, and after\n\
+--param generations=500 the color with the most surviving cells wins.\n\
+--model layers stacks two Life-like layers (--param rule_a, rule_b) where layer A decides where B's\n\
+cells are born (--param coupling=none, require, inhibit or count); Q / Shift+Q hide either one.\n\
+--rule-table <file.rule> runs a Golly @TABLE rule, with its @COLORS, instead of the B/S rules.\n\
+--torus WIDTHxHEIGHT wraps the grid around its edges; add --gpu to step it with a compute shader.\n\
+--pause-on-period pauses once the pattern stabilizes or cycles; the period shows in the HUD.\n\
+--auto-pause pauses, printing the generation, once every cell has died or nothing changes.\n\
+--trails 8 draws cells that died in the last 8 generations fading out, so spaceships leave trails.\n\
+--max-population 1000000 pauses with a warning, showing memory use, once more cells are alive (0 for no cap).\n\
+--autosave 500 (generations) or --autosave 60s keeps rotating celleste_autosave_<k>.json backups.\n\
+--record-video out.mp4 pipes every generation to ffmpeg, which must be installed, to make a video.\n\
+--listen 127.0.0.1:7878 takes newline-delimited JSON-RPC requests (pause, resume, step, set-rule,\n\
+load-pattern, get-state) from scripts and other programs.\n\
+--record-session session.jsonl records every edit and pause with its generation, and\n\
+--replay session.jsonl plays it back exactly (Escape stops the replay).\n\
+--pattern gosperglidergun loads a LifeWiki pattern (or a pattern URL) from a local cache; built\n\
+with `--features fetch`, missing patterns are downloaded into it.\n\
+--pattern glider --at 0,0 --pattern glider.rle --at 20,0 stamps several patterns (or pattern files).\n\
+--window 1280x720 sets the window size (default 1600x1200), and --fullscreen fills the screen.\n\
+--cell-size 4 --center 100,50 --paused starts zoomed, centered and stationary, e.g. for screenshots.\n\
+--compare B36/S23 runs the pattern under those rules too, side by side with --rules; both halves\n\
+step, pan, zoom and take edits together.\n\n\
 Controls:\n\
 - Space: Pause/Resume simulation\n\
-- Right Click: Add a cell\n\
+- Right Click: Toggle a cell; drag to paint (or erase, starting on a live cell)\n\
+- [ / ]: Shrink/grow the brush painting and erasing use, B: Switch it between square and circle\n\
+- D: Cycle the right-drag tool between the brush, line, rectangle, filled rectangle and ellipse\n\
 - S: Save the current state\n\
-- L: Load a state from the specified file"
+- Closing the window or a tab with unsaved edits asks first: S saves and closes, D discards, Escape cancels\n\
+- L: Load a state (or an .rle/.cells/.mc pattern) from the specified file\n\
+- Drop a save or pattern file on the window to load it, centered in the view\n\
+- N: Advance a single generation while paused\n\
+- R: Fill the view (or the selection) with a random soup\n\
+- C: Clear all cells, I: Reset to the initially loaded pattern\n\
+- Arrow keys: Pan the view, F: Fit the whole pattern in the window\n\
+- Left Drag: Pan the view, Mouse Wheel: Zoom in/out around the pointer\n\
+- Ctrl+Z / Ctrl+Y: Undo/redo cell edits\n\
+- +/-: Double/halve the simulation speed\n\
+- PageUp/PageDown: Double/halve the turbo generations per frame (see --gens-per-frame);\n\
+  halving from 1 turns turbo off\n\
+- P: Toggle the per-phase timing overlay\n\
+- H: Toggle neighbor-count heat coloring\n\
+- Shift+H: Toggle the history envelope of every cell that has been alive, drawn dimly\n\
+- V: Toggle the activity heat overlay of recent births and deaths\n\
+- O: Toggle the population graph\n\
+- K: Count the objects the pattern is made of (also done when it stabilizes)\n\
+- T / Shift+T: Turn the --model simulation's setting up/down, e.g. the element sand paints\n\
+- U: Run a reversible --model simulation backwards (or forwards again)\n\
+- Q / Shift+Q: Show/hide the first/second layer of --model layers\n\
+- J: Go to a generation: type its number and press Enter to run there as fast as possible\n\
+- X: Export the current pattern as RLE\n\
+- G: Start/stop recording an animated GIF (see --gif-path, --gif-every, --gif-scale,\n\
+  --gif-delay and --gif-max-frames)\n\
+- E: Toggle the keyboard editing cursor (arrows move, Enter toggles a cell,\n\
+  Shift+arrows select, Shift+Enter fills and Delete clears the selection)\n\
+- Ctrl+C / Ctrl+V: Copy the selection (or the whole pattern) to the system clipboard as RLE /\n\
+  hold the clipboard's RLE or plaintext pattern on the cursor to paste\n\
+- 1-5: Hold a classic pattern on the cursor (glider, LWSS, Gosper gun,\n\
+  R-pentomino, acorn)\n\
+- A: Browse thumbnails of the patterns in --patterns-dir (default ./patterns); arrows choose,\n\
+  Enter or a click holds one on the cursor to stamp, Escape closes\n\
+- While pasting: Right Click or Enter places it, , and . rotate it,\n\
+  M / Shift+M mirror it, Escape cancels\n\
+- Ctrl+T: Open a new tab, Ctrl+W: Close the current tab\n\
+- Ctrl+Tab / Ctrl+Shift+Tab: Switch to the next/previous tab"
 )]
 struct Cli {
     /// Path to the save file (default: ./celleste_save.json)
@@ -41,18 +238,86 @@ struct Cli {
         short,
         long,
         default_value = "B3/S23",
-        help = "Rules for the automaton in B<number>/S<number> format."
+        help = "Rules for the automaton: a preset name (see --list-rules) or B<number>/S<number> (or B<number>/S<number>/C<states>, or Larger-than-Life R<range>,C<states>,M<0|1>,S<min>..<max>,B<min>..<max>,N<M|N|H>) format, with an optional V or H suffix for the von Neumann or hexagonal neighborhood."
     )]
     rules: String,
 
+    /// Golly rule table to run instead of the B/S rules
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Run the multi-state transition table (@TABLE and @COLORS sections) in a Golly .rule file"
+    )]
+    rule_table: Option<PathBuf>,
+
+    /// Bounded grid that wraps around its edges
+    #[arg(
+        long,
+        value_name = "WIDTHxHEIGHT",
+        conflicts_with = "rule_table",
+        help = "Run --model life on a torus of this many cells from the origin, wrapping around its edges"
+    )]
+    torus: Option<String>,
+
+    /// Step on the GPU
+    #[arg(
+        long,
+        requires = "torus",
+        help = "Step the torus with a GPU compute shader (two-state B/S rules), copying each frame back"
+    )]
+    gpu: bool,
+
+    /// Print the rule presets and exit
+    #[arg(long, help = "List the named rule presets --rules accepts and exit")]
+    list_rules: bool,
+
+    /// Print the simulations --model runs and exit
+    #[arg(long, help = "List the simulations --model can run and exit")]
+    list_models: bool,
+
+    /// Neighborhood the rules count neighbors in
+    #[arg(
+        short = 'N',
+        long,
+        value_name = "moore|vonneumann|hexagonal",
+        help = "Count neighbors in the Moore (8), von Neumann (4) or hexagonal (6) neighborhood, overriding the rule's own"
+    )]
+    neighborhood: Option<String>,
+
     /// Path to load a saved automaton state
     #[arg(
         short = 'l',
         long,
-        help = "Path to load a previously saved automaton state."
+        help = "Path to load a previously saved automaton state or an .rle/.cells/.mc pattern."
     )]
     load_file: Option<String>,
 
+    /// Patterns to load by name, URL or path
+    #[arg(
+        long,
+        value_name = "NAME|URL|FILE",
+        conflicts_with = "load_file",
+        help = "Load a LifeWiki pattern by name (e.g. gosperglidergun), a pattern file by URL or a local pattern file; names and URLs come from the --pattern-cache directory, and builds with the fetch feature download them there first. Repeat it, each with its own --at, to start from several patterns"
+    )]
+    pattern: Vec<String>,
+
+    /// Where the `--pattern` before it goes
+    #[arg(
+        long,
+        value_name = "X,Y",
+        requires = "pattern",
+        help = "Place the top-left corner of the --pattern just before it at this cell instead of --origin"
+    )]
+    at: Vec<String>,
+
+    /// Where patterns loaded by name are kept
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Directory --pattern looks in and downloads to (default: ~/.cache/celleste/patterns)"
+    )]
+    pattern_cache: Option<PathBuf>,
+
      /// Show generation timer
      #[arg(
         short = 'c',
@@ -60,6 +325,425 @@ struct Cli {
         help = "Don't show generation clock"
     )]
     no_clock: bool,
+
+    /// Print per-phase timings on exit
+    #[arg(
+        long,
+        help = "Print a per-phase timing breakdown (simulation vs rendering) on exit"
+    )]
+    profile: bool,
+
+    /// Frame time budget for adaptive stepping
+    #[arg(
+        short = 'b',
+        long,
+        value_name = "MS",
+        help = "Run as many generations per frame as fit in this many milliseconds (e.g. 12)"
+    )]
+    budget: Option<f32>,
+
+    /// Fixed number of generations per frame
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with = "budget",
+        help = "Run exactly this many generations per rendered frame (turbo); PageUp/PageDown adjust it"
+    )]
+    gens_per_frame: Option<usize>,
+
+    /// Simulation speed in generations per second
+    #[arg(
+        long,
+        value_name = "GENS_PER_SEC",
+        default_value_t = DEFAULT_SPEED,
+        help = "Generations per second, independent of the frame rate"
+    )]
+    speed: f64,
+
+    /// Path to record the spacetime history to
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Record every generation's live cells to a Parquet file (columns: generation, x, y)"
+    )]
+    record_history: Option<String>,
+
+    /// Region of the grid to record
+    #[arg(
+        long,
+        value_name = "X0,Y0,X1,Y1",
+        requires = "record_history",
+        help = "Only record cells inside this rectangle (default: everything)"
+    )]
+    history_region: Option<String>,
+
+    /// Size of the window
+    #[arg(
+        long,
+        value_name = "WIDTHxHEIGHT",
+        default_value = "1600x1200",
+        help = "Size in pixels of the window, which can be resized afterwards (and of streamed or recorded frames)"
+    )]
+    window: String,
+
+    /// Fill the screen instead of opening a window
+    #[arg(long, conflicts_with = "stream_y4m", help = "Start fullscreen")]
+    fullscreen: bool,
+
+    /// Stream frames to stdout instead of opening a window
+    #[arg(
+        long,
+        help = "Run headless and write uncompressed y4m frames to stdout (e.g. `celleste --stream-y4m | mpv -`)"
+    )]
+    stream_y4m: bool,
+
+    /// Number of frames to stream
+    #[arg(
+        long,
+        value_name = "N",
+        requires = "stream_y4m",
+        help = "Stop streaming after this many frames (default: until the reader closes the pipe)"
+    )]
+    frames: Option<usize>,
+
+    /// Path to the milestone journal
+    #[arg(
+        short = 'j',
+        long,
+        value_name = "PATH",
+        help = "Append notable events (records, period detection, extinction) to this file"
+    )]
+    journal: Option<String>,
+
+    /// Pause once the pattern repeats
+    #[arg(
+        long,
+        help = "Pause when the pattern stabilizes or starts cycling (the period is shown either way)"
+    )]
+    pause_on_period: bool,
+
+    /// Pause once nothing more will happen
+    #[arg(
+        long,
+        help = "Pause when every cell has died or the pattern stops changing, printing the generation"
+    )]
+    auto_pause: bool,
+
+    /// Generations dead cells keep fading for
+    #[arg(
+        long,
+        value_name = "GENERATIONS",
+        help = "Draw cells that died in the last GENERATIONS generations (up to 255) fading out, leaving trails behind moving patterns"
+    )]
+    trails: Option<u8>,
+
+    /// Live cells past which running pauses
+    #[arg(
+        long,
+        value_name = "CELLS",
+        default_value_t = DEFAULT_MAX_POPULATION,
+        help = "Pause with a warning, showing memory use, once more than CELLS cells are alive or decaying, before a population explosion exhausts memory (0 for no cap)"
+    )]
+    max_population: usize,
+
+    /// Address to take remote commands on
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        conflicts_with = "stream_y4m",
+        help = "Accept JSON-RPC commands (pause, resume, step, set-rule, load-pattern, get-state), one per line, over TCP on this address (e.g. 127.0.0.1:7878)"
+    )]
+    listen: Option<String>,
+
+    /// Rules to compare against side by side
+    #[arg(
+        long,
+        value_name = "RULES",
+        conflicts_with_all = ["stream_y4m", "demo", "replay"],
+        help = "Split the window and run the same pattern under these rules on the right, stepping and moving together with --rules on the left (Life-like rules only, not other --model simulations)"
+    )]
+    compare: Option<String>,
+
+    /// Path to record the session to
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Record every edit, load, rule change and pause/resume with its generation to this file, for --replay"
+    )]
+    record_session: Option<PathBuf>,
+
+    /// Session to play back
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["record_session", "load_file", "pattern", "random", "demo"],
+        help = "Play back a session recorded with --record-session, starting from its recorded state"
+    )]
+    replay: Option<PathBuf>,
+
+    /// Run the pattern tour
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        num_args = 0..=1,
+        default_missing_value = "20",
+        conflicts_with_all = ["model", "rule_table"],
+        help = "Cycle through a tour of famous patterns and rules, advancing every SECONDS (default: 20)"
+    )]
+    demo: Option<u64>,
+
+    /// Path the X hotkey exports RLE to (default: ./celleste_export.rle)
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Path the current pattern is exported to as RLE when X is pressed"
+    )]
+    export_rle: Option<String>,
+
+    /// How often to autosave
+    #[arg(
+        long,
+        value_name = "GENERATIONS|SECONDSs",
+        help = "Autosave every N generations (e.g. 500) or seconds (e.g. 60s) to celleste_autosave_<k>.json"
+    )]
+    autosave: Option<String>,
+
+    /// Number of autosave files to rotate through
+    #[arg(
+        long,
+        value_name = "K",
+        default_value_t = 5,
+        requires = "autosave",
+        help = "Keep this many autosave files, overwriting the oldest"
+    )]
+    autosave_keep: usize,
+
+    /// Video file to record every generation to
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Pipe every generation as a raw RGBA frame to ffmpeg, which encodes this video file (e.g. out.mp4)"
+    )]
+    record_video: Option<PathBuf>,
+
+    /// Frame rate of the recorded video
+    #[arg(
+        long,
+        value_name = "FPS",
+        default_value_t = DEFAULT_VIDEO_FPS,
+        requires = "record_video",
+        help = "Frames (generations) per second of the video --record-video writes"
+    )]
+    video_fps: u32,
+
+    /// Path the G hotkey records a GIF to
+    #[arg(
+        long,
+        value_name = "PATH",
+        default_value = "./celleste_recording.gif",
+        help = "Path the animated GIF is written to when G stops a recording"
+    )]
+    gif_path: PathBuf,
+
+    /// Generations per recorded GIF frame
+    #[arg(
+        long,
+        value_name = "K",
+        default_value_t = 1,
+        help = "Capture every Kth generation while recording a GIF"
+    )]
+    gif_every: usize,
+
+    /// Size of recorded GIFs
+    #[arg(
+        long,
+        value_name = "SCALE",
+        default_value_t = 0.5,
+        help = "Size of recorded GIFs relative to the window (e.g. 0.5 for half size)"
+    )]
+    gif_scale: f32,
+
+    /// Delay between GIF frames
+    #[arg(
+        long,
+        value_name = "MS",
+        default_value_t = 50,
+        help = "Milliseconds each frame of a recorded GIF is shown for"
+    )]
+    gif_delay: u32,
+
+    /// Frames after which a GIF recording stops
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 300,
+        help = "Stop and save a GIF recording once it has this many frames"
+    )]
+    gif_max_frames: usize,
+
+    /// Directory the pattern catalog lists
+    #[arg(
+        long,
+        value_name = "DIR",
+        default_value = "./patterns",
+        help = "Directory of .rle/.cells/.mc patterns the A hotkey browses and stamps from"
+    )]
+    patterns_dir: PathBuf,
+
+    /// Where loaded patterns are placed
+    #[arg(
+        long,
+        value_name = "X,Y",
+        help = "Grid position of the top-left corner of loaded .rle/.cells/.mc patterns (default: 0,0)"
+    )]
+    origin: Option<String>,
+
+    /// Zoom to start at
+    #[arg(
+        long,
+        value_name = "PIXELS",
+        help = "Start with cells this many pixels across (default: 10), even after loading a save with its own view"
+    )]
+    cell_size: Option<f32>,
+
+    /// Cell to start centered on
+    #[arg(
+        long,
+        value_name = "X,Y",
+        help = "Start with this cell in the middle of the window, even after loading a save with its own view"
+    )]
+    center: Option<String>,
+
+    /// Don't start running
+    #[arg(
+        long,
+        conflicts_with = "demo",
+        help = "Start paused, even when the loaded save was running"
+    )]
+    paused: bool,
+
+    /// Density of random soups
+    #[arg(
+        long,
+        value_name = "DENSITY",
+        help = "Start from a random soup of this density (0-1) filling the window; also used by R"
+    )]
+    random: Option<f64>,
+
+    /// Rectangle for the starting soup
+    #[arg(
+        long,
+        value_name = "X0,Y0,X1,Y1",
+        requires = "random",
+        help = "Fill this rectangle with the starting soup instead of the window"
+    )]
+    random_region: Option<String>,
+
+    /// Seed for random soups
+    #[arg(long, help = "Seed random soups so they can be reproduced")]
+    seed: Option<u64>,
+
+    /// What drives the grid
+    #[arg(
+        long,
+        value_name = "life|elementary|ant|turmite|wireworld|...",
+        default_value = "life",
+        help = "Run the life-like rules, a 1D elementary automaton, Langton's ant, a general turmite, or another simulation from --list-models"
+    )]
+    model: String,
+
+    /// Size of bounded simulations
+    #[arg(
+        long,
+        value_name = "WIDTHxHEIGHT",
+        default_value = "160x120",
+        help = "Size in cells of the grid bounded simulations such as --model wireworld run on"
+    )]
+    size: String,
+
+    /// Simulation parameters
+    #[arg(
+        long,
+        value_name = "NAME=VALUE",
+        help = "Set a parameter of the --model simulation, e.g. --param outer=16 for smoothlife; --list-models shows them (repeatable)"
+    )]
+    param: Vec<String>,
+
+    /// Wolfram rule number for --model elementary
+    #[arg(
+        long,
+        value_name = "0-255",
+        default_value_t = 110,
+        help = "Wolfram code of the elementary automaton run by --model elementary"
+    )]
+    rule: u8,
+
+    /// Turmite specification
+    #[arg(
+        long,
+        help = "Turn per color for --model ant (default: RL), or a Golly-style transition table such as {{{1,2,0},{0,8,0}}} for --model turmite"
+    )]
+    spec: Option<String>,
+
+    /// Number of turmites
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..=MAX_AGENTS),
+        help = "Number of ants/turmites (1-1000), started side by side in the middle of the window"
+    )]
+    agents: usize,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Render every pattern file in a directory to a fitted PNG thumbnail
+    Render {
+        /// Directory of pattern files
+        dir: PathBuf,
+
+        /// Directory to write the thumbnails to
+        #[arg(short, long, default_value = "gallery")]
+        out: PathBuf,
+
+        /// Thumbnail width and height in pixels
+        #[arg(long, default_value_t = 256)]
+        size: u32,
+
+        /// Also write an animated GIF of this many generations for each pattern
+        #[arg(long, value_name = "GENERATIONS")]
+        gif: Option<usize>,
+    },
+    /// Run many random soups headlessly and census the objects they settle into
+    Search {
+        /// Number of soups to run
+        #[arg(long, default_value_t = 1000)]
+        soups: usize,
+
+        /// Width and height of each soup
+        #[arg(long, value_name = "WIDTHxHEIGHT", default_value = "16x16")]
+        size: String,
+
+        /// Rules to run the soups in; the census only knows Life's objects, so only B3/S23
+        #[arg(long, default_value = "B3/S23")]
+        rule: String,
+
+        /// Directory to write the summary and uncommon objects to
+        #[arg(short, long, default_value = "search")]
+        out: PathBuf,
+
+        /// Generations after which a soup that hasn't settled is censused anyway
+        #[arg(long, default_value_t = 20000)]
+        max_generations: usize,
+
+        /// Seed of the first soup; each following soup uses the next number
+        #[arg(long)]
+        seed: Option<u64>,
+    },
 }
 
 fn get_default_save_file() -> String {
@@ -71,71 +755,302 @@ fn get_default_save_file() -> String {
         .to_string()
 }
 
-#[derive(Eq, PartialEq, Hash, Clone, Copy, Serialize, Deserialize)]
-struct Cell(i32, i32);
-
 #[derive(Serialize, Deserialize)]
 struct SaveState {
     alive_cells: HashSet<Cell>,
     rules: String,
+    /// Decaying cells of Generations rules and their state
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    dying_cells: Vec<(Cell, u8)>,
+    /// Missing from saves written before the view was recorded
+    #[serde(flatten)]
+    view: Option<SavedView>,
+    /// State of the simulation running in place of the rules, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    simulation: Option<SavedSimulation>,
 }
 
-struct Rules {
-    birth: Vec<usize>,
-    survival: Vec<usize>,
+/// A simulation's own state, tagged with its name so it's only restored into the same kind.
+#[derive(Serialize, Deserialize)]
+struct SavedSimulation {
+    model: String,
+    state: Value,
 }
 
-impl Rules {
-    fn from_string(rule_str: &str) -> Result<Self, String> {
-        let parts: Vec<&str> = rule_str.split('/').collect();
-        if parts.len() != 2 || !parts[0].starts_with('B') || !parts[1].starts_with('S') {
-            return Err("Invalid rule format. Expected 'B<number>/S<number>'.".to_string());
-        }
-        let birth = parts[0][1..]
-            .chars()
-            .filter_map(|c| c.to_digit(10))
-            .map(|d| d as usize)
-            .collect();
-
-        let survival = parts[1][1..]
-            .chars()
-            .filter_map(|c| c.to_digit(10))
-            .map(|d| d as usize)
-            .collect();
+/// The view and clock a save restores, so loading picks up exactly where the user left off.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct SavedView {
+    #[serde(flatten)]
+    camera: Camera,
+    running: bool,
+    generation: usize,
+}
 
-        Ok(Self { birth, survival })
-    }
+/// Cells painted by one right-button drag.
+struct Stroke {
+    /// The state painted cells are set to
+    alive: bool,
+    last: Cell,
+    /// Everything painted so far, undone together
+    edit: Edit,
 }
 
 struct Celleste {
-    alive_cells: HashSet<Cell>,
-    cell_size: f32,
-    offset_x: f32,
-    offset_y: f32,
+    grid: Grid,
+    initial_cells: HashSet<Cell>,
+    initial_rules: Rules,
+    camera: Camera,
     dragging: bool,
     drag_start: Option<(f32, f32)>,
     running: bool,
-    rules: Rules,
     save_file: String,
+    export_file: String,
+    paste_origin: Cell,
     clock: bool,
-    generation: usize
+    profiler: Profiler,
+    show_profile: bool,
+    print_profile: bool,
+    frame_budget: Option<Duration>,
+    /// Turbo: a fixed number of generations per frame instead of a speed
+    gens_per_frame: Option<usize>,
+    speed: f64,
+    step_debt: f64,
+    rate_window_start: Instant,
+    rate_window_gens: usize,
+    gens_per_sec: f64,
+    heat_map: bool,
+    live_counts: HashMap<Cell, usize>,
+    /// Every cell alive since the history envelope was shown, drawn dimly behind the live
+    /// cells like Golly's LifeHistory; `None` while it's hidden
+    envelope: Option<HashSet<Cell>>,
+    /// Recent births and deaths per cell, drawn as a heat overlay; `None` while it's hidden
+    activity: Option<ActivityMap>,
+    /// Recently dead cells fading out behind moving patterns, with `--trails`
+    trails: Option<Trails>,
+    /// Live cells past which running pauses, with `--max-population`
+    max_population: Option<usize>,
+    /// Set once running has paused for passing the population cap, until the population
+    /// falls back under it, so resuming doesn't pause again straight away
+    over_population: bool,
+    history: Option<HistoryRecorder>,
+    autosave: Option<Autosave>,
+    gif_options: GifOptions,
+    /// The animated GIF being recorded, if any
+    gif: Option<GifRecorder>,
+    /// ffmpeg encoding every generation into a video, if recording one
+    video: Option<VideoRecorder>,
+    status_to_stderr: bool,
+    journal: Journal,
+    population_graph: PopulationGraph,
+    show_graph: bool,
+    /// Objects counted by the last census and the generation it was taken at
+    census: Option<(usize, Vec<String>)>,
+    pause_on_period: bool,
+    /// Pause on extinction or once the pattern stops changing
+    auto_pause: bool,
+    /// Digits typed so far at the go-to-generation prompt, while it's open
+    goto_prompt: Option<String>,
+    /// Whether the cells or rules were changed by hand since the last save or load
+    unsaved: bool,
+    /// Whether closing the window is waiting on the user to save, discard or cancel
+    quit_prompt: bool,
+    /// Whether that prompt is for closing just this tab rather than the window
+    closing_tab: bool,
+    /// Directory the pattern catalog lists
+    catalog_dir: PathBuf,
+    /// The pattern catalog, while it's open
+    catalog: Option<Catalog>,
+    /// Generation being jumped to, and the one the jump started from
+    fast_forward: Option<(usize, usize)>,
+    /// Where edits and other user events are recorded, with `--record-session`
+    session: Option<SessionRecorder>,
+    /// A recorded session being played back, driving the simulation in place of the clock
+    playback: Option<Playback>,
+    /// Drawn in one half of the window, next to a universe it's compared with
+    split_view: bool,
+    demo: Option<Demo>,
+    cursor: Option<Cell>,
+    selection_anchor: Option<Cell>,
+    undo: UndoStack,
+    /// Right-button drag in progress
+    stroke: Option<Stroke>,
+    brush: Brush,
+    tool: Tool,
+    /// Where the right button went down while drawing a shape
+    shape_start: Option<Cell>,
+    mouse_pos: (f32, f32),
+    clipboard: Option<Paste>,
+    system_clipboard: SystemClipboard,
+    paste: Option<Paste>,
+    rng: StdRng,
+    soup_density: f64,
+    /// Ants or turmites painting the grid in place of the rules
+    turmites: Option<Turmites>,
+    /// A 1D automaton writing one row per generation in place of the rules
+    elementary: Option<Elementary>,
+    /// A Golly transition table in place of the rules; state 1 is kept in `alive_cells` and
+    /// higher states in `dying_cells`
+    rule_table: Option<RuleTable>,
+    /// A simulation from the core registry running, drawing and taking edits in place of the
+    /// grid
+    simulation: Option<Box<dyn Simulation>>,
+    /// What `reset` restores the simulation to
+    initial_simulation: Option<Value>,
+    use_gpu: bool,
+    gpu: Option<GpuStepper>,
+    /// Whether `alive_cells` changed since the GPU last had a copy
+    gpu_stale: bool,
+    /// Generations per GPU batch under a frame budget, adapted to fit it
+    gpu_batch: usize,
 }
 
 impl Celleste {
     fn new(initial_state: Vec<Cell>, cell_size: f32, rules: Rules, clock: bool) -> Self {
-        let alive_cells = initial_state.into_iter().collect();
+        let alive_cells: HashSet<Cell> = initial_state.into_iter().collect();
         Self {
-            alive_cells,
-            cell_size,
-            offset_x: 0.0,
-            offset_y: 0.0,
+            initial_cells: alive_cells.clone(),
+            initial_rules: rules.clone(),
+            grid: Grid::new(alive_cells, rules),
+            camera: Camera::new(cell_size),
             dragging: false,
             drag_start: None,
             running: false,
-            rules,
             save_file: "./celleste_save.json".to_string(),
+            export_file: "./celleste_export.rle".to_string(),
+            paste_origin: Cell(0, 0),
             clock,
-            generation: 1,
+            profiler: Profiler::default(),
+            show_profile: false,
+            print_profile: false,
+            frame_budget: None,
+            gens_per_frame: None,
+            speed: DEFAULT_SPEED,
+            step_debt: 0.0,
+            rate_window_start: Instant::now(),
+            rate_window_gens: 0,
+            gens_per_sec: 0.0,
+            heat_map: false,
+            envelope: None,
+            activity: None,
+            trails: None,
+            max_population: Some(DEFAULT_MAX_POPULATION),
+            over_population: false,
+            live_counts: HashMap::new(),
+            history: None,
+            autosave: None,
+            gif_options: GifOptions {
+                path: PathBuf::from("./celleste_recording.gif"),
+                every: 1,
+                scale: 0.5,
+                delay_ms: 50,
+                max_frames: 300,
+            },
+            gif: None,
+            video: None,
+            status_to_stderr: false,
+            journal: Journal::new(),
+            population_graph: PopulationGraph::default(),
+            show_graph: false,
+            census: None,
+            pause_on_period: false,
+            auto_pause: false,
+            goto_prompt: None,
+            unsaved: false,
+            quit_prompt: false,
+            closing_tab: false,
+            catalog_dir: PathBuf::from("./patterns"),
+            catalog: None,
+            fast_forward: None,
+            session: None,
+            playback: None,
+            split_view: false,
+            demo: None,
+            cursor: None,
+            selection_anchor: None,
+            undo: UndoStack::default(),
+            stroke: None,
+            brush: Brush::default(),
+            tool: Tool::Brush,
+            shape_start: None,
+            mouse_pos: (0.0, 0.0),
+            clipboard: None,
+            system_clipboard: SystemClipboard::default(),
+            paste: None,
+            rng: StdRng::from_entropy(),
+            soup_density: DEFAULT_SOUP_DENSITY,
+            turmites: None,
+            elementary: None,
+            rule_table: None,
+            simulation: None,
+            initial_simulation: None,
+            use_gpu: false,
+            gpu: None,
+            gpu_stale: true,
+            gpu_batch: 1,
+        }
+    }
+
+    /// An empty universe sharing this one's rules and settings, used when opening a new tab.
+    fn blank_like(&self) -> Self {
+        let rules = self.grid.rules.clone();
+        let mut universe = Self::new(Vec::new(), self.camera.cell_size, rules, self.clock);
+        universe.save_file = self.save_file.clone();
+        universe.export_file = self.export_file.clone();
+        universe.paste_origin = self.paste_origin;
+        universe.print_profile = self.print_profile;
+        universe.frame_budget = self.frame_budget;
+        universe.gens_per_frame = self.gens_per_frame;
+        universe.speed = self.speed;
+        universe.soup_density = self.soup_density;
+        universe.pause_on_period = self.pause_on_period;
+        universe.auto_pause = self.auto_pause;
+        universe.max_population = self.max_population;
+        universe.catalog_dir = self.catalog_dir.clone();
+        universe.gif_options = self.gif_options.clone();
+        universe.turmites = self.turmites.as_ref().map(Turmites::blank_like);
+        universe.elementary = self.elementary.map(|mut elementary| {
+            elementary.restart();
+            elementary
+        });
+        universe.rule_table = self.rule_table.clone();
+        universe.simulation = self
+            .simulation
+            .as_ref()
+            .map(|simulation| simulation.blank());
+        universe.mark_initial();
+        universe.grid.torus = self.grid.torus;
+        universe.use_gpu = self.use_gpu;
+        universe
+    }
+
+    /// A copy of this universe's pattern, view and settings running under other rules, to
+    /// compare side by side with it.
+    fn compare_with(&self, rules: Rules) -> Self {
+        let mut universe = self.blank_like();
+        universe.grid.alive_cells = self.grid.alive_cells.clone();
+        universe.grid.dying_cells = self.grid.dying_cells.clone();
+        universe.grid.generation = self.grid.generation;
+        universe.grid.rules = rules;
+        universe.camera.offset_x = self.camera.offset_x;
+        universe.camera.offset_y = self.camera.offset_y;
+        universe.running = self.running;
+        // Random fills have to come out the same on both sides
+        universe.rng = self.rng.clone();
+        universe.heat_map = self.heat_map;
+        universe.envelope = self.envelope.clone();
+        universe.activity = self.activity.clone();
+        universe.trails = self.trails.clone();
+        universe.refresh_live_counts();
+        universe.mark_initial();
+        universe
+    }
+
+    /// Prints an informational message, keeping stdout clean when it carries a data stream.
+    fn status(&self, message: &str) {
+        if self.status_to_stderr {
+            eprintln!("{}", message);
+        } else {
+            println!("{}", message);
         }
     }
 
@@ -143,248 +1058,3194 @@ impl Celleste {
         self.save_file = file_path;
     }
 
-    fn step(&mut self) {
-        // Accumulate counts of live neighbors for every cell
-        let mut neighbor_counts: HashMap<Cell, usize> = HashMap::new();
-        for &cell in &self.alive_cells {
-            // For each neighbor of a live cell, increment its count
-            for neighbor in self.get_neighbors(cell) {
-                *neighbor_counts.entry(neighbor).or_insert(0) += 1;
+    /// Starts recording every generation, beginning with the current one, to a Parquet file.
+    fn start_history(&mut self, file_path: &str, region: Option<Region>) -> Result<(), String> {
+        let recorder = HistoryRecorder::create(file_path, region, &self.grid.rules.as_string())?;
+        self.history = Some(recorder);
+        self.record_history();
+        Ok(())
+    }
+
+    fn record_history(&mut self) {
+        if let Some(history) = &mut self.history {
+            if let Err(err) = history.record(self.grid.generation, &self.grid.alive_cells) {
+                eprintln!("Failed to record history, stopping: {}", err);
+                self.history = None;
             }
         }
+    }
 
-        let mut new_state = HashSet::new();
-        // Evaluate the new state based on neighbor counts
-        for (cell, count) in neighbor_counts {
-             if self.alive_cells.contains(&cell) {
-                 // For live cells, check if they survive
-                 if self.rules.survival.contains(&count) {
-                      new_state.insert(cell);
-                 }
-             } else {
-                 // For dead cells, check if they are born
-                 if self.rules.birth.contains(&count) {
-                      new_state.insert(cell);
-                 }
-             }
+    /// Starts recording the view into an animated GIF, or stops and saves the recording.
+    fn toggle_gif_recording(&mut self, width: f32, height: f32) {
+        match self.gif.take() {
+            Some(recorder) => self.finish_gif(recorder),
+            None => {
+                let options = self.gif_options.clone();
+                let recorder =
+                    GifRecorder::new(options, width as u32, height as u32, self.grid.generation);
+                self.gif = Some(recorder);
+                self.status("Recording GIF, press G to stop");
+                self.capture_gif_frame();
+            }
         }
-
-        self.alive_cells = new_state;
-        self.generation += 1;
     }
 
-    fn get_neighbors(&self, cell: Cell) -> Vec<Cell> {
-        let mut neighbors = Vec::new();
-        for dy in -1..=1 {
-            for dx in -1..=1 {
-                if dx != 0 || dy != 0 {
-                    neighbors.push(Cell(cell.0 + dx, cell.1 + dy));
-                }
+    /// Adds the view to the GIF being recorded when a frame is due, saving it once it's full.
+    fn capture_gif_frame(&mut self) {
+        let (width, height) = match &self.gif {
+            Some(recorder) if recorder.due(self.grid.generation) => recorder.size(),
+            _ => return,
+        };
+        let frame = self.render_frame(width, height);
+        if let Some(recorder) = &mut self.gif {
+            if let Err(err) = recorder.capture(frame, self.grid.generation) {
+                eprintln!("Failed to record GIF frame, stopping: {}", err);
+                self.gif = None;
+            } else if recorder.is_full() {
+                let recorder = self.gif.take().unwrap();
+                self.finish_gif(recorder);
             }
         }
-        neighbors
     }
 
-    fn toggle_cell(&mut self, x: f32, y: f32) {
-        let grid_x = ((x - self.offset_x) / self.cell_size).floor() as i32;
-        let grid_y = ((y - self.offset_y) / self.cell_size).floor() as i32;
-        let cell = Cell(grid_x, grid_y);
-        if self.alive_cells.contains(&cell) {
-            self.alive_cells.remove(&cell);
-        } else {
-            self.alive_cells.insert(cell);
-        }
+    /// Starts piping the view, from this generation on, to ffmpeg.
+    fn start_video(
+        &mut self,
+        path: &Path,
+        width: u32,
+        height: u32,
+        fps: u32,
+    ) -> Result<(), String> {
+        self.video = Some(VideoRecorder::start(path, width, height, fps)?);
+        self.write_video_frame();
+        Ok(())
     }
 
-    fn save_to_file(&self, file_path: &str) {
-        let save_state = SaveState {
-            alive_cells: self.alive_cells.clone(),
-            rules: format!("B{}/S{}", 
-                self.rules.birth.iter().map(|b| b.to_string()).collect::<String>(),
-                self.rules.survival.iter().map(|s| s.to_string()).collect::<String>()
-            ),
+    fn write_video_frame(&mut self) {
+        let (width, height) = match &self.video {
+            Some(video) => video.size(),
+            None => return,
         };
-        match serde_json::to_string(&save_state) {
-            Ok(json) => {
-                if let Err(err) = fs::write(file_path, json) {
-                    eprintln!("Failed to save game state: {}", err);
-                } else {
-                    println!("Game state saved to {}", file_path);
+        let frame = self.render_frame(width, height);
+        if let Some(video) = &mut self.video {
+            if let Err(err) = video.write_frame(&frame) {
+                eprintln!("Failed to write video frame, stopping: {}", err);
+                if let Some(video) = self.video.take() {
+                    self.finish_video(video);
                 }
             }
-            Err(err) => eprintln!("Failed to serialize game state: {}", err),
         }
     }
 
-    fn load_from_file(&mut self, file_path: &str) {
-        match fs::read_to_string(file_path) {
-            Ok(json) => match serde_json::from_str::<SaveState>(&json) {
-                Ok(save_state) => {
-                    self.alive_cells = save_state.alive_cells;
-                    match Rules::from_string(&save_state.rules) {
-                        Ok(rules) => self.rules = rules,
-                        Err(err) => eprintln!("Failed to parse rules from save state: {}", err),
-                    }
-                    println!("Game state and rules loaded from {}", file_path);
-                }
-                Err(err) => eprintln!("Failed to deserialize game state: {}", err),
+    /// Writes out whatever is still being recorded, so history files get their footer and
+    /// GIFs and videos are closed properly.
+    fn finish_recordings(&mut self) {
+        if let Some(history) = self.history.take() {
+            match history.finish() {
+                Ok(()) => self.status("History recording finished"),
+                Err(err) => eprintln!("Failed to finish history recording: {}", err),
+            }
+        }
+        if let Some(recorder) = self.gif.take() {
+            self.finish_gif(recorder);
+        }
+        if let Some(video) = self.video.take() {
+            self.finish_video(video);
+        }
+    }
+
+    fn finish_video(&self, video: VideoRecorder) {
+        match video.finish() {
+            Ok(frames) => self.status(&format!("Wrote {} frames to the video", frames)),
+            Err(err) => eprintln!("Failed to finish video: {}", err),
+        }
+    }
+
+    fn finish_gif(&self, recorder: GifRecorder) {
+        let frames = recorder.frame_count();
+        match recorder.finish() {
+            Ok(path) => self.status(&format!(
+                "Saved {} GIF frames to {}",
+                frames,
+                path.display()
+            )),
+            Err(err) => eprintln!("Failed to save GIF: {}", err),
+        }
+    }
+
+    /// Starts the pattern tour, showing its first entry straight away.
+    fn start_demo(&mut self, interval: Duration, width: f32, height: f32) {
+        self.demo = Some(Demo::new(interval));
+        self.show_demo_entry(width, height);
+    }
+
+    /// Moves the tour on to its next entry once the current one has had its time.
+    fn tick_demo(&mut self, width: f32, height: f32) {
+        if let Some(demo) = &mut self.demo {
+            if demo.due() {
+                demo.advance();
+                self.show_demo_entry(width, height);
+            }
+        }
+    }
+
+    fn show_demo_entry(&mut self, width: f32, height: f32) {
+        let Some(demo) = &self.demo else {
+            return;
+        };
+        let entry = demo.current();
+        let seed = demo.seed();
+        self.simulation = entry.simulation().and_then(|name| {
+            let options = simulation::Options {
+                width: demo::SIMULATION_SIZE.0,
+                height: demo::SIMULATION_SIZE.1,
+                rules: self.grid.rules.clone(),
+                torus: None,
+                params: HashMap::new(),
+                seed,
+            };
+            simulation::create(name, &options)
+                .map_err(|err| eprintln!("Failed to start demo simulation: {}", err))
+                .ok()
+        });
+        if self.simulation.is_none() {
+            match Rules::from_string(entry.rules) {
+                Ok(rules) => self.grid.rules = rules,
+                Err(err) => eprintln!("Failed to parse demo rules: {}", err),
+            }
+        }
+        self.grid.alive_cells = entry.cells(seed).into_iter().collect();
+        self.grid.dying_cells.clear();
+        self.grid.generation = self
+            .simulation
+            .as_ref()
+            .map_or(1, |simulation| simulation.generation());
+        self.fast_forward = None;
+        self.wrap_into_torus();
+        self.refresh_live_counts();
+        self.restart_overlays();
+        self.journal.reset();
+        self.population_graph.clear();
+        self.census = None;
+        self.undo.clear();
+        self.mark_initial();
+        self.log_event(|game| Event::Load(game.snapshot()));
+        if self.simulation.is_some() {
+            self.fit_to_pattern(width, height, FIT_FILL);
+        } else {
+            // Leave room around the pattern for whatever it grows into
+            self.fit_to_pattern(width, height, 0.4);
+        }
+        self.running = true;
+    }
+
+    /// Zooms and centers the camera so the pattern's bounding box fills `fill` of the view.
+    fn fit_to_pattern(&mut self, width: f32, height: f32, fill: f32) {
+        let bounds = match &self.simulation {
+            Some(simulation) => simulation.bounds(),
+            None => bounding_box(&self.grid.alive_cells),
+        };
+        let Some((min, max)) = bounds else {
+            return;
+        };
+        self.camera.fit(min, max, width, height, fill);
+    }
+
+    fn set_frame_budget(&mut self, budget_ms: f32) {
+        self.frame_budget = Some(Duration::from_secs_f32(budget_ms.max(0.0) / 1000.0));
+    }
+
+    fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+    }
+
+    /// Doubles (or, with `faster` false, halves) the turbo setting; halving from one
+    /// generation per frame turns turbo off again.
+    fn adjust_turbo(&mut self, faster: bool) {
+        self.gens_per_frame = match (self.gens_per_frame, faster) {
+            (None, true) => Some(2),
+            (None, false) | (Some(1), false) => None,
+            (Some(n), true) => Some((n * 2).min(MAX_GENS_PER_FRAME)),
+            (Some(n), false) => Some(n / 2),
+        };
+        match self.gens_per_frame {
+            Some(n) => self.status(&format!("Turbo: {} generations per frame", n)),
+            None => self.status(&format!("Turbo off: {} generations per second", self.speed)),
+        }
+    }
+
+    /// Cells the grid holds, live or decaying; both take up memory, so both count towards
+    /// `--max-population`.
+    fn population(&self) -> usize {
+        self.grid.alive_cells.len() + self.grid.dying_cells.len()
+    }
+
+    /// Whether the population has just grown past `--max-population`, and running should
+    /// stop before it eats all the memory.
+    fn population_exploded(&self) -> bool {
+        !self.over_population
+            && self
+                .max_population
+                .is_some_and(|cap| self.population() > cap)
+    }
+
+    /// Stops running once the population has exploded, saying how big it got.
+    fn pause_for_population(&mut self) {
+        self.over_population = true;
+        self.running = false;
+        self.status(&format!(
+            "Paused at generation {}: {} cells are alive or decaying, over the cap of {} \
+             ({} in use); press Space to keep going anyway",
+            self.grid.generation,
+            self.population(),
+            self.max_population.unwrap_or_default(),
+            self.memory_usage()
+        ));
+    }
+
+    /// The memory the process is using, or where the OS doesn't say, a rough estimate of what
+    /// the live and decaying cells take up.
+    fn memory_usage(&self) -> String {
+        match memory::resident_bytes() {
+            Some(bytes) => memory::describe(bytes),
+            None => {
+                let cells = self.grid.alive_cells.capacity() + self.grid.dying_cells.capacity();
+                let bytes = cells as u64 * (std::mem::size_of::<Cell>() as u64 + 1);
+                format!("about {}", memory::describe(bytes))
+            }
+        }
+    }
+
+    /// Advances the simulation by `elapsed` of wall-clock time at the configured speed, in
+    /// budgeted mode by as many generations as fit in the frame budget, or in turbo by a fixed
+    /// number of generations.
+    fn advance(&mut self, elapsed: Duration) {
+        if let Some(generations) = self.gens_per_frame {
+            for generation in 1..=generations {
+                self.step();
+                if self.population_exploded() {
+                    self.rate_window_gens += generation;
+                    return;
+                }
+            }
+            self.rate_window_gens += generations;
+            return;
+        }
+        match self.frame_budget {
+            Some(budget) => {
+                let start = Instant::now();
+                loop {
+                    self.step();
+                    self.rate_window_gens += 1;
+                    let extinct = self.grid.alive_cells.is_empty() && self.simulation.is_none();
+                    if start.elapsed() >= budget || extinct || self.population_exploded() {
+                        break;
+                    }
+                }
+            }
+            None => {
+                self.step_debt += elapsed.as_secs_f64() * self.speed;
+                let start = Instant::now();
+                while self.step_debt >= 1.0 {
+                    self.step();
+                    self.rate_window_gens += 1;
+                    self.step_debt -= 1.0;
+                    if self.population_exploded() {
+                        self.step_debt = 0.0;
+                    }
+                    if start.elapsed() >= MAX_CATCH_UP {
+                        // Can't keep up; run slower rather than freezing the window
+                        self.step_debt = 0.0;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Jumps to the generation typed at the prompt, if it's still ahead.
+    fn start_fast_forward(&mut self) {
+        let Some(prompt) = self.goto_prompt.take() else {
+            return;
+        };
+        match prompt.parse::<usize>() {
+            Ok(target) if target > self.grid.generation => {
+                self.fast_forward = Some((target, self.grid.generation));
+                self.status(&format!("Going to generation {}", target));
+            }
+            Ok(target) => self.status(&format!(
+                "Already past generation {} (at {})",
+                target, self.grid.generation
+            )),
+            Err(_) => {}
+        }
+    }
+
+    /// Steps toward the fast-forward target for a frame's worth of time, pausing there once
+    /// it's reached, or early if the population grows past `--max-population` or the
+    /// generation stops advancing.
+    fn tick_fast_forward(&mut self) {
+        let Some((target, _)) = self.fast_forward else {
+            return;
+        };
+        let start = Instant::now();
+        while self.grid.generation < target && start.elapsed() < FAST_FORWARD_BUDGET {
+            let before = self.grid.generation;
+            self.step();
+            self.rate_window_gens += 1;
+            // Finished battles and Margolus running backwards don't move toward the target
+            if self.grid.generation <= before {
+                self.fast_forward = None;
+                self.running = false;
+                self.status(&format!(
+                    "Stopped at generation {}: the simulation isn't stepping forward",
+                    self.grid.generation
+                ));
+                return;
+            }
+            if self.population_exploded() {
+                self.fast_forward = None;
+                self.pause_for_population();
+                return;
+            }
+        }
+        if self.grid.generation >= target {
+            self.fast_forward = None;
+            self.running = false;
+            self.status(&format!("Reached generation {}", self.grid.generation));
+        }
+    }
+
+    /// Records a user event for `--record-session`, stopping the recording if it can't be
+    /// written. `event` is only built while recording.
+    fn log_event(&mut self, event: impl FnOnce(&Self) -> Event) {
+        if self.session.is_none() {
+            return;
+        }
+        let event = event(self);
+        if let Some(session) = &mut self.session {
+            if let Err(err) = session.record(self.grid.generation, event) {
+                eprintln!("{}, stopping the session recording", err);
+                self.session = None;
+            }
+        }
+    }
+
+    /// Starts recording the session to `path`, beginning with the current state.
+    fn start_session(&mut self, path: &Path) -> Result<(), String> {
+        self.session = Some(SessionRecorder::create(path, &self.snapshot())?);
+        // Whether the run starts paused isn't part of the snapshot
+        self.log_event(|game| Event::Running(game.running));
+        Ok(())
+    }
+
+    /// Starts playing back the session recorded in `path` from the state it began in.
+    fn start_playback(&mut self, path: &Path) -> Result<(), String> {
+        let (start, playback) = Playback::load(path)?;
+        self.restore(start);
+        self.playback = Some(playback);
+        self.status(&format!(
+            "Replaying {}, press Escape to stop",
+            path.display()
+        ));
+        Ok(())
+    }
+
+    /// Applies every recorded event that's due, then steps toward the next one at the
+    /// configured speed, ending the replay after the last event.
+    fn tick_playback(&mut self, elapsed: Duration) {
+        while let Some(event) = self
+            .playback
+            .as_mut()
+            .and_then(|playback| playback.next_event(self.grid.generation, !self.running))
+        {
+            self.apply_event(event);
+        }
+        let Some(playback) = &self.playback else {
+            return;
+        };
+        let Some(target) = playback.next_generation() else {
+            self.playback = None;
+            self.status(&format!(
+                "Replay finished at generation {}",
+                self.grid.generation
+            ));
+            return;
+        };
+        self.step_debt += elapsed.as_secs_f64() * self.speed;
+        let start = Instant::now();
+        while self.step_debt >= 1.0 && self.grid.generation < target {
+            self.step();
+            self.rate_window_gens += 1;
+            self.step_debt -= 1.0;
+            if start.elapsed() >= MAX_CATCH_UP {
+                self.step_debt = 0.0;
+            }
+        }
+    }
+
+    /// Replays one recorded event.
+    fn apply_event(&mut self, event: Event) {
+        match event {
+            Event::Edit(edit) => self.edit_cells(edit),
+            Event::Clear => self.clear(),
+            Event::Reset => self.reset(),
+            Event::Load(snapshot) => self.restore(snapshot),
+            Event::Rules(rules) => match Rules::from_string(&rules) {
+                Ok(rules) => self.set_rules(rules),
+                Err(err) => eprintln!("Failed to parse rules from the session: {}", err),
             },
-            Err(err) => eprintln!("Failed to read game state from file: {}", err),
+            Event::Running(running) => self.set_running(running),
+        }
+    }
+
+    fn set_running(&mut self, running: bool) {
+        self.running = running;
+        self.log_event(|_| Event::Running(running));
+    }
+
+    /// Switches rules, keeping the cells.
+    fn set_rules(&mut self, rules: Rules) {
+        self.grid.rules = rules;
+        self.gpu_stale = true;
+        self.journal.forget_states();
+        self.census = None;
+        self.unsaved = true;
+        self.refresh_live_counts();
+        self.log_event(|game| Event::Rules(game.grid.rules.as_string()));
+    }
+
+    /// Whether the newest elementary automaton row is on screen, so the view should follow it.
+    fn following_row(&self, height: f32) -> bool {
+        self.elementary.is_some_and(|elementary| {
+            self.camera.cell_to_screen(Cell(0, elementary.row() + 1)).1 <= height
+        })
+    }
+
+    /// Scrolls down just far enough to keep the newest elementary automaton row on screen.
+    fn scroll_to_row(&mut self, height: f32) {
+        if let Some(elementary) = self.elementary {
+            let bottom = (elementary.row() + 1) as f32 * self.camera.cell_size;
+            self.camera.offset_y = self.camera.offset_y.min(height - bottom);
+        }
+    }
+
+    /// Refreshes the achieved generations-per-second figure twice a second.
+    fn update_rate(&mut self) {
+        let elapsed = self.rate_window_start.elapsed();
+        if elapsed >= Duration::from_millis(500) {
+            self.gens_per_sec = self.rate_window_gens as f64 / elapsed.as_secs_f64();
+            self.rate_window_start = Instant::now();
+            self.rate_window_gens = 0;
+        }
+    }
+
+    fn step(&mut self) {
+        if let Some(simulation) = &mut self.simulation {
+            let start = Instant::now();
+            simulation.step();
+            self.profiler.record(Phase::RuleEval, start.elapsed());
+            // The history and journal follow live cells, which simulations don't keep
+            self.grid.generation = simulation.generation();
+            let counts: Vec<usize> = simulation
+                .populations()
+                .iter()
+                .map(|population| population.count)
+                .collect();
+            if !counts.is_empty() {
+                self.population_graph.record_all(counts);
+            }
+            self.capture_gif_frame();
+            self.write_video_frame();
+            return;
+        }
+        if let Some(turmites) = &mut self.turmites {
+            let start = Instant::now();
+            turmites.step(&mut self.grid.alive_cells);
+            self.profiler.record(Phase::RuleEval, start.elapsed());
+            self.finish_step();
+            return;
+        }
+        if let Some(elementary) = &mut self.elementary {
+            let start = Instant::now();
+            elementary.step(&mut self.grid.alive_cells);
+            self.profiler.record(Phase::RuleEval, start.elapsed());
+            self.finish_step();
+            return;
+        }
+        if let Some(table) = &mut self.rule_table {
+            let start = Instant::now();
+            let mut cells = self.grid.dying_cells.clone();
+            cells.extend(self.grid.alive_cells.iter().map(|&cell| (cell, 1)));
+            let next = table.step(&cells);
+            self.profiler.record(Phase::RuleEval, start.elapsed());
+
+            let start = Instant::now();
+            self.grid.alive_cells.clear();
+            self.grid.dying_cells.clear();
+            for (cell, state) in next {
+                if state == 1 {
+                    self.grid.alive_cells.insert(cell);
+                } else {
+                    self.grid.dying_cells.insert(cell, state);
+                }
+            }
+            self.profiler.record(Phase::StateSwap, start.elapsed());
+            self.finish_step();
+            return;
+        }
+        self.gpu_stale = true;
+        let profiler = &mut self.profiler;
+        self.grid.step_timed(|phase, elapsed| {
+            let phase = match phase {
+                StepPhase::NeighborCount => Phase::NeighborCount,
+                StepPhase::RuleEval => Phase::RuleEval,
+                StepPhase::StateSwap => Phase::StateSwap,
+            };
+            profiler.record(phase, elapsed);
+        });
+        self.refresh_live_counts();
+        self.record_generation();
+    }
+
+    /// Whether the GPU can run the current rules: plain two-state Moore B/S rules.
+    fn gpu_supported(&self) -> bool {
+        self.turmites.is_none()
+            && self.elementary.is_none()
+            && self.rule_table.is_none()
+            && !self.grid.rules.is_ltl()
+            && self.grid.rules.neighborhood == Neighborhood::Moore
+            && self.grid.rules.states == 2
+    }
+
+    /// Runs this frame's generations on the GPU in one batch and copies the result back. History
+    /// and the journal only see the last generation of each batch. Returns false when the GPU
+    /// can't take the current setup, leaving the frame to `advance`.
+    fn advance_gpu(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        elapsed: Duration,
+    ) -> bool {
+        let Some(torus) = self.grid.torus else {
+            return false;
+        };
+        if !self.use_gpu || !self.gpu_supported() {
+            return false;
+        }
+        if self.gpu.is_none() {
+            match GpuStepper::new(device, torus) {
+                Ok(gpu) => self.gpu = Some(gpu),
+                Err(err) => {
+                    eprintln!("GPU stepping unavailable, using the CPU: {}", err);
+                    self.use_gpu = false;
+                    return false;
+                }
+            }
+        }
+        let generations = match (self.gens_per_frame, self.frame_budget) {
+            (Some(generations), _) => generations,
+            (None, Some(_)) => self.gpu_batch,
+            (None, None) => {
+                self.step_debt += elapsed.as_secs_f64() * self.speed;
+                let due = self.step_debt.floor();
+                self.step_debt -= due;
+                due as usize
+            }
+        }
+        .min(MAX_GPU_BATCH);
+        let Some(gpu) = &mut self.gpu else {
+            return false;
+        };
+        if generations == 0 {
+            return true;
+        }
+
+        let start = Instant::now();
+        gpu.set_rules(
+            queue,
+            bitboard::count_mask(&self.grid.rules.birth),
+            bitboard::count_mask(&self.grid.rules.survival),
+        );
+        if self.gpu_stale {
+            gpu.upload(queue, &self.grid.alive_cells);
+        }
+        gpu.step(device, queue, generations);
+        self.grid.alive_cells = gpu.download(device, queue);
+        self.gpu_stale = false;
+        let took = start.elapsed();
+        self.profiler.record(Phase::RuleEval, took);
+        if let Some(budget) = self.frame_budget {
+            self.gpu_batch = if took < budget {
+                (self.gpu_batch * 2).min(MAX_GPU_BATCH)
+            } else {
+                (self.gpu_batch / 2).max(1)
+            };
+        }
+
+        self.grid.generation += generations;
+        self.rate_window_gens += generations;
+        self.refresh_live_counts();
+        self.record_generation();
+        true
+    }
+
+    /// Bookkeeping after a generation computed outside the per-cell engine.
+    fn finish_step(&mut self) {
+        self.grid.generation += 1;
+        self.refresh_live_counts();
+        self.record_generation();
+    }
+
+    /// Hands the new generation to everything that records or watches the run.
+    fn record_generation(&mut self) {
+        if let Some(envelope) = &mut self.envelope {
+            envelope.extend(&self.grid.alive_cells);
+        }
+        if let Some(activity) = &mut self.activity {
+            activity.record(&self.grid.alive_cells);
+        }
+        if let Some(trails) = &mut self.trails {
+            trails.record(&self.grid.alive_cells);
+        }
+        self.record_history();
+        self.capture_gif_frame();
+        self.write_video_frame();
+        self.population_graph.record(self.grid.alive_cells.len());
+        self.journal
+            .observe(self.grid.generation, &self.grid.alive_cells);
+    }
+
+    /// Shows the history envelope, starting from the current cells, or hides and forgets it.
+    fn toggle_envelope(&mut self) {
+        if self.envelope.take().is_some() {
+            self.status("History envelope hidden");
+        } else {
+            self.envelope = Some(self.grid.alive_cells.clone());
+            self.status("History envelope shown");
+        }
+    }
+
+    /// Shows the activity heat overlay, starting cold, or hides and forgets it.
+    fn toggle_activity(&mut self) {
+        if self.activity.take().is_some() {
+            self.status("Activity heat hidden");
+        } else {
+            self.activity = Some(ActivityMap::new(&self.grid.alive_cells));
+            self.status("Activity heat shown");
+        }
+    }
+
+    /// Starts the history envelope, activity heat and trails over from the current cells, if
+    /// they're shown.
+    fn restart_overlays(&mut self) {
+        if let Some(envelope) = &mut self.envelope {
+            envelope.clone_from(&self.grid.alive_cells);
+        }
+        if let Some(activity) = &mut self.activity {
+            activity.restart(&self.grid.alive_cells);
+        }
+        if let Some(trails) = &mut self.trails {
+            trails.restart(&self.grid.alive_cells);
+        }
+    }
+
+    /// Recomputes each live cell's live-neighbor count for heat coloring (no-op when it's off).
+    fn refresh_live_counts(&mut self) {
+        if !self.heat_map {
+            return;
+        }
+        let counts = self
+            .grid
+            .alive_cells
+            .iter()
+            .map(|&cell| {
+                let count = self
+                    .grid
+                    .neighbors(cell)
+                    .into_iter()
+                    .filter(|neighbor| self.grid.alive_cells.contains(neighbor))
+                    .count();
+                (cell, count)
+            })
+            .collect();
+        self.live_counts = counts;
+    }
+
+    /// The rule table's own color for a state, if it has one.
+    fn table_color(&self, state: u8) -> Option<Color> {
+        let (r, g, b) = self.rule_table.as_ref()?.color(state)?;
+        Some(Color::new(r, g, b, 1.0))
+    }
+
+    /// Decaying cells fade from bright to dark blue as they approach death.
+    fn dying_color(&self, state: u8) -> Color {
+        if let Some(color) = self.table_color(state) {
+            return color;
+        }
+        let states = self
+            .rule_table
+            .as_ref()
+            .map_or(u16::from(self.grid.rules.states), |table| table.states);
+        let t = state.saturating_sub(1) as f32 / (states.max(2) - 1) as f32;
+        Color::new(0.2 * (1.0 - t), 0.6 * (1.0 - t) + 0.1, 1.0 - 0.7 * t, 1.0)
+    }
+
+    /// Rasterizes the current view on the CPU, matching what `draw` puts on screen.
+    fn render_frame(&self, width: u32, height: u32) -> Frame {
+        let mut frame = Frame::new(width, height);
+        if let Some(image) = self.simulation_image(width as f32, height as f32) {
+            for y in 0..image.height as i32 {
+                for x in 0..image.width as i32 {
+                    let cell = Cell(image.origin.0 + x, image.origin.1 + y);
+                    if let Some([r, g, b, a]) = image.get(cell) {
+                        frame.fill_rect(self.camera.cell_rect(cell), Color::from_rgba(r, g, b, a));
+                    }
+                }
+            }
+        }
+        let visible = |cell: &Cell| self.on_screen(*cell, width as f32, height as f32);
+        for (&cell, &state) in self
+            .grid
+            .dying_cells
+            .iter()
+            .filter(|(cell, _)| visible(cell))
+        {
+            frame.fill_rect(self.camera.cell_rect(cell), self.dying_color(state));
+        }
+        if self.camera.cell_size < 1.0 {
+            let cells = self.grid.alive_cells.iter().copied();
+            let levels = raster::density(cells, &self.camera, width, height);
+            for (pixel, level) in frame.pixels.chunks_exact_mut(4).zip(levels) {
+                if level > 0 {
+                    pixel[..3].fill(level);
+                }
+            }
+        } else {
+            for &cell in self.grid.alive_cells.iter().filter(|cell| visible(cell)) {
+                frame.fill_rect(self.camera.cell_rect(cell), self.live_color(cell));
+            }
+        }
+        let agents = self.turmites.iter().flat_map(Turmites::agents);
+        for agent in agents.filter(|agent| visible(&agent.cell)) {
+            frame.fill_rect(self.camera.cell_rect(agent.cell), Color::RED);
+        }
+        frame
+    }
+
+    /// The part of the running simulation inside a `width` x `height` view, one pixel per cell.
+    fn simulation_image(&self, width: f32, height: f32) -> Option<CellImage> {
+        let simulation = self.simulation.as_ref()?;
+        let (mut min, mut max) = (
+            self.screen_to_cell(0.0, 0.0),
+            self.screen_to_cell(width, height),
+        );
+        if let Some((low, high)) = simulation.bounds() {
+            min = Cell(min.0.max(low.0), min.1.max(low.1));
+            max = Cell(max.0.min(high.0), max.1.min(high.1));
+        }
+        if min.0 > max.0 || min.1 > max.1 {
+            return None;
+        }
+        // Keep the middle of oversized views, so the image stays small enough to upload
+        let clip = |low: i32, high: i32| {
+            let excess = (high - low + 1 - MAX_SIMULATION_SPAN).max(0);
+            (low + excess / 2, high - (excess - excess / 2))
+        };
+        let ((left, right), (top, bottom)) = (clip(min.0, max.0), clip(min.1, max.1));
+        let mut image = CellImage::new(
+            Cell(left, top),
+            (right - left + 1) as usize,
+            (bottom - top + 1) as usize,
+        );
+        simulation.draw(&mut image);
+        Some(image)
+    }
+
+    fn live_color(&self, cell: Cell) -> Color {
+        if self.heat_map {
+            self.heat_color(cell)
+        } else if let Some(color) = self.table_color(1) {
+            color
+        } else if let Some(turmites) = &self.turmites {
+            let (r, g, b) = turmites.color_of(cell);
+            Color::new(r, g, b, 1.0)
+        } else {
+            Color::WHITE
+        }
+    }
+
+    /// Cold blue for isolated cells through to hot red for fully crowded ones.
+    fn heat_color(&self, cell: Cell) -> Color {
+        let count = self.live_counts.get(&cell).copied().unwrap_or(0);
+        let t = (count as f32 / self.grid.rules.max_neighbors() as f32).min(1.0);
+        Color::new(t, 0.2, 1.0 - t, 1.0)
+    }
+
+    fn screen_to_cell(&self, x: f32, y: f32) -> Cell {
+        self.camera.screen_to_cell(x, y)
+    }
+
+    /// Whether any part of `cell` lands inside a `width` x `height` window.
+    fn on_screen(&self, cell: Cell, width: f32, height: f32) -> bool {
+        self.camera.on_screen(cell, width, height)
+    }
+
+    /// Starts painting with the right button: cells under the drag all take the state opposite
+    /// to the first one's.
+    fn start_stroke(&mut self, x: f32, y: f32) {
+        let cell = self.screen_to_cell(x, y);
+        let occupied = match &self.simulation {
+            Some(simulation) => simulation.occupied(cell),
+            None => self.grid.alive_cells.contains(&cell),
+        };
+        self.stroke = Some(Stroke {
+            alive: !occupied,
+            last: cell,
+            edit: Edit::default(),
+        });
+        self.paint_to(cell);
+    }
+
+    /// Paints every cell between the last painted one and `cell`, so fast drags leave no gaps.
+    fn paint_to(&mut self, cell: Cell) {
+        let Some(stroke) = &mut self.stroke else {
+            return;
+        };
+        let line = shapes::line(stroke.last, cell);
+        let brush = self.brush;
+        let painted = line.into_iter().flat_map(|cell| brush.cells_at(cell));
+        if let Some(simulation) = &mut self.simulation {
+            // Simulations keep their own state, outside undo and session recording
+            for painted in painted {
+                self.unsaved |= simulation.handle_input(Input::Paint {
+                    cell: painted,
+                    erase: !stroke.alive,
+                });
+            }
+            stroke.last = cell;
+            return;
+        }
+        let mut edit = Edit::set(painted, stroke.alive, &self.grid.alive_cells);
+        if let Some(torus) = self.grid.torus {
+            edit = edit.wrapped(torus, &self.grid.alive_cells);
+        }
+        stroke.last = cell;
+        if edit.is_empty() {
+            return;
+        }
+        edit.apply(&mut self.grid.alive_cells);
+        self.log_event(|_| Event::Edit(edit.clone()));
+        if let Some(stroke) = &mut self.stroke {
+            stroke.edit.extend(edit);
+        }
+        self.cells_edited();
+    }
+
+    fn next_tool(&mut self) {
+        self.tool = self.tool.next();
+        self.status(&format!("Drawing tool: {}", self.tool.name()));
+    }
+
+    /// The shape being dragged out, from where the button went down to the mouse.
+    fn shape_preview(&self) -> Option<Vec<Cell>> {
+        let start = self.shape_start?;
+        let end = self.screen_to_cell(self.mouse_pos.0, self.mouse_pos.1);
+        self.tool.cells(start, end)
+    }
+
+    /// Brings the dragged-out shape to life as one undoable edit.
+    fn place_shape(&mut self) {
+        match self.shape_preview() {
+            Some(cells) => self.edit_cells(Edit::set(cells, true, &self.grid.alive_cells)),
+            None => self.status("Shape is too large to draw"),
+        }
+        self.shape_start = None;
+    }
+
+    fn adjust_brush(&mut self, adjust: fn(&mut Brush)) {
+        adjust(&mut self.brush);
+        self.status(&self.brush.describe());
+    }
+
+    /// Finishes the stroke, recording it as a single undoable edit.
+    fn end_stroke(&mut self) {
+        if let Some(stroke) = self.stroke.take() {
+            self.undo.record(stroke.edit);
+        }
+    }
+
+    fn toggle_grid_cell(&mut self, cell: Cell) {
+        if let Some(simulation) = &mut self.simulation {
+            let erase = simulation.occupied(cell);
+            self.unsaved |= simulation.handle_input(Input::Paint { cell, erase });
+            return;
+        }
+        let alive = !self.grid.alive_cells.contains(&cell);
+        self.edit_cells(Edit::set([cell], alive, &self.grid.alive_cells));
+    }
+
+    /// Applies a manual edit to the grid and records it for undo.
+    fn edit_cells(&mut self, edit: Edit) {
+        self.log_event(|_| Event::Edit(edit.clone()));
+        self.apply_edit(edit);
+    }
+
+    /// `edit_cells` without recording the edit in the session, for changes recorded as a
+    /// whole.
+    fn apply_edit(&mut self, edit: Edit) {
+        if let Some(simulation) = &mut self.simulation {
+            for &cell in &edit.added {
+                self.unsaved |= simulation.handle_input(Input::Paint { cell, erase: false });
+            }
+            for &cell in &edit.removed {
+                self.unsaved |= simulation.handle_input(Input::Paint { cell, erase: true });
+            }
+            return;
+        }
+        // Wrapped before it's recorded, so undo takes back the cells that actually changed
+        let edit = match self.grid.torus {
+            Some(torus) => edit.wrapped(torus, &self.grid.alive_cells),
+            None => edit,
+        };
+        edit.apply(&mut self.grid.alive_cells);
+        self.undo.record(edit);
+        self.cells_edited();
+    }
+
+    fn undo_edit(&mut self) {
+        if let Some(change) = self.undo.undo(&mut self.grid.alive_cells) {
+            self.log_event(|_| Event::Edit(change));
+            self.cells_edited();
+        }
+    }
+
+    fn redo_edit(&mut self) {
+        if let Some(change) = self.undo.redo(&mut self.grid.alive_cells) {
+            self.log_event(|_| Event::Edit(change));
+            self.cells_edited();
+        }
+    }
+
+    /// Remembers the current cells and rules as what `reset` goes back to.
+    fn mark_initial(&mut self) {
+        self.initial_cells = self.grid.alive_cells.clone();
+        self.initial_rules = self.grid.rules.clone();
+        self.initial_simulation = self.simulation.as_ref().map(|simulation| simulation.save());
+    }
+
+    /// Turns the running simulation's adjustable setting one notch up or down.
+    fn adjust_simulation(&mut self, up: bool) {
+        match self.simulation.as_mut().and_then(|sim| sim.adjust(up)) {
+            Some(setting) => self.status(&setting),
+            None => self.status("Nothing to adjust"),
+        }
+    }
+
+    /// Flips which way time runs in the simulation, for rules that can be undone.
+    fn reverse_simulation(&mut self) {
+        match self.simulation.as_mut().and_then(|sim| sim.reverse()) {
+            Some(true) => self.status("Running backwards"),
+            Some(false) => self.status("Running forwards"),
+            None => self.status("This simulation can't run backwards"),
+        }
+    }
+
+    /// Shows or hides one of the simulation's layers.
+    fn toggle_layer(&mut self, layer: usize) {
+        match self
+            .simulation
+            .as_mut()
+            .and_then(|sim| sim.toggle_layer(layer))
+        {
+            Some(toggled) => self.status(&toggled),
+            None => self.status("This simulation has no layers to toggle"),
+        }
+    }
+
+    /// Sends the simulation's own action to `cell`, e.g. an electron down a WireWorld wire.
+    fn activate(&mut self, cell: Cell) {
+        if let Some(simulation) = &mut self.simulation {
+            simulation.handle_input(Input::Activate(cell));
+        }
+    }
+
+    /// Kills every cell, as an undoable edit.
+    fn clear(&mut self) {
+        self.fast_forward = None;
+        if let Some(simulation) = &mut self.simulation {
+            *simulation = simulation.blank();
+            self.grid.generation = simulation.generation();
+            self.population_graph.clear();
+            self.unsaved = true;
+            return;
+        }
+        self.log_event(|_| Event::Clear);
+        self.grid.dying_cells.clear();
+        let edit = Edit::set(self.grid.alive_cells.clone(), false, &self.grid.alive_cells);
+        self.apply_edit(edit);
+        self.restart_overlays();
+    }
+
+    /// Goes back to the initially loaded pattern, rules and generation count.
+    fn reset(&mut self) {
+        self.fast_forward = None;
+        if let (Some(simulation), Some(state)) = (&mut self.simulation, &self.initial_simulation) {
+            if let Err(err) = simulation.restore(state.clone()) {
+                eprintln!("{}", err);
+            }
+            self.grid.generation = simulation.generation();
+            self.population_graph.clear();
+            self.unsaved = true;
+            return;
+        }
+        self.log_event(|_| Event::Reset);
+        self.grid.dying_cells.clear();
+        let edit = Edit {
+            added: self
+                .initial_cells
+                .difference(&self.grid.alive_cells)
+                .copied()
+                .collect(),
+            removed: self
+                .grid
+                .alive_cells
+                .difference(&self.initial_cells)
+                .copied()
+                .collect(),
+        };
+        self.apply_edit(edit);
+        self.restart_overlays();
+        if let Some(turmites) = &mut self.turmites {
+            turmites.restart();
+        }
+        if let Some(elementary) = &mut self.elementary {
+            elementary.restart();
+        }
+        self.grid.rules = self.initial_rules.clone();
+        self.grid.generation = 1;
+        self.journal.reset();
+        self.population_graph.clear();
+        self.census = None;
+        self.refresh_live_counts();
+    }
+
+    /// Bookkeeping shared by every manual edit of `alive_cells`.
+    fn cells_edited(&mut self) {
+        // A cell brought back to life stops decaying
+        let alive_cells = &self.grid.alive_cells;
+        self.grid
+            .dying_cells
+            .retain(|cell, _| !alive_cells.contains(cell));
+        self.wrap_into_torus();
+        if let Some(turmites) = &mut self.turmites {
+            turmites.sync(&self.grid.alive_cells);
+        }
+        self.refresh_live_counts();
+        self.journal.forget_states();
+        self.census = None;
+        self.unsaved = true;
+    }
+
+    /// Counts the objects the pattern is made of, for the console and the HUD.
+    fn take_census(&mut self) {
+        let counts = census::census(&self.grid.alive_cells);
+        let lines = census::lines(&counts);
+        if lines.is_empty() {
+            self.status("Census: no live cells");
+        } else {
+            self.status(&format!(
+                "Census at generation {}: {}",
+                self.grid.generation,
+                lines.join(", ")
+            ));
+        }
+        self.census = Some((self.grid.generation, lines));
+    }
+
+    /// Moves cells placed outside the torus onto the grid cells they wrap around to.
+    fn wrap_into_torus(&mut self) {
+        if let Some(torus) = self.grid.torus {
+            let cells = std::mem::take(&mut self.grid.alive_cells);
+            self.grid.alive_cells = cells.into_iter().map(|cell| torus.wrap(cell)).collect();
+            let dying = std::mem::take(&mut self.grid.dying_cells);
+            let alive_cells = &self.grid.alive_cells;
+            self.grid.dying_cells = dying
+                .into_iter()
+                .map(|(cell, state)| (torus.wrap(cell), state))
+                .filter(|(cell, _)| !alive_cells.contains(cell))
+                .collect();
+        }
+        self.gpu_stale = true;
+    }
+
+    /// Shows the keyboard cursor at the middle of the view, or hides it.
+    fn toggle_cursor(&mut self, width: f32, height: f32) {
+        self.cursor = match self.cursor {
+            Some(_) => None,
+            None => Some(self.screen_to_cell(width / 2.0, height / 2.0)),
+        };
+        self.selection_anchor = None;
+    }
+
+    /// Moves the cursor by one cell; with `extend` the selection grows from where it started.
+    fn move_cursor(&mut self, dx: i32, dy: i32, extend: bool, width: f32, height: f32) {
+        let Some(cursor) = self.cursor else {
+            return;
+        };
+        if extend {
+            self.selection_anchor.get_or_insert(cursor);
+        } else {
+            self.selection_anchor = None;
+        }
+        let moved = cursor.offset(dx, dy).unwrap_or(cursor);
+        self.cursor = Some(moved);
+
+        self.camera.keep_in_view(moved, width, height);
+    }
+
+    /// The selected rectangle as its smallest and largest corner.
+    fn selection(&self) -> Option<(Cell, Cell)> {
+        let (anchor, cursor) = (self.selection_anchor?, self.cursor?);
+        Some((
+            Cell(anchor.0.min(cursor.0), anchor.1.min(cursor.1)),
+            Cell(anchor.0.max(cursor.0), anchor.1.max(cursor.1)),
+        ))
+    }
+
+    /// Copies the selection, or the whole pattern without one, both for pasting here and as
+    /// RLE on the system clipboard.
+    fn copy_selection(&mut self) {
+        let cells: HashSet<Cell> = match self.selection() {
+            Some((min, max)) => self
+                .grid
+                .alive_cells
+                .iter()
+                .copied()
+                .filter(|cell| {
+                    (min.0..=max.0).contains(&cell.0) && (min.1..=max.1).contains(&cell.1)
+                })
+                .collect(),
+            None => self.grid.alive_cells.clone(),
+        };
+        if cells.is_empty() {
+            return;
+        }
+        let rle = patterns::to_rle(&cells, &self.grid.rules.as_string());
+        match self.system_clipboard.set_text(rle) {
+            Ok(()) => self.status(&format!(
+                "Copied {} cells to the clipboard as RLE",
+                cells.len()
+            )),
+            Err(err) => eprintln!("{}", err),
+        }
+        self.clipboard = Some(Paste::new(cells));
+    }
+
+    /// Holds the RLE or plaintext pattern on the system clipboard on the cursor, falling back
+    /// to the last selection copied here when there isn't one.
+    fn paste_from_clipboard(&mut self) {
+        let text = self.system_clipboard.text();
+        if let Ok(text) = &text {
+            if text.len() > MAX_PASTE_BYTES {
+                self.status(&format!(
+                    "Clipboard is too large to paste: {} bytes, at most {}",
+                    text.len(),
+                    MAX_PASTE_BYTES
+                ));
+                return;
+            }
+        }
+        let pasted = text.and_then(|text| patterns::parse_text(&text));
+        match pasted {
+            Ok(pattern) => self.paste = Some(Paste::new(pattern.cells)),
+            Err(_) if self.clipboard.is_some() => self.paste = self.clipboard.clone(),
+            Err(err) => self.status(&format!("Nothing to paste: {}", err)),
+        }
+    }
+
+    /// Where a pending paste would be placed: centered on the keyboard cursor or the mouse.
+    fn paste_origin_for(&self, paste: &Paste) -> Cell {
+        let center = self
+            .cursor
+            .unwrap_or_else(|| self.screen_to_cell(self.mouse_pos.0, self.mouse_pos.1));
+        paste.origin_centered_on(center)
+    }
+
+    /// Holds one of the built-in patterns on the cursor, ready to stamp.
+    fn pick_stamp(&mut self, index: usize) {
+        if let Some((name, paste)) = library::stamp(index) {
+            self.status(&format!("Stamping {}", name));
+            self.paste = Some(paste);
+        }
+    }
+
+    /// Opens the pattern catalog, reading `catalog_dir` afresh, or closes it.
+    fn toggle_catalog(&mut self) {
+        if self.catalog.take().is_some() {
+            return;
+        }
+        match Catalog::load(&self.catalog_dir) {
+            Ok(catalog) if catalog.entries.is_empty() => {
+                self.status(&format!("No patterns in {}", catalog.dir.display()))
+            }
+            Ok(catalog) => self.catalog = Some(catalog),
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+
+    /// Closes the catalog, holding its selected pattern on the cursor, ready to stamp.
+    fn stamp_from_catalog(&mut self) {
+        let Some(catalog) = self.catalog.take() else {
+            return;
+        };
+        let Some(entry) = catalog.selected() else {
+            return;
+        };
+        match &entry.rules {
+            Some(rules) if *rules != self.grid.rules.as_string() => self.status(&format!(
+                "Stamping {} (written for {}, running {})",
+                entry.name,
+                rules,
+                self.grid.rules.as_string()
+            )),
+            _ => self.status(&format!("Stamping {}", entry.name)),
+        }
+        self.paste = Some(entry.paste.clone());
+    }
+
+    /// Rotates (`,` and `.`) or mirrors (`M`, Shift+`M` for top to bottom) a pending paste.
+    fn transform_paste(&mut self, keycode: KeyCode, shift: bool) {
+        let Some(paste) = &mut self.paste else {
+            return;
+        };
+        match keycode {
+            KeyCode::Period => paste.rotate_clockwise(),
+            KeyCode::Comma => paste.rotate_anticlockwise(),
+            KeyCode::M if shift => paste.flip_vertical(),
+            KeyCode::M => paste.flip_horizontal(),
+            _ => {}
+        }
+    }
+
+    fn place_paste(&mut self) {
+        if let Some(paste) = self.paste.take() {
+            let origin = self.paste_origin_for(&paste);
+            let edit = Edit::set(paste.cells_at(origin), true, &self.grid.alive_cells);
+            self.edit_cells(edit);
+        }
+    }
+
+    /// Replaces the rectangle between two corners with live cells at `soup_density`.
+    fn random_fill(&mut self, min: Cell, max: Cell) {
+        let area = (max.0 as i64 - min.0 as i64 + 1) * (max.1 as i64 - min.1 as i64 + 1);
+        if area > MAX_SOUP_AREA {
+            eprintln!(
+                "Refusing to fill {} cells with a random soup; zoom in first",
+                area
+            );
+            return;
+        }
+        let mut edit = Edit::default();
+        for y in min.1..=max.1 {
+            for x in min.0..=max.0 {
+                let cell = Cell(x, y);
+                let alive = self.rng.gen_bool(self.soup_density);
+                if alive && !self.grid.alive_cells.contains(&cell) {
+                    edit.added.push(cell);
+                } else if !alive && self.grid.alive_cells.contains(&cell) {
+                    edit.removed.push(cell);
+                }
+            }
+        }
+        self.edit_cells(edit);
+    }
+
+    /// Random fill of the selection if there is one, otherwise of everything on screen.
+    fn random_fill_view(&mut self, width: f32, height: f32) {
+        let (min, max) = self.selection().unwrap_or_else(|| {
+            (
+                self.screen_to_cell(0.0, 0.0),
+                self.screen_to_cell(width - 1.0, height - 1.0),
+            )
+        });
+        self.random_fill(min, max);
+    }
+
+    /// Makes every cell in the selection alive (`alive`) or dead.
+    fn fill_selection(&mut self, alive: bool) {
+        let Some((min, max)) = self.selection() else {
+            return;
+        };
+        let cells = (min.1..=max.1).flat_map(|y| (min.0..=max.0).map(move |x| Cell(x, y)));
+        self.edit_cells(Edit::set(cells, alive, &self.grid.alive_cells));
+    }
+
+    fn save_to_file(&mut self, file_path: &str) {
+        match self.save_json() {
+            Ok(json) => {
+                if let Err(err) = fs::write(file_path, json) {
+                    eprintln!("Failed to save game state: {}", err);
+                } else {
+                    self.unsaved = false;
+                    self.status(&format!("Game state saved to {}", file_path));
+                }
+            }
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+
+    fn save_json(&self) -> Result<String, String> {
+        let save_state = SaveState {
+            alive_cells: self.grid.alive_cells.clone(),
+            rules: self.grid.rules.as_string(),
+            dying_cells: self
+                .grid
+                .dying_cells
+                .iter()
+                .map(|(&cell, &state)| (cell, state))
+                .collect(),
+            view: Some(SavedView {
+                camera: self.camera,
+                running: self.running,
+                generation: self.grid.generation,
+            }),
+            simulation: self.simulation.as_ref().map(|simulation| SavedSimulation {
+                model: simulation.name().to_string(),
+                state: simulation.save(),
+            }),
+        };
+        serde_json::to_string(&save_state)
+            .map_err(|err| format!("Failed to serialize game state: {}", err))
+    }
+
+    /// Writes the next autosave if one is due.
+    fn tick_autosave(&mut self) {
+        match &self.autosave {
+            Some(autosave) if autosave.due(self.grid.generation) => {}
+            _ => return,
+        }
+        let saved = self.save_json().and_then(|json| match &mut self.autosave {
+            Some(autosave) => autosave.save(self.grid.generation, json),
+            None => Ok(None),
+        });
+        match saved {
+            Ok(Some(path)) => self.status(&format!("Autosaved to {}", path.display())),
+            Ok(None) => {}
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+
+    fn export_rle(&self, file_path: &str) {
+        let rle = patterns::to_rle(&self.grid.alive_cells, &self.grid.rules.as_string());
+        match fs::write(file_path, rle) {
+            Ok(()) => self.status(&format!("Pattern exported as RLE to {}", file_path)),
+            Err(err) => eprintln!("Failed to export pattern: {}", err),
+        }
+    }
+
+    fn load_from_file(&mut self, file_path: &str) {
+        if let Err(err) = self.load_pattern_file(file_path, None) {
+            eprintln!("{}", err);
+        }
+    }
+
+    /// Adds a pattern file's cells to the grid with its top-left corner at `origin`, keeping
+    /// the cells already there. Rules the file names are switched to, as loading it would.
+    fn stamp_pattern_file(&mut self, file_path: &str, origin: Cell) -> Result<(), String> {
+        let (mut pattern, _) = patterns::load_pattern(Path::new(file_path))?;
+        pattern.place_at(origin);
+        self.grid.alive_cells.extend(pattern.cells);
+        self.grid.dying_cells.extend(pattern.dying);
+        if let Some(rule_str) = pattern.rules.filter(|_| !self.split_view) {
+            match Rules::from_string(&rule_str) {
+                Ok(rules) => self.grid.rules = rules,
+                Err(err) => eprintln!("Failed to parse rules from {}: {}", file_path, err),
+            }
+        }
+        self.pattern_replaced();
+        self.status(&format!(
+            "Pattern {} placed at {},{}",
+            file_path, origin.0, origin.1
+        ));
+        Ok(())
+    }
+
+    /// Loads a file dropped on the window; patterns without a position of their own are
+    /// centered in the view.
+    fn load_dropped_file(&mut self, path: &Path, width: f32, height: f32) {
+        let center = self.screen_to_cell(width / 2.0, height / 2.0);
+        if let Err(err) = self.load_pattern_file(&path.to_string_lossy(), Some(center)) {
+            eprintln!("{}", err);
+        }
+    }
+
+    /// Loads a save or pattern file, placing relative patterns at `--origin` or, if given,
+    /// around `center`.
+    fn load_pattern_file(&mut self, file_path: &str, center: Option<Cell>) -> Result<(), String> {
+        if self.simulation.is_some() {
+            return self.load_simulation(file_path);
+        }
+        let (mut pattern, view) = patterns::load_pattern(Path::new(file_path))?;
+        match center {
+            Some(center) => pattern.center_at(center),
+            None => pattern.place_at(self.paste_origin),
+        }
+        self.grid.alive_cells = pattern.cells;
+        self.grid.dying_cells = pattern.dying.into_iter().collect();
+        // Universes being compared keep their own rules
+        if let Some(rule_str) = pattern.rules.filter(|_| !self.split_view) {
+            match Rules::from_string(&rule_str) {
+                Ok(rules) => self.grid.rules = rules,
+                Err(err) => eprintln!("Failed to parse rules from save state: {}", err),
+            }
+        }
+        if let Some(view) = view {
+            self.camera = view.camera;
+            self.running = view.running;
+            self.grid.generation = view.generation;
+        }
+        self.pattern_replaced();
+        self.unsaved = false;
+        self.log_event(|game| Event::Load(game.snapshot()));
+        self.status(&format!("Game state and rules loaded from {}", file_path));
+        Ok(())
+    }
+
+    /// Restores the running simulation from a save it wrote.
+    fn load_simulation(&mut self, file_path: &str) -> Result<(), String> {
+        let json = fs::read_to_string(file_path)
+            .map_err(|err| format!("Failed to read {}: {}", file_path, err))?;
+        let save_state: SaveState = serde_json::from_str(&json)
+            .map_err(|err| format!("Failed to parse {}: {}", file_path, err))?;
+        let Some(simulation) = &mut self.simulation else {
+            return Ok(());
+        };
+        let saved = save_state
+            .simulation
+            .filter(|saved| saved.model == simulation.name())
+            .ok_or_else(|| format!("{} holds no {} state", file_path, simulation.name()))?;
+        simulation.restore(saved.state)?;
+        self.grid.generation = simulation.generation();
+        if let Some(view) = save_state.view {
+            self.camera = view.camera;
+            self.running = view.running;
+        }
+        self.mark_initial();
+        self.unsaved = false;
+        self.status(&format!("Simulation state loaded from {}", file_path));
+        Ok(())
+    }
+
+    /// Bookkeeping after the grid was swapped for a loaded or replayed one.
+    fn pattern_replaced(&mut self) {
+        self.fast_forward = None;
+        self.wrap_into_torus();
+        if let Some(turmites) = &mut self.turmites {
+            turmites.sync(&self.grid.alive_cells);
+        }
+        self.refresh_live_counts();
+        self.restart_overlays();
+        self.journal.reset();
+        self.population_graph.clear();
+        self.census = None;
+        self.undo.clear();
+        self.mark_initial();
+    }
+
+    /// The cells, rules and generation, as a session records them.
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            cells: self.grid.alive_cells.iter().copied().collect(),
+            dying: self
+                .grid
+                .dying_cells
+                .iter()
+                .map(|(&cell, &state)| (cell, state))
+                .collect(),
+            rules: self.grid.rules.as_string(),
+            generation: self.grid.generation,
+        }
+    }
+
+    /// Replaces the grid with a recorded snapshot.
+    fn restore(&mut self, snapshot: Snapshot) {
+        match Rules::from_string(&snapshot.rules) {
+            Ok(rules) => self.grid.rules = rules,
+            Err(err) => eprintln!("Failed to parse rules from the session: {}", err),
+        }
+        self.grid.alive_cells = snapshot.cells.into_iter().collect();
+        self.grid.dying_cells = snapshot.dying.into_iter().collect();
+        self.grid.generation = snapshot.generation;
+        self.pattern_replaced();
+    }
+
+    /// Runs a command from a remote client, returning what to send back.
+    fn run_remote_command(&mut self, command: remote::Command) -> Result<Value, String> {
+        match command {
+            remote::Command::Pause => self.set_running(false),
+            remote::Command::Resume => self.set_running(true),
+            remote::Command::Step(count) => {
+                for _ in 0..count {
+                    self.step();
+                    if self.population_exploded() {
+                        self.pause_for_population();
+                        break;
+                    }
+                }
+            }
+            remote::Command::SetRule(rule) => {
+                if self.turmites.is_some()
+                    || self.elementary.is_some()
+                    || self.rule_table.is_some()
+                    || self.simulation.is_some()
+                {
+                    return Err("The rules aren't used by the current model".to_string());
+                }
+                self.set_rules(Rules::from_string(&rule)?);
+                self.status(&format!("Rules set to {}", self.grid.rules.as_string()));
+            }
+            remote::Command::LoadPattern(path) => self.load_pattern_file(&path, None)?,
+            remote::Command::GetState { cells } => {
+                let mut state = json!({
+                    "generation": self.grid.generation,
+                    "running": self.running,
+                    "rule": self.grid.rules.as_string(),
+                    "population": self.grid.alive_cells.len(),
+                });
+                if let Some(simulation) = &self.simulation {
+                    // Simulations count several kinds of cell instead of live ones
+                    let populations: serde_json::Map<String, Value> = simulation
+                        .populations()
+                        .into_iter()
+                        .map(|population| (population.name.to_string(), json!(population.count)))
+                        .collect();
+                    state["population"] = Value::Object(populations);
+                }
+                if cells {
+                    let mut alive: Vec<Cell> = self.grid.alive_cells.iter().copied().collect();
+                    alive.sort_unstable_by_key(|cell| (cell.1, cell.0));
+                    state["cells"] = json!(alive);
+                }
+                return Ok(state);
+            }
+        }
+        Ok(json!({ "generation": self.grid.generation, "running": self.running }))
+    }
+}
+
+// Event handling for a single universe; `Tabs` forwards events to the active one.
+impl Celleste {
+    /// Size of the part of the window this universe is drawn in.
+    fn view_size(&self, ctx: &Context) -> (f32, f32) {
+        let (width, height) = ctx.gfx.drawable_size();
+        if self.split_view {
+            (width / 2.0, height)
+        } else {
+            (width, height)
+        }
+    }
+
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        let (width, height) = self.view_size(ctx);
+        self.tick_demo(width, height);
+        if self.playback.is_some() {
+            self.tick_playback(ctx.time.delta());
+        } else if self.fast_forward.is_some() {
+            self.tick_fast_forward();
+        } else if self.running {
+            let following = self.following_row(height);
+            let wgpu = ctx.gfx.wgpu();
+            if !self.advance_gpu(&wgpu.device, &wgpu.queue, ctx.time.delta()) {
+                self.advance(ctx.time.delta());
+            }
+            if following {
+                self.scroll_to_row(height);
+            }
+            if let Some(outcome) = self.simulation.as_ref().and_then(|sim| sim.outcome()) {
+                self.running = false;
+                self.status(&outcome);
+            }
+            if self.population_exploded() {
+                self.pause_for_population();
+            }
+            if self.journal.take_extinction() && self.auto_pause {
+                self.running = false;
+                self.status(&format!(
+                    "Paused at generation {}: every cell has died",
+                    self.grid.generation
+                ));
+            }
+            if let Some(period) = self.journal.take_detection() {
+                self.take_census();
+                if period == 1 && (self.auto_pause || self.pause_on_period) {
+                    self.running = false;
+                    self.status(&format!(
+                        "Paused at generation {}: the pattern stopped changing",
+                        self.grid.generation
+                    ));
+                } else if self.pause_on_period {
+                    self.running = false;
+                    self.status(&format!(
+                        "Paused: the pattern repeats with period {}",
+                        period
+                    ));
+                }
+            }
+        }
+        if self.over_population
+            && self
+                .max_population
+                .is_none_or(|cap| self.population() <= cap)
+        {
+            self.over_population = false;
+        }
+        self.update_rate();
+        self.tick_autosave();
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        let start = Instant::now();
+        let mut mb = graphics::MeshBuilder::new();
+        // Only cells inside the window get geometry, however large the pattern
+        let (width, height) = self.view_size(ctx);
+        let visible = |cell: &Cell| self.on_screen(*cell, width, height);
+
+        if let Some(envelope) = &self.envelope {
+            let faded = envelope
+                .iter()
+                .filter(|cell| visible(cell) && !self.grid.alive_cells.contains(cell));
+            for &cell in faded {
+                let rect = self.camera.cell_rect(cell);
+                mb.rectangle(DrawMode::fill(), rect, ENVELOPE_COLOR)?;
+            }
+        }
+
+        for (&cell, &state) in self
+            .grid
+            .dying_cells
+            .iter()
+            .filter(|(cell, _)| visible(cell))
+        {
+            let rect = self.camera.cell_rect(cell);
+            mb.rectangle(DrawMode::fill(), rect, self.dying_color(state))?;
+        }
+
+        let fading = self.trails.iter().flat_map(Trails::cells);
+        for (cell, opacity) in fading.filter(|(cell, _)| visible(cell)) {
+            let rect = self.camera.cell_rect(cell);
+            let color = Color::new(0.6, 0.8, 1.0, 0.8 * opacity);
+            mb.rectangle(DrawMode::fill(), rect, color)?;
+        }
+
+        // Once cells are smaller than a pixel, live cells are drawn as a density image instead
+        let density = self.camera.cell_size < 1.0;
+        for &cell in self
+            .grid
+            .alive_cells
+            .iter()
+            .filter(|cell| !density && visible(cell))
+        {
+            let rect = self.camera.cell_rect(cell);
+            mb.rectangle(DrawMode::fill(), rect, self.live_color(cell))?;
+        }
+
+        // Heat goes over the cells, from a faint red to a bright yellow
+        let warm = self.activity.iter().flat_map(ActivityMap::cells);
+        for (cell, heat) in warm.filter(|(cell, _)| visible(cell)) {
+            let rect = self.camera.cell_rect(cell);
+            let color = Color::new(1.0, 0.2 + 0.8 * heat, 0.0, 0.25 + 0.5 * heat);
+            mb.rectangle(DrawMode::fill(), rect, color)?;
+        }
+
+        let agents = self.turmites.iter().flat_map(Turmites::agents);
+        for agent in agents.filter(|agent| visible(&agent.cell)) {
+            let rect = self.camera.cell_rect(agent.cell);
+            mb.rectangle(DrawMode::fill(), rect, Color::RED)?;
+        }
+        
+        let mesh_data = mb.build();
+        let mesh = Mesh::from_data(ctx, mesh_data);
+        self.profiler.record(Phase::MeshBuild, start.elapsed());
+
+        canvas.draw(&mesh, DrawParam::default());
+
+        if let Some(image) = self.simulation_image(width, height) {
+            let texture = Image::from_pixels(
+                ctx,
+                &image.pixels,
+                ImageFormat::Rgba8UnormSrgb,
+                image.width as u32,
+                image.height as u32,
+            );
+            let (x, y) = self.camera.cell_to_screen(image.origin);
+            let size = self.camera.cell_size;
+            // Cells stay crisp squares however far the view is zoomed in
+            canvas.set_sampler(Sampler::nearest_clamp());
+            canvas.draw(
+                &texture,
+                DrawParam::default().dest([x, y]).scale([size, size]),
+            );
+            canvas.set_default_sampler();
+        }
+
+        let (pixels_wide, pixels_high) = (width as u32, height as u32);
+        if density && pixels_wide > 0 && pixels_high > 0 {
+            let cells = self.grid.alive_cells.iter().copied();
+            let levels = raster::density(cells, &self.camera, pixels_wide, pixels_high);
+            let pixels: Vec<u8> = levels
+                .into_iter()
+                .flat_map(|level| [level, level, level, if level > 0 { 255 } else { 0 }])
+                .collect();
+            let texture = Image::from_pixels(
+                ctx,
+                &pixels,
+                ImageFormat::Rgba8UnormSrgb,
+                pixels_wide,
+                pixels_high,
+            );
+            canvas.draw(&texture, DrawParam::default());
+        }
+
+        if let Some(torus) = self.grid.torus {
+            let corner = Cell(torus.width - 1, torus.height - 1);
+            let bounds = self.camera.region_rect(Cell(0, 0), corner);
+            let outline = Mesh::new_rectangle(
+                ctx,
+                DrawMode::stroke(1.0),
+                bounds,
+                Color::new(0.4, 0.4, 0.4, 1.0),
+            )?;
+            canvas.draw(&outline, DrawParam::default());
+        }
+
+        if let Some(paste) = &self.paste {
+            let mut mb = graphics::MeshBuilder::new();
+            for cell in paste.cells_at(self.paste_origin_for(paste)) {
+                let rect = self.camera.cell_rect(cell);
+                mb.rectangle(DrawMode::fill(), rect, Color::new(0.2, 1.0, 0.4, 0.5))?;
+            }
+            let preview = Mesh::from_data(ctx, mb.build());
+            canvas.draw(&preview, DrawParam::default());
+        }
+
+        if let Some(cells) = self.shape_preview() {
+            let mut mb = graphics::MeshBuilder::new();
+            for cell in cells {
+                let rect = self.camera.cell_rect(cell);
+                mb.rectangle(DrawMode::fill(), rect, Color::new(0.2, 1.0, 0.4, 0.5))?;
+            }
+            let preview = Mesh::from_data(ctx, mb.build());
+            canvas.draw(&preview, DrawParam::default());
+        }
+
+        if self.brush.radius > 0 && self.paste.is_none() && self.tool == Tool::Brush {
+            let mut mb = graphics::MeshBuilder::new();
+            let center = self.screen_to_cell(self.mouse_pos.0, self.mouse_pos.1);
+            for cell in self.brush.cells_at(center) {
+                let rect = self.camera.cell_rect(cell);
+                mb.rectangle(DrawMode::fill(), rect, Color::new(1.0, 1.0, 1.0, 0.2))?;
+            }
+            let footprint = Mesh::from_data(ctx, mb.build());
+            canvas.draw(&footprint, DrawParam::default());
+        }
+
+        if let Some(cursor) = self.cursor {
+            let mut mb = graphics::MeshBuilder::new();
+            if let Some((min, max)) = self.selection() {
+                let rect = self.camera.region_rect(min, max);
+                mb.rectangle(DrawMode::fill(), rect, Color::new(0.2, 0.5, 1.0, 0.3))?;
+            }
+            let rect = self.camera.cell_rect(cursor);
+            mb.rectangle(DrawMode::stroke(2.0), rect, Color::YELLOW)?;
+            let overlay = Mesh::from_data(ctx, mb.build());
+            canvas.draw(&overlay, DrawParam::default());
+        }
+
+        if !self.clock {
+            let gen_text = Text::new(format!(
+                "Generation: {} ({:.0} gen/s, target {}){}{}{}{}",
+                self.grid.generation,
+                self.gens_per_sec,
+                match (self.gens_per_frame, self.frame_budget) {
+                    (Some(generations), _) => format!("{}/frame", generations),
+                    (None, Some(_)) => "max".to_string(),
+                    (None, None) => format!("{}", self.speed),
+                },
+                match self.journal.period() {
+                    Some(1) => " | Still life".to_string(),
+                    Some(period) => format!(" | Period {}", period),
+                    None => String::new(),
+                },
+                if self.playback.is_some() {
+                    " | Replaying"
+                } else {
+                    ""
+                },
+                if self.over_population {
+                    format!(
+                        " | Over {} cells, {} in use",
+                        self.max_population.unwrap_or_default(),
+                        self.memory_usage()
+                    )
+                } else {
+                    String::new()
+                },
+                self.simulation
+                    .as_ref()
+                    .and_then(|simulation| simulation.status())
+                    .map(|status| format!(" | {}", status))
+                    .unwrap_or_default()
+            ));
+            canvas.draw(&gen_text, DrawParam::default().dest([10.0, 10.0]));
+        }
+
+        if self.show_profile {
+            let profile_text = Text::new(self.profiler.summary());
+            canvas.draw(&profile_text, DrawParam::default().dest([10.0, 30.0]));
+        }
+
+        if self.show_graph {
+            self.draw_graph(ctx, canvas)?;
+        }
+
+        if let Some((generation, lines)) = &self.census {
+            let mut text = format!("Census at generation {}:", generation);
+            for line in lines {
+                text.push('\n');
+                text.push_str(line);
+            }
+            let census_text = Text::new(text);
+            let x = self.view_size(ctx).0 - GRAPH_WIDTH - 10.0;
+            let y = if self.show_graph {
+                GRAPH_HEIGHT + 50.0
+            } else {
+                40.0
+            };
+            canvas.draw(&census_text, DrawParam::default().dest([x, y]));
+        }
+
+        if let Some((target, from)) = self.fast_forward {
+            self.draw_progress(ctx, canvas, target, from)?;
+        }
+
+        if let Some(prompt) = &self.goto_prompt {
+            let mut prompt_text = Text::new(format!("Go to generation: {}_", prompt));
+            prompt_text.set_scale(28.0);
+            let (width, height) = self.view_size(ctx);
+            let dest = [(width - prompt_text.measure(ctx)?.x) / 2.0, height / 2.0];
+            canvas.draw(&prompt_text, DrawParam::default().dest(dest));
+        }
+
+        if self.catalog.is_some() {
+            self.draw_catalog(ctx, canvas)?;
+        }
+
+        if self.quit_prompt {
+            let action = if self.closing_tab {
+                "close the tab"
+            } else {
+                "quit"
+            };
+            let mut prompt_text = Text::new(format!(
+                "Unsaved changes. S: save to {} and {}, D: discard and {}, Esc: cancel",
+                self.save_file, action, action
+            ));
+            prompt_text.set_scale(24.0);
+            let (width, height) = self.view_size(ctx);
+            let dest = [(width - prompt_text.measure(ctx)?.x) / 2.0, height / 2.0];
+            canvas.draw(&prompt_text, DrawParam::default().dest(dest));
+        }
+
+        if let Some(demo) = &self.demo {
+            let mut caption = Text::new(demo.current().caption);
+            caption.set_scale(28.0);
+            let caption_width = caption.measure(ctx)?.x;
+            let (width, height) = self.view_size(ctx);
+            let dest = [(width - caption_width) / 2.0, height - 60.0];
+            canvas.draw(&caption, DrawParam::default().dest(dest));
+        }
+
+        if let Some(recorder) = &self.gif {
+            let rec_text = Text::new(format!("REC {}", recorder.frame_count()));
+            let (width, height) = self.view_size(ctx);
+            let dest = [width - 100.0, height - 30.0];
+            canvas.draw(&rec_text, DrawParam::default().dest(dest).color(Color::RED));
+        }
+
+        let (_, height) = self.view_size(ctx);
+        let feed = self.journal.feed();
+        if !feed.is_empty() {
+            let feed_text = Text::new(feed.join("\n"));
+            let y = height - 10.0 - 20.0 * feed.len() as f32;
+            canvas.draw(&feed_text, DrawParam::default().dest([10.0, y]));
+        }
+
+        Ok(())
+    }
+
+    /// The progress bar shown while jumping from generation `from` to `target`.
+    fn draw_progress(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        target: usize,
+        from: usize,
+    ) -> GameResult {
+        // A reverse step or a restore can leave the generation below where the jump started
+        let progress = self.grid.generation.saturating_sub(from) as f32
+            / target.saturating_sub(from).max(1) as f32;
+        let (width, height) = self.view_size(ctx);
+        let bounds = graphics::Rect::new(width / 2.0 - 200.0, height - 100.0, 400.0, 16.0);
+        let mut filled = bounds;
+        filled.w *= progress.clamp(0.0, 1.0);
+        let mut mb = graphics::MeshBuilder::new();
+        mb.rectangle(DrawMode::fill(), bounds, Color::new(0.0, 0.0, 0.0, 0.7))?;
+        mb.rectangle(DrawMode::fill(), filled, Color::GREEN)?;
+        mb.rectangle(DrawMode::stroke(1.0), bounds, Color::WHITE)?;
+        let mesh = Mesh::from_data(ctx, mb.build());
+        canvas.draw(&mesh, DrawParam::default());
+
+        let label = Text::new(format!(
+            "Going to generation {}: {:.0}% (Escape cancels)",
+            target,
+            progress * 100.0
+        ));
+        let dest = [bounds.x, bounds.y - 20.0];
+        canvas.draw(&label, DrawParam::default().dest(dest));
+        Ok(())
+    }
+
+    /// The pattern catalog over the whole view: a thumbnail, name and rule for each pattern.
+    fn draw_catalog(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        let (width, height) = self.view_size(ctx);
+        let Some(catalog) = &mut self.catalog else {
+            return Ok(());
+        };
+        let mut mb = graphics::MeshBuilder::new();
+        let backdrop = graphics::Rect::new(0.0, 0.0, width, height);
+        mb.rectangle(DrawMode::fill(), backdrop, Color::new(0.0, 0.0, 0.0, 0.85))?;
+        let visible = catalog.visible(width, height);
+        for &(index, [x, y]) in &visible {
+            let size = catalog::THUMBNAIL_SIZE as f32;
+            let frame = graphics::Rect::new(x - 1.0, y - 1.0, size + 2.0, size + 2.0);
+            let (color, thickness) = if index == catalog.selected {
+                (Color::YELLOW, 3.0)
+            } else {
+                (Color::new(0.4, 0.4, 0.4, 1.0), 1.0)
+            };
+            mb.rectangle(DrawMode::stroke(thickness), frame, color)?;
+        }
+        let mesh = Mesh::from_data(ctx, mb.build());
+        canvas.draw(&mesh, DrawParam::default());
+
+        let title = Text::new(format!(
+            "{} patterns in {} (arrows choose, Enter or click stamps, Escape closes)",
+            catalog.entries.len(),
+            catalog.dir.display()
+        ));
+        canvas.draw(&title, DrawParam::default().dest([20.0, 20.0]));
+        for (index, [x, y]) in visible {
+            let thumbnail = catalog.thumbnail(ctx, index);
+            canvas.draw(&thumbnail, DrawParam::default().dest([x, y]));
+            let entry = &catalog.entries[index];
+            let text_y = y + catalog::THUMBNAIL_SIZE as f32 + 6.0;
+            let name = Text::new(entry.label());
+            canvas.draw(&name, DrawParam::default().dest([x, text_y]));
+            let rules = Text::new(entry.rules.as_deref().unwrap_or("any rule"));
+            let grey = Color::new(0.6, 0.6, 0.6, 1.0);
+            let dest = [x, text_y + 18.0];
+            canvas.draw(&rules, DrawParam::default().dest(dest).color(grey));
+        }
+        Ok(())
+    }
+
+    /// The population chart in the top-right corner.
+    fn draw_graph(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        let (x, y) = (self.view_size(ctx).0 - GRAPH_WIDTH - 10.0, 40.0);
+        let bounds = graphics::Rect::new(x, y, GRAPH_WIDTH, GRAPH_HEIGHT);
+        let mut mb = graphics::MeshBuilder::new();
+        mb.rectangle(DrawMode::fill(), bounds, Color::new(0.0, 0.0, 0.0, 0.7))?;
+        let border = Color::new(0.4, 0.4, 0.4, 1.0);
+        mb.rectangle(DrawMode::stroke(1.0), bounds, border)?;
+        let populations = self
+            .simulation
+            .as_ref()
+            .map(|simulation| simulation.populations())
+            .unwrap_or_default();
+        // Simulations chart each of their populations in its own color
+        let series: Vec<Color> = if populations.is_empty() {
+            vec![Color::GREEN]
+        } else {
+            populations
+                .iter()
+                .map(|population| {
+                    let [r, g, b, a] = population.color;
+                    Color::from_rgba(r, g, b, a)
+                })
+                .collect()
+        };
+        for (index, color) in series.into_iter().enumerate() {
+            let points: Vec<[f32; 2]> = self
+                .population_graph
+                .points(index, GRAPH_WIDTH, GRAPH_HEIGHT)
+                .into_iter()
+                .map(|[px, py]| [x + px, y + py])
+                .collect();
+            if points.len() >= 2 {
+                mb.line(&points, 1.5, color)?;
+            }
+        }
+        let mesh = Mesh::from_data(ctx, mb.build());
+        canvas.draw(&mesh, DrawParam::default());
+
+        let label = Text::new(if populations.is_empty() {
+            format!(
+                "Population: {} (max {})",
+                self.grid.alive_cells.len(),
+                self.population_graph.max()
+            )
+        } else {
+            let counts: Vec<String> = populations
+                .iter()
+                .map(|population| format!("{}: {}", population.name, population.count))
+                .collect();
+            counts.join(", ")
+        });
+        canvas.draw(&label, DrawParam::default().dest([x + 5.0, y + 5.0]));
+        Ok(())
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        key_input: KeyInput,
+        _repeat: bool,
+    ) -> GameResult {
+        let (width, height) = self.view_size(ctx);
+        let shift = key_input.mods.contains(KeyMods::SHIFT);
+        let ctrl = key_input.mods.contains(KeyMods::CTRL);
+        if self.quit_prompt {
+            // The prompt takes the keyboard until it's answered
+            match key_input.keycode {
+                Some(KeyCode::S) => {
+                    let save_file = self.save_file.clone();
+                    self.save_to_file(&save_file);
+                    if !self.unsaved {
+                        self.quit_prompt = false;
+                        if !self.closing_tab {
+                            ctx.request_quit();
+                        }
+                    }
+                }
+                Some(KeyCode::D) => {
+                    self.unsaved = false;
+                    self.quit_prompt = false;
+                    if !self.closing_tab {
+                        ctx.request_quit();
+                    }
+                }
+                Some(KeyCode::Escape) => self.quit_prompt = false,
+                _ => {}
+            }
+            return Ok(());
+        }
+        if self.goto_prompt.is_some() {
+            // The prompt takes the keyboard; digits arrive through `text_input_event`
+            match key_input.keycode {
+                Some(KeyCode::Return | KeyCode::NumpadEnter) => self.start_fast_forward(),
+                Some(KeyCode::Back) => {
+                    if let Some(prompt) = &mut self.goto_prompt {
+                        prompt.pop();
+                    }
+                }
+                Some(KeyCode::Escape) => self.goto_prompt = None,
+                _ => {}
+            }
+            return Ok(());
+        }
+        if let Some(catalog) = &mut self.catalog {
+            // The catalog takes the keyboard while it's open
+            match key_input.keycode {
+                Some(KeyCode::Left) => catalog.move_selection(-1, 0, width, height),
+                Some(KeyCode::Right) => catalog.move_selection(1, 0, width, height),
+                Some(KeyCode::Up) => catalog.move_selection(0, -1, width, height),
+                Some(KeyCode::Down) => catalog.move_selection(0, 1, width, height),
+                Some(KeyCode::Return | KeyCode::NumpadEnter) => self.stamp_from_catalog(),
+                Some(KeyCode::Escape | KeyCode::A) => self.catalog = None,
+                _ => {}
+            }
+            return Ok(());
+        }
+        if let Some(keycode) = key_input.keycode {
+            match keycode {
+                KeyCode::Z if ctrl && shift => self.redo_edit(),
+                KeyCode::Z if ctrl => self.undo_edit(),
+                KeyCode::Y if ctrl => self.redo_edit(),
+                KeyCode::C if ctrl => self.copy_selection(),
+                KeyCode::V if ctrl => self.paste_from_clipboard(),
+                KeyCode::V => self.toggle_activity(),
+                KeyCode::Key1 => self.pick_stamp(0),
+                KeyCode::Key2 => self.pick_stamp(1),
+                KeyCode::Key3 => self.pick_stamp(2),
+                KeyCode::Key4 => self.pick_stamp(3),
+                KeyCode::Key5 => self.pick_stamp(4),
+                KeyCode::Period | KeyCode::Comma | KeyCode::M if self.paste.is_some() => {
+                    self.transform_paste(keycode, shift)
+                }
+                KeyCode::Space => {
+                    // Toggle the `running` state
+                    self.set_running(!self.running);
+                }
+                KeyCode::S => {
+                    // Save the current state to a file
+                    let save_file = self.save_file.clone();
+                    self.save_to_file(&save_file);
+                }
+                KeyCode::L => {
+                    // Clone the save file path to avoid immutable borrow conflicts
+                    let save_file = self.save_file.clone();
+                    self.load_from_file(&save_file);
+                }
+                KeyCode::P => {
+                    self.show_profile = !self.show_profile;
+                }
+                KeyCode::O => self.show_graph = !self.show_graph,
+                KeyCode::K => self.take_census(),
+                KeyCode::T => self.adjust_simulation(!shift),
+                KeyCode::U => self.reverse_simulation(),
+                KeyCode::Q => self.toggle_layer(shift as usize),
+                KeyCode::J => self.goto_prompt = Some(String::new()),
+                KeyCode::A => self.toggle_catalog(),
+                KeyCode::H if shift => self.toggle_envelope(),
+                KeyCode::H => {
+                    self.heat_map = !self.heat_map;
+                    self.refresh_live_counts();
+                }
+                KeyCode::N if !self.running => self.step(),
+                KeyCode::R => self.random_fill_view(width, height),
+                KeyCode::C if !ctrl => self.clear(),
+                KeyCode::I => self.reset(),
+                KeyCode::Equals | KeyCode::Plus | KeyCode::NumpadAdd => {
+                    self.set_speed(self.speed * 2.0)
+                }
+                KeyCode::Minus | KeyCode::NumpadSubtract => self.set_speed(self.speed / 2.0),
+                KeyCode::PageUp => self.adjust_turbo(true),
+                KeyCode::PageDown => self.adjust_turbo(false),
+                KeyCode::X => self.export_rle(&self.export_file),
+                KeyCode::G => self.toggle_gif_recording(width, height),
+                KeyCode::LBracket => self.adjust_brush(Brush::shrink),
+                KeyCode::RBracket => self.adjust_brush(Brush::grow),
+                KeyCode::B => self.adjust_brush(Brush::toggle_shape),
+                KeyCode::D => self.next_tool(),
+                KeyCode::E => self.toggle_cursor(width, height),
+                KeyCode::Up if self.cursor.is_some() => {
+                    self.move_cursor(0, -1, shift, width, height)
+                }
+                KeyCode::Down if self.cursor.is_some() => {
+                    self.move_cursor(0, 1, shift, width, height)
+                }
+                KeyCode::Left if self.cursor.is_some() => {
+                    self.move_cursor(-1, 0, shift, width, height)
+                }
+                KeyCode::Right if self.cursor.is_some() => {
+                    self.move_cursor(1, 0, shift, width, height)
+                }
+                KeyCode::Up => self.camera.pan(0.0, PAN_STEP),
+                KeyCode::Down => self.camera.pan(0.0, -PAN_STEP),
+                KeyCode::Left => self.camera.pan(PAN_STEP, 0.0),
+                KeyCode::Right => self.camera.pan(-PAN_STEP, 0.0),
+                KeyCode::F => self.fit_to_pattern(width, height, FIT_FILL),
+                KeyCode::Return | KeyCode::NumpadEnter => {
+                    if self.paste.is_some() {
+                        self.place_paste();
+                    } else if shift && self.selection().is_some() {
+                        self.fill_selection(true);
+                    } else if let Some(cursor) = self.cursor {
+                        self.toggle_grid_cell(cursor);
+                    }
+                }
+                KeyCode::Delete | KeyCode::Back => self.fill_selection(false),
+                KeyCode::Escape => {
+                    if self.fast_forward.take().is_some() {
+                        self.status(&format!("Stopped at generation {}", self.grid.generation));
+                    }
+                    if self.playback.take().is_some() {
+                        self.status(&format!(
+                            "Stopped the replay at generation {}",
+                            self.grid.generation
+                        ));
+                    }
+                    self.paste = None;
+                    self.selection_anchor = None;
+                    self.shape_start = None;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn text_input_event(&mut self, character: char) {
+        if let Some(prompt) = &mut self.goto_prompt {
+            if character.is_ascii_digit() {
+                prompt.push(character);
+            }
+        }
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut Context,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) -> GameResult {
+        let (width, height) = self.view_size(ctx);
+        if let Some(catalog) = &mut self.catalog {
+            // Clicking a card stamps it, and clicking anywhere else leaves the catalog open
+            if let Some(index) = catalog.entry_at(x, y, width, height) {
+                catalog.selected = index;
+                self.stamp_from_catalog();
+            }
+            return Ok(());
+        }
+        if button == MouseButton::Left {
+            self.dragging = true;
+            self.drag_start = Some((x, y));
+        } else if button == MouseButton::Right {
+            if self.paste.is_some() {
+                self.place_paste();
+            } else if self.simulation.is_some() && ctx.keyboard.is_mod_active(KeyMods::SHIFT) {
+                self.activate(self.screen_to_cell(x, y));
+            } else if self.tool == Tool::Brush {
+                self.start_stroke(x, y);
+            } else {
+                self.shape_start = Some(self.screen_to_cell(x, y));
+            }
+        }
+        Ok(())
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut Context,
+        button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) -> GameResult {
+        if button == MouseButton::Left {
+            self.dragging = false;
+            self.drag_start = None;
+        } else if button == MouseButton::Right {
+            if self.shape_start.is_some() {
+                self.place_shape();
+            }
+            self.end_stroke();
+        }
+        Ok(())
+    }
+
+    fn mouse_motion_event(
+        &mut self,
+        _ctx: &mut Context,
+        x: f32,
+        y: f32,
+        dx: f32,
+        dy: f32,
+    ) -> GameResult {
+        self.mouse_pos = (x, y);
+        if self.dragging {
+            self.camera.pan(dx, dy);
+        }
+        if self.stroke.is_some() {
+            self.paint_to(self.screen_to_cell(x, y));
+        }
+        Ok(())
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, _x: f32, y: f32) -> GameResult {
+        let (width, height) = self.view_size(ctx);
+        if let Some(catalog) = &mut self.catalog {
+            // Scrolls the catalog a row at a time rather than zooming
+            if y != 0.0 {
+                let rows = if y > 0.0 { -1 } else { 1 };
+                catalog.move_selection(0, rows, width, height);
+            }
+            return Ok(());
+        }
+        // Zoom around the mouse, so whatever is under it stays put
+        let zoom_factor = 0.1;
+        let (mouse_x, mouse_y) = self.mouse_pos;
+        if y > 0.0 {
+            self.camera.zoom_at(1.0 + zoom_factor, mouse_x, mouse_y);
+        } else if y < 0.0 {
+            self.camera.zoom_at(1.0 - zoom_factor, mouse_x, mouse_y);
+        }
+        Ok(())
+    }
+
+    fn quit_event(&mut self, _ctx: &mut Context) -> GameResult<bool> {
+        if self.print_profile {
+            println!("{}", self.profiler.summary());
         }
+        self.finish_recordings();
+        Ok(false)
     }
 }
 
-impl EventHandler for Celleste {
-    fn update(&mut self, _ctx: &mut Context) -> GameResult {
-        if self.running {
-            self.step();
+/// Several independent universes in one window; only the active tab runs and receives input.
+/// When comparing rules, the first two are shown side by side instead and both take every
+/// input, so they step, pan and get edited together.
+struct Tabs {
+    tabs: Vec<Celleste>,
+    active: usize,
+    /// Commands from `--listen` clients, run against the active tab
+    remote: Option<RemoteServer>,
+    /// Split-screen comparison of the two tabs, from `--compare`
+    compare: bool,
+    /// Size of the window the views were last laid out for
+    window: (f32, f32),
+}
+
+impl Tabs {
+    fn new(first: Celleste) -> Self {
+        Self {
+            tabs: vec![first],
+            active: 0,
+            remote: None,
+            compare: false,
+            window: (0.0, 0.0),
+        }
+    }
+
+    /// The same pattern under two rules, side by side.
+    fn compare(mut left: Celleste, mut right: Celleste) -> Self {
+        left.split_view = true;
+        right.split_view = true;
+        let mut tabs = Self {
+            tabs: vec![left],
+            active: 0,
+            remote: None,
+            compare: true,
+            window: (0.0, 0.0),
+        };
+        right.save_file = tabs.unused_save_file(&right.save_file);
+        tabs.tabs.push(right);
+        tabs
+    }
+
+    fn current(&mut self) -> &mut Celleste {
+        &mut self.tabs[self.active]
+    }
+
+    /// The universes input goes to: both sides when comparing, otherwise the active tab.
+    fn receivers(&mut self) -> &mut [Celleste] {
+        if self.compare {
+            &mut self.tabs
+        } else {
+            std::slice::from_mut(&mut self.tabs[self.active])
+        }
+    }
+
+    /// `x` relative to the side of the window it's in when comparing.
+    fn pane_x(&self, ctx: &Context, x: f32) -> f32 {
+        let half = ctx.gfx.drawable_size().0 / 2.0;
+        if self.compare && x >= half {
+            x - half
+        } else {
+            x
+        }
+    }
+
+    /// Keeps the compared universes in step: the one behind catches up, and when either
+    /// pauses (e.g. on a period) both do.
+    fn synchronize(&mut self) {
+        let running = self.tabs.iter().all(|tab| tab.running);
+        let generation = self.tabs.iter().map(|tab| tab.grid.generation).max();
+        for tab in &mut self.tabs {
+            tab.running = running;
+            while generation.is_some_and(|generation| tab.grid.generation < generation) {
+                let before = tab.grid.generation;
+                tab.step();
+                // A universe that's stopped (e.g. a finished battle) would never catch up
+                if tab.grid.generation == before {
+                    break;
+                }
+            }
+        }
+    }
+
+    fn open_tab(&mut self) {
+        let mut tab = self.tabs[self.active].blank_like();
+        tab.save_file = self.unused_save_file(&tab.save_file);
+        self.tabs.push(tab);
+        self.active = self.tabs.len() - 1;
+    }
+
+    /// `path` numbered (`celleste_save_2.json`, `_3`, ...) so no other tab saves to it, since
+    /// saving one tab over another's file would lose it.
+    fn unused_save_file(&self, path: &str) -> String {
+        let path = Path::new(path);
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let extension = path
+            .extension()
+            .map(|extension| format!(".{}", extension.to_string_lossy()))
+            .unwrap_or_default();
+        (2..)
+            .map(|n| {
+                path.with_file_name(format!("{}_{}{}", stem, n, extension))
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .find(|numbered| self.tabs.iter().all(|tab| tab.save_file != *numbered))
+            .unwrap_or_default()
+    }
+
+    /// Closes the active tab, first asking whether to save it if it has unsaved changes.
+    fn close_tab(&mut self) {
+        if self.tabs.len() < 2 {
+            return;
+        }
+        let tab = &mut self.tabs[self.active];
+        if tab.unsaved {
+            tab.quit_prompt = true;
+            tab.closing_tab = true;
+            return;
+        }
+        let mut closed = self.tabs.remove(self.active);
+        closed.finish_recordings();
+        self.active = self.active.min(self.tabs.len() - 1);
+    }
+
+    fn cycle(&mut self, backwards: bool) {
+        let count = self.tabs.len();
+        self.active = if backwards {
+            (self.active + count - 1) % count
+        } else {
+            (self.active + 1) % count
+        };
+    }
+
+    /// Draws each compared universe into its half of the window, labelled with its rules.
+    fn draw_split(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        let (width, height) = ctx.gfx.drawable_size();
+        let half = width / 2.0;
+        for (i, tab) in self.tabs.iter_mut().enumerate() {
+            let left = i as f32 * half;
+            canvas.set_scissor_rect(graphics::Rect::new(left, 0.0, half, height))?;
+            canvas.set_screen_coordinates(graphics::Rect::new(-left, 0.0, width, height));
+            tab.draw(ctx, canvas)?;
+            let label = Text::new(format!("Rules: {}", tab.grid.rules.as_string()));
+            let dest = [half - label.measure(ctx)?.x - 10.0, 10.0];
+            canvas.draw(&label, DrawParam::default().dest(dest).color(Color::YELLOW));
+        }
+        canvas.set_default_scissor_rect();
+        canvas.set_screen_coordinates(graphics::Rect::new(0.0, 0.0, width, height));
+        let divider = Mesh::new_line(
+            ctx,
+            &[[half, 0.0], [half, height]],
+            2.0,
+            Color::new(0.4, 0.4, 0.4, 1.0),
+        )?;
+        canvas.draw(&divider, DrawParam::default());
+        Ok(())
+    }
+}
+
+impl EventHandler for Tabs {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        if let Some(remote) = &self.remote {
+            let tab = &mut self.tabs[self.active];
+            for request in remote.pending() {
+                request.answer(|command| tab.run_remote_command(command));
+            }
+        }
+        for tab in self.receivers() {
+            tab.update(ctx)?;
+        }
+        if self.compare {
+            self.synchronize();
         }
         Ok(())
     }
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
         let mut canvas = Canvas::from_frame(ctx, Color::BLACK);
-        let mut mb = graphics::MeshBuilder::new();
-
-        for &cell in &self.alive_cells {
-            let rect = graphics::Rect::new(
-                (cell.0 as f32 * self.cell_size) + self.offset_x,
-                (cell.1 as f32 * self.cell_size) + self.offset_y,
-                self.cell_size,
-                self.cell_size,
-            );
-            mb.rectangle(DrawMode::fill(), rect, Color::WHITE)?;
-        }
-        
-        let mesh_data = mb.build();
-        let mesh = Mesh::from_data(ctx, mesh_data);
-        canvas.draw(&mesh, DrawParam::default());
+        if self.compare {
+            self.draw_split(ctx, &mut canvas)?;
+        } else {
+            let tab_count = self.tabs.len();
+            let active = self.active;
+            self.current().draw(ctx, &mut canvas)?;
 
-        if !self.clock {
-            let gen_text = Text::new(format!("Generation: {}", self.generation));
-            canvas.draw(&gen_text, DrawParam::default().dest([10.0, 10.0]));
+            if tab_count > 1 {
+                let tab_text = Text::new(format!("Tab {}/{}", active + 1, tab_count));
+                let x = ctx.gfx.drawable_size().0 - 100.0;
+                canvas.draw(&tab_text, DrawParam::default().dest([x, 10.0]));
+            }
         }
 
-        canvas.finish(ctx)
+        let start = Instant::now();
+        let result = canvas.finish(ctx);
+        self.current().profiler.record(Phase::Draw, start.elapsed());
+        result
     }
 
     fn key_down_event(
         &mut self,
-        _ctx: &mut Context,
+        ctx: &mut Context,
         key_input: KeyInput,
-        _repeat: bool,
+        repeat: bool,
     ) -> GameResult {
-        if let Some(keycode) = key_input.keycode {
-            match keycode {
-                KeyCode::Space => {
-                    // Toggle the `running` state
-                    self.running = !self.running;
+        // Only the tab asking about its unsaved changes hears the answer
+        if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.quit_prompt) {
+            tab.key_down_event(ctx, key_input, repeat)?;
+            if tab.closing_tab && !tab.quit_prompt {
+                // Saved or discarded closes the tab; cancelled keeps it
+                tab.closing_tab = false;
+                if !tab.unsaved {
+                    self.close_tab();
                 }
-                KeyCode::S => {
-                    // Save the current state to a file
-                    self.save_to_file(&self.save_file);
+            }
+            return Ok(());
+        }
+        // Tabs stay put while comparing
+        if key_input.mods.contains(KeyMods::CTRL) && !self.compare {
+            match key_input.keycode {
+                Some(KeyCode::T) => {
+                    self.open_tab();
+                    return Ok(());
                 }
-                KeyCode::L => {
-                    // Clone the save file path to avoid immutable borrow conflicts
-                    let save_file = self.save_file.clone();
-                    self.load_from_file(&save_file);
+                Some(KeyCode::W) => {
+                    self.close_tab();
+                    return Ok(());
+                }
+                Some(KeyCode::Tab) => {
+                    self.cycle(key_input.mods.contains(KeyMods::SHIFT));
+                    return Ok(());
                 }
                 _ => {}
             }
         }
+        for tab in self.receivers() {
+            tab.key_down_event(ctx, key_input, repeat)?;
+        }
+        Ok(())
+    }
+
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) -> GameResult {
+        for tab in self.receivers() {
+            tab.text_input_event(character);
+        }
         Ok(())
     }
 
     fn mouse_button_down_event(
         &mut self,
-        _ctx: &mut Context,
+        ctx: &mut Context,
         button: MouseButton,
         x: f32,
         y: f32,
     ) -> GameResult {
-        if button == MouseButton::Left {
-            self.dragging = true;
-            self.drag_start = Some((x, y));
-        } else if button == MouseButton::Right {
-            self.toggle_cell(x, y);
+        let x = self.pane_x(ctx, x);
+        for tab in self.receivers() {
+            tab.mouse_button_down_event(ctx, button, x, y)?;
         }
         Ok(())
     }
 
     fn mouse_button_up_event(
         &mut self,
-        _ctx: &mut Context,
+        ctx: &mut Context,
         button: MouseButton,
-        _x: f32,
-        _y: f32,
+        x: f32,
+        y: f32,
     ) -> GameResult {
-        if button == MouseButton::Left {
-            self.dragging = false;
-            self.drag_start = None;
+        let x = self.pane_x(ctx, x);
+        for tab in self.receivers() {
+            tab.mouse_button_up_event(ctx, button, x, y)?;
         }
         Ok(())
     }
 
     fn mouse_motion_event(
         &mut self,
-        _ctx: &mut Context,
-        _x: f32,
-        _y: f32,
+        ctx: &mut Context,
+        x: f32,
+        y: f32,
         dx: f32,
         dy: f32,
     ) -> GameResult {
-        if self.dragging {
-            self.offset_x += dx;
-            self.offset_y += dy;
+        let x = self.pane_x(ctx, x);
+        for tab in self.receivers() {
+            tab.mouse_motion_event(ctx, x, y, dx, dy)?;
         }
         Ok(())
     }
 
-    fn mouse_wheel_event(&mut self, _ctx: &mut Context, _x: f32, y: f32) -> GameResult {
-        let zoom_factor = 0.1;
-        if y > 0.0 {
-            self.cell_size *= 1.0 + zoom_factor;
-        } else if y < 0.0 {
-            self.cell_size *= 1.0 - zoom_factor;
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, x: f32, y: f32) -> GameResult {
+        for tab in self.receivers() {
+            tab.mouse_wheel_event(ctx, x, y)?;
+        }
+        Ok(())
+    }
+
+    /// Keeps whatever was in the middle of each view there as the window changes size.
+    fn resize_event(&mut self, _ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        let (old_width, old_height) = self.window;
+        self.window = (width, height);
+        let panes = if self.compare { 2.0 } else { 1.0 };
+        for tab in &mut self.tabs {
+            tab.camera.pan(
+                (width - old_width) / panes / 2.0,
+                (height - old_height) / 2.0,
+            );
+        }
+        Ok(())
+    }
+
+    /// Asks about the first tab with unsaved changes before closing, keeping the window open
+    /// until the question is answered; answering it asks again, so every tab gets a say.
+    fn quit_event(&mut self, ctx: &mut Context) -> GameResult<bool> {
+        if let Some(unsaved) = self.tabs.iter().position(|tab| tab.unsaved) {
+            if !self.compare {
+                self.active = unsaved;
+            }
+            self.tabs[unsaved].quit_prompt = true;
+            self.tabs[unsaved].closing_tab = false;
+            return Ok(true);
+        }
+        for tab in &mut self.tabs {
+            tab.quit_event(ctx)?;
+        }
+        Ok(false)
+    }
+}
+
+impl WindowEvents for Tabs {
+    fn file_dropped_event(&mut self, ctx: &mut Context, path: PathBuf) -> GameResult {
+        for tab in self.receivers() {
+            let (width, height) = tab.view_size(ctx);
+            tab.load_dropped_file(&path, width, height);
         }
         Ok(())
     }
 }
 
+/// Parses a `WIDTHxHEIGHT` size such as `1280x720` given to `flag`. Sizes fit an `i32`, so
+/// they can be used as cell counts too.
+fn parse_dimensions(flag: &str, size: &str) -> Result<(u32, u32), String> {
+    let invalid = || {
+        format!(
+            "Invalid {} '{}'. Expected 'WIDTHxHEIGHT' with sizes from 1 to {}.",
+            flag,
+            size,
+            i32::MAX
+        )
+    };
+    let (width, height) = size.split_once(['x', 'X']).ok_or_else(invalid)?;
+    let parse = |side: &str| {
+        side.trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|&side| side > 0 && side <= i32::MAX as u32)
+    };
+    match (parse(width), parse(height)) {
+        (Some(width), Some(height)) => Ok((width, height)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Each `--pattern` with the `--at` that follows it, if one comes before the next `--pattern`.
+fn pattern_placements(matches: &ArgMatches) -> Result<Vec<(String, Option<Cell>)>, String> {
+    let patterns = matches
+        .get_many::<String>("pattern")
+        .into_iter()
+        .flatten()
+        .zip(matches.indices_of("pattern").into_iter().flatten());
+    let mut placements: Vec<(String, usize, Option<Cell>)> = patterns
+        .map(|(spec, index)| (spec.clone(), index, None))
+        .collect();
+    let ats = matches
+        .get_many::<String>("at")
+        .into_iter()
+        .flatten()
+        .zip(matches.indices_of("at").into_iter().flatten());
+    for (at, index) in ats {
+        let placement = placements
+            .iter_mut()
+            .rev()
+            .find(|(_, pattern_index, _)| *pattern_index < index)
+            .ok_or_else(|| format!("--at {} must come after the --pattern it places", at))?;
+        if placement.2.is_some() {
+            return Err(format!("--pattern {} has more than one --at", placement.0));
+        }
+        placement.2 = Some(Cell::from_string(at)?);
+    }
+    Ok(placements
+        .into_iter()
+        .map(|(spec, _, at)| (spec, at))
+        .collect())
+}
+
+/// Headless mode: runs the simulation and writes each generation to stdout as a y4m frame.
+fn stream_y4m(mut game: Celleste, frames: Option<usize>, size: (u32, u32)) -> GameResult {
+    let (width, height) = size;
+    let (view_width, view_height) = (width as f32, height as f32);
+    let out = BufWriter::new(io::stdout().lock());
+    let mut writer = Y4mWriter::new(out, width, height, STREAM_FPS)?;
+    let mut written = 0;
+    while frames.is_none_or(|limit| written < limit) {
+        match writer.write_frame(&game.render_frame(width, height)) {
+            Ok(()) => written += 1,
+            // The reader went away (e.g. mpv was closed), which ends the stream normally
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => break,
+            Err(err) => return Err(err.into()),
+        }
+        game.tick_demo(view_width, view_height);
+        let following = game.following_row(view_height);
+        game.advance(Duration::from_secs(1) / STREAM_FPS);
+        if following {
+            game.scroll_to_row(view_height);
+        }
+        game.tick_autosave();
+    }
+    if let Some(history) = game.history.take() {
+        if let Err(err) = history.finish() {
+            eprintln!("Failed to finish history recording: {}", err);
+        }
+    }
+    if let Some(video) = game.video.take() {
+        game.finish_video(video);
+    }
+    eprintln!("Streamed {} frames", written);
+    Ok(())
+}
+
 // B12356/S12356
 fn main() -> GameResult {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+
+    if let Some(Command::Render {
+        dir,
+        out,
+        size,
+        gif,
+    }) = cli.command
+    {
+        let options = GalleryOptions {
+            out_dir: out,
+            size,
+            gif_generations: gif,
+        };
+        if let Err(err) = gallery::render_gallery(&dir, &options) {
+            eprintln!("Error rendering gallery: {}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::Search {
+        soups,
+        size,
+        rule,
+        out,
+        max_generations,
+        seed,
+    }) = cli.command
+    {
+        let (width, height) = parse_dimensions("--size", &size).unwrap_or_else(|err| {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        });
+        let options = SearchOptions {
+            soups,
+            width: width as i32,
+            height: height as i32,
+            rules: rule,
+            out_dir: out,
+            max_generations,
+            seed: seed.unwrap_or_else(|| rand::thread_rng().gen()),
+        };
+        if let Err(err) = search::run_search(&options) {
+            eprintln!("Error running search: {}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if cli.list_rules {
+        println!("{}", celleste_core::rules::rule_catalog());
+        return Ok(());
+    }
 
-    let rules = Rules::from_string(&cli.rules).unwrap_or_else(|err| {
+    if cli.list_models {
+        for registration in simulation::SIMULATIONS {
+            println!("{:<12} {}", registration.name, registration.description);
+            for (name, description) in registration.params {
+                println!("{:<12}   {}: {}", "", name, description);
+            }
+        }
+        for (name, description) in [
+            ("elementary", "A 1D automaton, one row per generation"),
+            ("ant", "Langton's ant and other turn-string turmites"),
+            ("turmite", "A turmite from a Golly-style transition table"),
+        ] {
+            println!("{:<12} {}", name, description);
+        }
+        return Ok(());
+    }
+
+    let mut rules = Rules::from_string(&cli.rules).unwrap_or_else(|err| {
         eprintln!("Error parsing rules: {}", err);
         std::process::exit(1);
     });
+    if let Some(name) = &cli.neighborhood {
+        rules = Neighborhood::from_string(name)
+            .and_then(|neighborhood| rules.with_neighborhood(neighborhood))
+            .unwrap_or_else(|err| {
+                eprintln!("Error parsing neighborhood: {}", err);
+                std::process::exit(1);
+            });
+    }
+
+    if cli.compare.is_some() && cli.model != "life" {
+        eprintln!("Error: --compare only works with --model life");
+        std::process::exit(1);
+    }
+
+    let torus = cli.torus.as_ref().map(|size| {
+        let (width, height) = parse_dimensions("--torus", size).unwrap_or_else(|err| {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        });
+        Torus {
+            width: width as i32,
+            height: height as i32,
+        }
+    });
+    let registered = simulation::find(&cli.model);
+    // Other models have edges of their own or none, and would ignore the torus
+    if torus.is_some() && registered.map(|registration| registration.name) != Some("life") {
+        eprintln!("Error: --torus only works with --model life");
+        std::process::exit(1);
+    }
+    let simulation = registered.map(|registration| {
+        let (width, height) = parse_dimensions("--size", &cli.size).unwrap_or_else(|err| {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        });
+        let mut params = HashMap::new();
+        for param in &cli.param {
+            let Some((name, value)) = param.split_once('=') else {
+                eprintln!("Error parsing parameter '{}': expected NAME=VALUE", param);
+                std::process::exit(1);
+            };
+            params.insert(name.trim().to_string(), value.trim().to_string());
+        }
+        let options = simulation::Options {
+            width: width as i32,
+            height: height as i32,
+            rules: rules.clone(),
+            torus,
+            params,
+            seed: cli.seed.unwrap_or_else(|| rand::thread_rng().gen()),
+        };
+        simulation::create(registration.name, &options).unwrap_or_else(|err| {
+            eprintln!("Error creating simulation: {}", err);
+            std::process::exit(1);
+        })
+    });
+    // Life comes back as a bare grid, which runs as the universe's own grid so it gets the
+    // GPU stepping, analysis and recording built around it
+    let life = simulation
+        .as_ref()
+        .and_then(|simulation| simulation.grid().cloned());
+    let simulation = simulation.filter(|simulation| simulation.grid().is_none());
+    let turmites = (cli.model != "elementary" && registered.is_none()).then(|| {
+        let spec = cli.spec.as_deref().unwrap_or("RL");
+        Turmites::from_spec(&cli.model, spec).unwrap_or_else(|err| {
+            eprintln!("Error parsing model: {}", err);
+            std::process::exit(1);
+        })
+    });
+    let window = parse_dimensions("--window", &cli.window).unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    });
+    let (window_width, window_height) = (window.0 as f32, window.1 as f32);
+    let cell_size = cli.cell_size.unwrap_or(DEFAULT_CELL_SIZE);
+    if !cell_size.is_finite() || cell_size <= 0.0 {
+        eprintln!("Error: --cell-size must be above 0");
+        std::process::exit(1);
+    }
+    let center = cli.center.map(|center| {
+        Cell::from_string(&center).unwrap_or_else(|err| {
+            eprintln!("Error parsing center: {}", err);
+            std::process::exit(1);
+        })
+    });
+    let elementary = (cli.model == "elementary")
+        .then(|| Elementary::new(cli.rule, (window_width / cell_size) as i32));
 
-    let cb = ContextBuilder::new("Celleste", "alskdfjsaodjkf")
-        .window_setup(ggez::conf::WindowSetup::default().title("Celleste"))
-        .window_mode(ggez::conf::WindowMode::default().dimensions(1600.0, 1200.0));
-    let (ctx, event_loop) = cb.build()?;
-    
     // Default initial state
-    let initial_state = vec![
-        Cell(50, 50),
-        Cell(50, 51),
-        Cell(50, 52),
-        Cell(49, 50),
-        Cell(51, 51),
-    ];
+    let initial_state = if let Some(elementary) = elementary {
+        vec![elementary.seed()]
+    } else if turmites.is_some() || simulation.is_some() {
+        Vec::new()
+    } else {
+        vec![
+            Cell(50, 50),
+            Cell(50, 51),
+            Cell(50, 52),
+            Cell(49, 50),
+            Cell(51, 51),
+        ]
+    };
 
-    let mut game = Celleste::new(initial_state.clone(), 10.0, rules, cli.no_clock);
+    let mut game = Celleste::new(initial_state.clone(), cell_size, rules, cli.no_clock);
+    if let Some(mut turmites) = turmites {
+        let center = game.screen_to_cell(window_width / 2.0, window_height / 2.0);
+        turmites.place_agents(center, cli.agents);
+        game.turmites = Some(turmites);
+    }
+    game.elementary = elementary;
+    if let Some(simulation) = simulation {
+        // Charting is half the point of simulations with populations
+        game.show_graph = !simulation.populations().is_empty();
+        game.simulation = Some(simulation);
+        game.fit_to_pattern(window_width, window_height, FIT_FILL);
+        game.mark_initial();
+    }
+    if let Some(mut grid) = life {
+        grid.alive_cells = std::mem::take(&mut game.grid.alive_cells);
+        game.use_gpu = cli.gpu && grid.torus.is_some();
+        game.grid = grid;
+        game.wrap_into_torus();
+    }
 
     // Set the save file from the CLI argument
     game.set_save_file(cli.save_file);
+    if let Some(export_file) = cli.export_rle {
+        game.export_file = export_file;
+    }
+    if let Some(origin) = cli.origin {
+        game.paste_origin = Cell::from_string(&origin).unwrap_or_else(|err| {
+            eprintln!("Error parsing origin: {}", err);
+            std::process::exit(1);
+        });
+    }
+    game.catalog_dir = cli.patterns_dir;
+    game.print_profile = cli.profile;
+    game.set_speed(cli.speed);
+    game.status_to_stderr = cli.stream_y4m;
+    if let Some(path) = cli.rule_table {
+        match RuleTable::load(&path) {
+            Ok(table) => {
+                game.status(&format!("Running rule table {}", table.name));
+                game.rule_table = Some(table);
+            }
+            Err(err) => {
+                eprintln!("Error loading rule table: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+    game.pause_on_period = cli.pause_on_period;
+    game.auto_pause = cli.auto_pause;
+    if let Some(journal_file) = cli.journal {
+        if let Err(err) = game.journal.open_file(&journal_file) {
+            eprintln!("Failed to open journal: {}", err);
+            std::process::exit(1);
+        }
+    }
+    if let Some(budget) = cli.budget {
+        game.set_frame_budget(budget);
+    }
+    game.gens_per_frame = cli
+        .gens_per_frame
+        .map(|generations| generations.clamp(1, MAX_GENS_PER_FRAME));
+    game.max_population = Some(cli.max_population).filter(|&cells| cells > 0);
+    game.trails = cli
+        .trails
+        .filter(|&generations| generations > 0)
+        .map(|generations| Trails::new(generations, &game.grid.alive_cells));
 
     // Load from the provided file if specified
     if let Some(load_file) = cli.load_file {
         game.load_from_file(&load_file);
+    } else if !cli.pattern.is_empty() {
+        let cache_dir = cli.pattern_cache.unwrap_or_else(fetch::default_cache_dir);
+        let placements = pattern_placements(&matches).unwrap_or_else(|err| {
+            eprintln!("Error placing patterns: {}", err);
+            std::process::exit(1);
+        });
+        let resolve = |spec: &str| {
+            fetch::resolve(spec, &cache_dir).unwrap_or_else(|err| {
+                eprintln!("Error loading pattern: {}", err);
+                std::process::exit(1);
+            })
+        };
+        if let [(spec, None)] = placements.as_slice() {
+            // A lone pattern loads like any other file
+            game.load_from_file(&resolve(spec).to_string_lossy());
+        } else {
+            game.grid.alive_cells.clear();
+            game.grid.dying_cells.clear();
+            for (spec, at) in &placements {
+                let path = resolve(spec);
+                let origin = at.unwrap_or(game.paste_origin);
+                if let Err(err) = game.stamp_pattern_file(&path.to_string_lossy(), origin) {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            }
+        }
     } else {
-        println!("No load file provided. Using default");
+        game.status("No load file provided. Using default");
+    }
+
+    // The view asked for wins over one a save or simulation brought along, and comes before
+    // the soup so it fills what will be on screen
+    if cli.cell_size.is_some() {
+        let factor = cell_size / game.camera.cell_size;
+        game.camera
+            .zoom_at(factor, window_width / 2.0, window_height / 2.0);
+    }
+    if let Some(center) = center {
+        game.camera.center_on(center, window_width, window_height);
+    }
+    if cli.paused {
+        game.running = false;
+    }
+
+    if let Some(history_file) = cli.record_history {
+        let region = cli.history_region.map(|region| {
+            Region::from_string(&region).unwrap_or_else(|err| {
+                eprintln!("Error parsing history region: {}", err);
+                std::process::exit(1);
+            })
+        });
+        if let Err(err) = game.start_history(&history_file, region) {
+            eprintln!("Failed to start history recording: {}", err);
+            std::process::exit(1);
+        }
+    }
+
+    if !(cli.gif_scale > 0.0 && cli.gif_scale <= MAX_GIF_SCALE) {
+        eprintln!(
+            "Error: --gif-scale must be above 0 and at most {}",
+            MAX_GIF_SCALE
+        );
+        std::process::exit(1);
+    }
+    game.gif_options = GifOptions {
+        path: cli.gif_path,
+        every: cli.gif_every.max(1),
+        scale: cli.gif_scale,
+        delay_ms: cli.gif_delay,
+        max_frames: cli.gif_max_frames.max(1),
+    };
+
+    if let Some(seed) = cli.seed {
+        game.rng = StdRng::seed_from_u64(seed);
+    }
+    if let Some(density) = cli.random {
+        game.soup_density = density.clamp(0.0, 1.0);
+        match cli.random_region {
+            Some(region) => {
+                let region = Region::from_string(&region).unwrap_or_else(|err| {
+                    eprintln!("Error parsing random region: {}", err);
+                    std::process::exit(1);
+                });
+                let (min, max) = region.corners();
+                game.random_fill(min, max);
+            }
+            None => game.random_fill_view(window_width, window_height),
+        }
+    }
+
+    if let Some(seconds) = cli.demo {
+        game.start_demo(Duration::from_secs(seconds), window_width, window_height);
+    }
+
+    game.mark_initial();
+    // Whatever the command line set up doesn't need saving before quitting
+    game.unsaved = false;
+
+    if let Some(path) = cli.replay {
+        if let Err(err) = game.start_playback(&path) {
+            eprintln!("Error loading replay: {}", err);
+            std::process::exit(1);
+        }
+    }
+    if let Some(path) = cli.record_session {
+        if let Err(err) = game.start_session(&path) {
+            eprintln!("Failed to start session recording: {}", err);
+            std::process::exit(1);
+        }
+    }
+
+    if let Some(interval) = cli.autosave {
+        game.autosave = Some(
+            Autosave::from_string(&interval, cli.autosave_keep).unwrap_or_else(|err| {
+                eprintln!("Error parsing autosave interval: {}", err);
+                std::process::exit(1);
+            }),
+        );
+    }
+
+    if let Some(path) = cli.record_video {
+        let (width, height) = window;
+        if let Err(err) = game.start_video(&path, width, height, cli.video_fps) {
+            eprintln!("Failed to start video recording: {}", err);
+            std::process::exit(1);
+        }
+    }
+
+    if cli.stream_y4m {
+        return stream_y4m(game, cli.frames, window);
+    }
+
+    let cb = ContextBuilder::new("Celleste", "alskdfjsaodjkf")
+        .window_setup(ggez::conf::WindowSetup::default().title("Celleste"))
+        .window_mode(
+            ggez::conf::WindowMode::default()
+                .dimensions(window_width, window_height)
+                .resizable(true)
+                .fullscreen_type(if cli.fullscreen {
+                    ggez::conf::FullscreenType::Desktop
+                } else {
+                    ggez::conf::FullscreenType::Windowed
+                }),
+        );
+    let (mut ctx, event_loop) = cb.build()?;
+
+    let mut tabs = match cli.compare {
+        Some(rules) => {
+            let rules = Rules::from_string(&rules).unwrap_or_else(|err| {
+                eprintln!("Error parsing comparison rules: {}", err);
+                std::process::exit(1);
+            });
+            let other = game.compare_with(rules);
+            Tabs::compare(game, other)
+        }
+        None => Tabs::new(game),
+    };
+    if let Some(address) = cli.listen {
+        let server = RemoteServer::bind(&address).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        });
+        tabs.remote = Some(server);
+        println!("Listening for remote commands on {}", address);
     }
+    // Fullscreen and high-DPI windows don't come out the size asked for
+    tabs.window = (window_width, window_height);
+    let (width, height) = ctx.gfx.drawable_size();
+    tabs.resize_event(&mut ctx, width, height)?;
 
-    event::run(ctx, event_loop, game)
+    event_loop::run(ctx, event_loop, tabs)
 }
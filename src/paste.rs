@@ -0,0 +1,139 @@
+use crate::Cell;
+
+/// A pattern held on the cursor before it is placed, kept with its top-left corner at (0, 0).
+#[derive(Clone)]
+pub struct Paste {
+    cells: Vec<Cell>,
+    width: i32,
+    height: i32,
+}
+
+impl Paste {
+    pub fn new(cells: impl IntoIterator<Item = Cell>) -> Self {
+        let mut paste = Self {
+            cells: cells.into_iter().collect(),
+            width: 0,
+            height: 0,
+        };
+        paste.normalize();
+        paste
+    }
+
+    fn normalize(&mut self) {
+        let min_x = self.cells.iter().map(|cell| cell.0).min().unwrap_or(0);
+        let min_y = self.cells.iter().map(|cell| cell.1).min().unwrap_or(0);
+        for cell in &mut self.cells {
+            *cell = Cell(cell.0.saturating_sub(min_x), cell.1.saturating_sub(min_y));
+        }
+        self.width = self
+            .cells
+            .iter()
+            .map(|cell| cell.0)
+            .max()
+            .map_or(0, |x| x.saturating_add(1));
+        self.height = self
+            .cells
+            .iter()
+            .map(|cell| cell.1)
+            .max()
+            .map_or(0, |y| y.saturating_add(1));
+    }
+
+    fn transform(&mut self, f: impl Fn(Cell) -> Cell) {
+        for cell in &mut self.cells {
+            *cell = f(*cell);
+        }
+        self.normalize();
+    }
+
+    /// Quarter turn clockwise as seen on screen (y grows downwards).
+    pub fn rotate_clockwise(&mut self) {
+        self.transform(|Cell(x, y)| Cell(-y, x));
+    }
+
+    pub fn rotate_anticlockwise(&mut self) {
+        self.transform(|Cell(x, y)| Cell(y, -x));
+    }
+
+    /// Mirrors left to right.
+    pub fn flip_horizontal(&mut self) {
+        self.transform(|Cell(x, y)| Cell(-x, y));
+    }
+
+    /// Mirrors top to bottom.
+    pub fn flip_vertical(&mut self) {
+        self.transform(|Cell(x, y)| Cell(x, -y));
+    }
+
+    /// The grid cell the top-left corner goes on so the pattern is centered on `center`.
+    pub fn origin_centered_on(&self, center: Cell) -> Cell {
+        Cell(
+            center.0.saturating_sub(self.width / 2),
+            center.1.saturating_sub(self.height / 2),
+        )
+    }
+
+    /// The pattern's cells with its top-left corner on `origin`, leaving out any past the edge
+    /// of the universe.
+    pub fn cells_at(&self, origin: Cell) -> impl Iterator<Item = Cell> + '_ {
+        self.cells
+            .iter()
+            .filter_map(move |cell| cell.offset(origin.0, origin.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashSet;
+
+    fn placed(paste: &Paste) -> HashSet<Cell> {
+        paste.cells_at(Cell(0, 0)).collect()
+    }
+
+    /// An R-pentomino, which looks different in every orientation.
+    fn r_pentomino() -> Paste {
+        Paste::new([
+            Cell(11, 5),
+            Cell(12, 5),
+            Cell(10, 6),
+            Cell(11, 6),
+            Cell(11, 7),
+        ])
+    }
+
+    #[test]
+    fn four_quarter_turns_give_back_the_original() {
+        let original = r_pentomino();
+        for rotate in [Paste::rotate_clockwise, Paste::rotate_anticlockwise] {
+            let mut paste = original.clone();
+            for turns in 1..=4 {
+                rotate(&mut paste);
+                assert_eq!(placed(&paste) == placed(&original), turns == 4);
+            }
+        }
+    }
+
+    #[test]
+    fn rotations_and_flips_keep_the_corner_at_the_origin() {
+        let mut paste = r_pentomino();
+        assert_eq!(
+            placed(&paste),
+            HashSet::from([Cell(1, 0), Cell(2, 0), Cell(0, 1), Cell(1, 1), Cell(1, 2)])
+        );
+        paste.rotate_clockwise();
+        assert_eq!(
+            placed(&paste),
+            HashSet::from([Cell(1, 0), Cell(0, 1), Cell(1, 1), Cell(2, 1), Cell(2, 2)])
+        );
+        paste.flip_horizontal();
+        paste.flip_horizontal();
+        paste.flip_vertical();
+        paste.flip_vertical();
+        assert_eq!(
+            placed(&paste),
+            HashSet::from([Cell(1, 0), Cell(0, 1), Cell(1, 1), Cell(2, 1), Cell(2, 2)])
+        );
+    }
+}
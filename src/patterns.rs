@@ -0,0 +1,39 @@
+use std::fs;
+use std::path::Path;
+
+pub use celleste_core::patterns::{parse_cells, parse_text, to_rle, Pattern};
+
+use crate::{SaveState, SavedView};
+
+/// Whether the file is a saved state or in any pattern format the engine reads.
+pub fn is_pattern_file(path: &Path) -> bool {
+    celleste_core::patterns::extension(path).as_deref() == Some("json")
+        || celleste_core::patterns::is_pattern_file(path)
+}
+
+/// Saved states are JSON, and files without an extension are assumed to be saves too.
+fn is_save_file(path: &Path) -> bool {
+    matches!(
+        celleste_core::patterns::extension(path).as_deref(),
+        Some("json") | None
+    )
+}
+
+/// Loads a saved state or pattern, picking the format from the file extension. Saved states
+/// also bring back the view they were written from.
+pub fn load_pattern(path: &Path) -> Result<(Pattern, Option<SavedView>), String> {
+    if !is_save_file(path) {
+        return celleste_core::patterns::load_pattern(path).map(|pattern| (pattern, None));
+    }
+    let json = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read pattern from file: {}", err))?;
+    let save_state = serde_json::from_str::<SaveState>(&json)
+        .map_err(|err| format!("Failed to deserialize game state: {}", err))?;
+    let pattern = Pattern {
+        cells: save_state.alive_cells,
+        dying: save_state.dying_cells,
+        rules: Some(save_state.rules),
+        absolute: true,
+    };
+    Ok((pattern, save_state.view))
+}
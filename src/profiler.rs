@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+/// The stages of a single generation, from stepping the rules to putting pixels on screen.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    NeighborCount,
+    RuleEval,
+    StateSwap,
+    MeshBuild,
+    Draw,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 5] = [
+        Phase::NeighborCount,
+        Phase::RuleEval,
+        Phase::StateSwap,
+        Phase::MeshBuild,
+        Phase::Draw,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Phase::NeighborCount => "neighbor count",
+            Phase::RuleEval => "rule eval",
+            Phase::StateSwap => "state swap",
+            Phase::MeshBuild => "mesh build",
+            Phase::Draw => "draw",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Accumulates per-phase timings so slowness can be pinned on simulation or rendering.
+#[derive(Default)]
+pub struct Profiler {
+    last: [Duration; 5],
+    total: [Duration; 5],
+    samples: [u32; 5],
+}
+
+impl Profiler {
+    pub fn record(&mut self, phase: Phase, elapsed: Duration) {
+        let i = phase.index();
+        self.last[i] = elapsed;
+        self.total[i] += elapsed;
+        self.samples[i] += 1;
+    }
+
+    fn mean(&self, phase: Phase) -> Duration {
+        let i = phase.index();
+        if self.samples[i] == 0 {
+            Duration::ZERO
+        } else {
+            self.total[i] / self.samples[i]
+        }
+    }
+
+    /// One line per phase with the last and mean time and its share of the mean frame.
+    pub fn summary(&self) -> String {
+        let frame: Duration = Phase::ALL.iter().map(|&p| self.mean(p)).sum();
+        let mut lines = Vec::new();
+        for phase in Phase::ALL {
+            let mean = self.mean(phase);
+            let share = if frame.is_zero() {
+                0.0
+            } else {
+                100.0 * mean.as_secs_f64() / frame.as_secs_f64()
+            };
+            lines.push(format!(
+                "{:<15}{:>8.3} ms last {:>8.3} ms mean {:>5.1}%",
+                phase.label(),
+                self.last[phase.index()].as_secs_f64() * 1000.0,
+                mean.as_secs_f64() * 1000.0,
+                share
+            ));
+        }
+        lines.join("\n")
+    }
+}
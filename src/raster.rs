@@ -0,0 +1,74 @@
+use ggez::graphics::{Color, Rect};
+
+use crate::{camera::Camera, Cell};
+
+/// Gray level of a pixel holding any live cell at all, so sparse objects stay visible when
+/// zoomed far out.
+const MIN_DENSITY_LEVEL: f32 = 64.0;
+
+/// An RGBA image rendered on the CPU, for output paths that have no window to draw into.
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Frame {
+    /// A frame cleared to opaque black.
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut pixels = vec![0; (width * height * 4) as usize];
+        for alpha in pixels.iter_mut().skip(3).step_by(4) {
+            *alpha = 255;
+        }
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Fills the pixels covered by a screen-space rectangle, clipped to the frame.
+    pub fn fill_rect(&mut self, rect: Rect, color: Color) {
+        let x0 = rect.x.floor().max(0.0) as u32;
+        let y0 = rect.y.floor().max(0.0) as u32;
+        let x1 = ((rect.x + rect.w).ceil().max(0.0) as u32).min(self.width);
+        let y1 = ((rect.y + rect.h).ceil().max(0.0) as u32).min(self.height);
+        let (r, g, b, a) = color.to_rgba();
+        for py in y0..y1 {
+            for px in x0..x1 {
+                let i = ((py * self.width + px) * 4) as usize;
+                self.pixels[i..i + 4].copy_from_slice(&[r, g, b, a]);
+            }
+        }
+    }
+}
+
+/// How full of live cells each pixel of a `width` x `height` view is, row by row, for views
+/// zoomed out until cells are smaller than a pixel: 0 where there are none, otherwise from
+/// a faint gray up to 255 for a pixel entirely covered by live cells.
+pub fn density(
+    cells: impl Iterator<Item = Cell>,
+    camera: &Camera,
+    width: u32,
+    height: u32,
+) -> Vec<u8> {
+    let mut counts = vec![0u32; (width * height) as usize];
+    for cell in cells {
+        let (x, y) = camera.cell_to_screen(cell);
+        let (x, y) = (x.floor(), y.floor());
+        if x >= 0.0 && y >= 0.0 && x < width as f32 && y < height as f32 {
+            counts[(y as u32 * width + x as u32) as usize] += 1;
+        }
+    }
+    let cells_per_pixel = 1.0 / (camera.cell_size * camera.cell_size);
+    counts
+        .into_iter()
+        .map(|count| {
+            if count == 0 {
+                return 0;
+            }
+            let fill = (count as f32 / cells_per_pixel).min(1.0);
+            (fill * 255.0).max(MIN_DENSITY_LEVEL) as u8
+        })
+        .collect()
+}
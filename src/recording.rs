@@ -0,0 +1,168 @@
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    imageops::{self, FilterType},
+    Delay, RgbaImage,
+};
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+use crate::raster::Frame;
+
+/// Quantization speed for the GIF encoder (1-30); the default of 1 is far too slow for
+/// window-sized frames.
+const GIF_ENCODE_SPEED: i32 = 10;
+
+/// How the G hotkey records.
+#[derive(Clone)]
+pub struct GifOptions {
+    pub path: PathBuf,
+    /// Capture one frame per this many generations
+    pub every: usize,
+    /// Size of the GIF relative to the window
+    pub scale: f32,
+    pub delay_ms: u32,
+    /// Recording stops by itself after this many frames
+    pub max_frames: usize,
+}
+
+/// Collects frames of the view while recording, and encodes them as an animated GIF when
+/// recording stops.
+pub struct GifRecorder {
+    options: GifOptions,
+    width: u32,
+    height: u32,
+    frames: Vec<image::Frame>,
+    /// First generation the next frame is taken at
+    next_generation: usize,
+}
+
+impl GifRecorder {
+    /// Starts recording a `width` x `height` view from `generation` on.
+    pub fn new(options: GifOptions, width: u32, height: u32, generation: usize) -> Self {
+        Self {
+            options,
+            width,
+            height,
+            frames: Vec::new(),
+            next_generation: generation,
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether a frame is due at `generation`.
+    pub fn due(&self, generation: usize) -> bool {
+        generation >= self.next_generation
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.frames.len() >= self.options.max_frames
+    }
+
+    pub fn capture(&mut self, frame: Frame, generation: usize) -> Result<(), String> {
+        let image = RgbaImage::from_raw(frame.width, frame.height, frame.pixels)
+            .ok_or_else(|| "Rendered frame has the wrong size".to_string())?;
+        let width = ((frame.width as f32 * self.options.scale).round() as u32).max(1);
+        let height = ((frame.height as f32 * self.options.scale).round() as u32).max(1);
+        // Averaging keeps cells smaller than a pixel visible when scaling down
+        let filter = if self.options.scale < 1.0 {
+            FilterType::Triangle
+        } else {
+            FilterType::Nearest
+        };
+        let image = imageops::resize(&image, width, height, filter);
+        let delay = Delay::from_numer_denom_ms(self.options.delay_ms, 1);
+        self.frames
+            .push(image::Frame::from_parts(image, 0, 0, delay));
+        self.next_generation = generation + self.options.every;
+        Ok(())
+    }
+
+    /// Encodes the frames into the GIF file, returning its path.
+    pub fn finish(self) -> Result<PathBuf, String> {
+        if self.frames.is_empty() {
+            return Err("No frames were captured".to_string());
+        }
+        let path = self.options.path;
+        let file = File::create(&path)
+            .map_err(|err| format!("Failed to create {}: {}", path.display(), err))?;
+        let mut encoder = GifEncoder::new_with_speed(file, GIF_ENCODE_SPEED);
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(|err| err.to_string())?;
+        encoder
+            .encode_frames(self.frames)
+            .map_err(|err| err.to_string())?;
+        Ok(path)
+    }
+}
+
+/// Pipes raw RGBA frames to a spawned ffmpeg process, which encodes them into a video file.
+pub struct VideoRecorder {
+    ffmpeg: Child,
+    stdin: Option<ChildStdin>,
+    width: u32,
+    height: u32,
+    frames: usize,
+}
+
+impl VideoRecorder {
+    /// Starts ffmpeg writing `path`, its format picked by ffmpeg from the extension.
+    pub fn start(path: &Path, width: u32, height: u32, fps: u32) -> Result<Self, String> {
+        let mut ffmpeg = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error"])
+            .args(["-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-s", &format!("{}x{}", width, height)])
+            .args(["-r", &fps.to_string()])
+            .args(["-i", "-"])
+            // Most encoders need 4:2:0 chroma, and with it even dimensions
+            .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"])
+            .args(["-pix_fmt", "yuv420p"])
+            .arg(path)
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("Failed to start ffmpeg (is it installed?): {}", err))?;
+        let stdin = ffmpeg.stdin.take();
+        Ok(Self {
+            ffmpeg,
+            stdin,
+            width,
+            height,
+            frames: 0,
+        })
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        let stdin = self
+            .stdin
+            .as_mut()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        stdin.write_all(&frame.pixels)?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Closes the pipe and waits for ffmpeg to finish the file, returning the frames written.
+    pub fn finish(mut self) -> Result<usize, String> {
+        // Dropping stdin signals the end of the input
+        drop(self.stdin.take());
+        let status = self.ffmpeg.wait().map_err(|err| err.to_string())?;
+        if !status.success() {
+            return Err(format!("ffmpeg exited with {}", status));
+        }
+        Ok(self.frames)
+    }
+}
@@ -0,0 +1,166 @@
+use serde_json::{json, Value};
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// JSON-RPC error codes for requests that can't be run at all.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Error code for commands that ran but failed, e.g. a rule that doesn't parse.
+const COMMAND_FAILED: i64 = -32000;
+/// Most generations one `step` request may run, since it runs between two frames.
+const MAX_STEP_COUNT: u64 = 10_000;
+
+/// What a remote client asked the running instance to do.
+pub enum Command {
+    Pause,
+    Resume,
+    Step(usize),
+    SetRule(String),
+    LoadPattern(String),
+    /// Report the generation, rules and population, and with `cells` every live cell
+    GetState {
+        cells: bool,
+    },
+}
+
+/// A command waiting for the main loop, which answers it through `answer`.
+pub struct Request {
+    command: Command,
+    reply: Sender<Result<Value, String>>,
+}
+
+impl Request {
+    /// Runs the command with `run` and sends what it returns back to the client.
+    pub fn answer(self, run: impl FnOnce(Command) -> Result<Value, String>) {
+        let result = run(self.command);
+        // The client may have hung up in the meantime, which is no concern of the main loop
+        let _ = self.reply.send(result);
+    }
+}
+
+/// A TCP server taking newline-delimited JSON-RPC 2.0 requests, one per line, and handing
+/// them to the main loop. Each connection is served on its own thread.
+pub struct RemoteServer {
+    requests: Receiver<Request>,
+}
+
+impl RemoteServer {
+    /// Starts listening on `address`, e.g. `127.0.0.1:7878`.
+    pub fn bind(address: &str) -> Result<Self, String> {
+        let listener = TcpListener::bind(address)
+            .map_err(|err| format!("Failed to listen on {}: {}", address, err))?;
+        let (sender, requests) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let sender = sender.clone();
+                thread::spawn(move || serve(stream, sender));
+            }
+        });
+        Ok(Self { requests })
+    }
+
+    /// Requests that arrived since the last call, without waiting for more.
+    pub fn pending(&self) -> impl Iterator<Item = Request> + '_ {
+        self.requests.try_iter()
+    }
+}
+
+/// Answers one client's requests until it disconnects or the main loop goes away.
+fn serve(stream: TcpStream, sender: Sender<Request>) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match parse_request(&line) {
+            Ok((id, command)) => {
+                let (reply, result) = mpsc::channel();
+                if sender.send(Request { command, reply }).is_err() {
+                    return;
+                }
+                let Ok(result) = result.recv() else {
+                    return;
+                };
+                // Requests without an id are notifications, which get no response
+                let Some(id) = id else {
+                    continue;
+                };
+                match result {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err(message) => error_response(id, COMMAND_FAILED, &message),
+                }
+            }
+            Err((id, code, message)) => error_response(id, code, &message),
+        };
+        if writeln!(writer, "{}", response).is_err() {
+            return;
+        }
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Reads one JSON-RPC request into its id and command, or the error to answer it with.
+fn parse_request(line: &str) -> Result<(Option<Value>, Command), (Value, i64, String)> {
+    let request: Value = serde_json::from_str(line)
+        .map_err(|err| (Value::Null, PARSE_ERROR, format!("Invalid JSON: {}", err)))?;
+    let id = request.get("id").cloned();
+    let error_id = id.clone().unwrap_or(Value::Null);
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return Err((error_id, INVALID_REQUEST, "Missing method".to_string()));
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    let string_param = |name: &str| {
+        params
+            .get(name)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| {
+                (
+                    error_id.clone(),
+                    INVALID_PARAMS,
+                    format!("Missing {}", name),
+                )
+            })
+    };
+    let command = match method {
+        "pause" => Command::Pause,
+        "resume" => Command::Resume,
+        "step" => {
+            let count = match params.get("count") {
+                None => 1,
+                Some(count) => count
+                    .as_u64()
+                    .filter(|count| (1..=MAX_STEP_COUNT).contains(count))
+                    .ok_or_else(|| {
+                        let message =
+                            format!("count must be an integer from 1 to {}", MAX_STEP_COUNT);
+                        (error_id.clone(), INVALID_PARAMS, message)
+                    })? as usize,
+            };
+            Command::Step(count)
+        }
+        "set-rule" => Command::SetRule(string_param("rule")?),
+        "load-pattern" => Command::LoadPattern(string_param("path")?),
+        "get-state" => Command::GetState {
+            cells: params.get("cells").and_then(Value::as_bool).unwrap_or(true),
+        },
+        _ => {
+            let message = format!("Unknown method {}", method);
+            return Err((error_id, METHOD_NOT_FOUND, message));
+        }
+    };
+    Ok((id, command))
+}
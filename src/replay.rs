@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::undo::Edit;
+use crate::Cell;
+
+/// Longest a replay waits between two events recorded while paused, so idle stretches of the
+/// session don't stall it.
+const MAX_PAUSE: Duration = Duration::from_secs(3);
+
+/// The whole state of the grid, written at the start of a session and whenever it's replaced.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub cells: Vec<Cell>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dying: Vec<(Cell, u8)>,
+    pub rules: String,
+    pub generation: usize,
+}
+
+/// Something the user did that changed what the simulation does next.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Event {
+    /// Cells brought to life and killed by hand: painting, pasting, shapes, fills, undo and redo
+    Edit(Edit),
+    Clear,
+    Reset,
+    /// A save, pattern or tour entry replaced the grid
+    Load(Snapshot),
+    Rules(String),
+    Running(bool),
+}
+
+/// One line of a session file after the opening snapshot.
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    generation: usize,
+    /// Milliseconds since recording started
+    time_ms: u64,
+    event: Event,
+}
+
+/// Writes a session as newline-delimited JSON: the starting snapshot, then every event with the
+/// generation it happened at. Each line is flushed straight away so a crash keeps the session.
+pub struct SessionRecorder {
+    writer: BufWriter<File>,
+    start: Instant,
+}
+
+impl SessionRecorder {
+    pub fn create(path: &Path, start: &Snapshot) -> Result<Self, String> {
+        let file = File::create(path)
+            .map_err(|err| format!("Failed to create {}: {}", path.display(), err))?;
+        let mut recorder = Self {
+            writer: BufWriter::new(file),
+            start: Instant::now(),
+        };
+        recorder.write_line(start)?;
+        Ok(recorder)
+    }
+
+    pub fn record(&mut self, generation: usize, event: Event) -> Result<(), String> {
+        let entry = Entry {
+            generation,
+            time_ms: self.start.elapsed().as_millis() as u64,
+            event,
+        };
+        self.write_line(&entry)
+    }
+
+    fn write_line(&mut self, value: &impl Serialize) -> Result<(), String> {
+        let json = serde_json::to_string(value)
+            .map_err(|err| format!("Failed to serialize session event: {}", err))?;
+        writeln!(self.writer, "{}", json)
+            .and_then(|()| self.writer.flush())
+            .map_err(|err| format!("Failed to write session: {}", err))
+    }
+}
+
+/// A recorded session being played back. Events are applied once the simulation reaches
+/// their generation, and events recorded while paused keep their original spacing in time.
+pub struct Playback {
+    entries: VecDeque<Entry>,
+    last_time_ms: u64,
+    last_applied: Instant,
+}
+
+impl Playback {
+    /// Reads a session file into the snapshot it starts from and the events to play.
+    pub fn load(path: &Path) -> Result<(Snapshot, Self), String> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+        let start: Snapshot = lines
+            .next()
+            .ok_or_else(|| format!("{} is an empty session", path.display()))
+            .and_then(|line| {
+                serde_json::from_str(line)
+                    .map_err(|err| format!("Invalid session start in {}: {}", path.display(), err))
+            })?;
+        let entries = lines
+            .enumerate()
+            .map(|(i, line)| {
+                serde_json::from_str(line).map_err(|err| {
+                    format!(
+                        "Invalid event on line {} of {}: {}",
+                        i + 2,
+                        path.display(),
+                        err
+                    )
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        let playback = Self {
+            entries,
+            last_time_ms: 0,
+            last_applied: Instant::now(),
+        };
+        Ok((start, playback))
+    }
+
+    /// Generation the next event happens at, or `None` once the session is over.
+    pub fn next_generation(&self) -> Option<usize> {
+        self.entries.front().map(|entry| entry.generation)
+    }
+
+    /// The next event if it's due at `generation`; while `paused`, only once as much time has
+    /// passed since the last one as did in the recording.
+    pub fn next_event(&mut self, generation: usize, paused: bool) -> Option<Event> {
+        let entry = self.entries.front()?;
+        if entry.generation > generation {
+            return None;
+        }
+        let gap = Duration::from_millis(entry.time_ms.saturating_sub(self.last_time_ms));
+        if paused && self.last_applied.elapsed() < gap.min(MAX_PAUSE) {
+            return None;
+        }
+        let entry = self.entries.pop_front()?;
+        self.last_time_ms = entry.time_ms;
+        self.last_applied = Instant::now();
+        Some(entry.event)
+    }
+}
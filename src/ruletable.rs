@@ -0,0 +1,527 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::Cell;
+
+/// Neighbor offsets, in the order Golly lists them in transitions (clockwise from north).
+const MOORE: [(i32, i32); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+const VON_NEUMANN: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const HEXAGONAL: [(i32, i32); 6] = [(0, -1), (1, 0), (1, 1), (0, 1), (-1, 0), (-1, -1)];
+
+/// Set of states a variable stands for; a bit per state.
+#[derive(Clone, Copy, Default)]
+struct StateSet([u64; 4]);
+
+impl StateSet {
+    fn insert(&mut self, state: u8) {
+        self.0[state as usize / 64] |= 1 << (state % 64);
+    }
+
+    fn union(&mut self, other: StateSet) {
+        for (word, other) in self.0.iter_mut().zip(other.0) {
+            *word |= other;
+        }
+    }
+
+    fn contains(&self, state: u8) -> bool {
+        self.0[state as usize / 64] & (1 << (state % 64)) != 0
+    }
+}
+
+/// A transition input or output: a literal state, or a variable (bound to one value throughout
+/// the transition).
+#[derive(Clone, Copy)]
+enum Entry {
+    State(u8),
+    Var(usize),
+}
+
+/// Cell, neighbors in `offsets` order, then the new state.
+#[derive(Clone)]
+struct Transition {
+    center: Entry,
+    neighbors: Vec<Entry>,
+    output: Entry,
+}
+
+/// A Golly `@TABLE` rule: multi-state transitions tried in order, the first match deciding a
+/// cell's next state and cells matching none keeping theirs. State 0 is the empty background.
+#[derive(Clone)]
+pub struct RuleTable {
+    pub name: String,
+    /// Number of states, from 2 to 256 as in Golly
+    pub states: u16,
+    offsets: &'static [(i32, i32)],
+    /// Orderings of the neighbor positions a transition also applies under
+    symmetries: Vec<Vec<usize>>,
+    /// Whether neighbor order doesn't matter at all (`symmetries:permute`), in which case
+    /// neighbors are matched as a multiset instead of trying every ordering
+    permute: bool,
+    vars: Vec<StateSet>,
+    transitions: Vec<Transition>,
+    /// Display colors from the `@COLORS` section, by state
+    colors: HashMap<u8, (u8, u8, u8)>,
+    /// Next state by center and neighbor states, filled in as neighborhoods are seen
+    cache: HashMap<Vec<u8>, u8>,
+}
+
+impl RuleTable {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut table = Self {
+            name: String::new(),
+            states: 2,
+            offsets: &MOORE,
+            symmetries: Vec::new(),
+            permute: false,
+            vars: Vec::new(),
+            transitions: Vec::new(),
+            colors: HashMap::new(),
+            cache: HashMap::new(),
+        };
+        let mut var_names: HashMap<String, usize> = HashMap::new();
+        let mut symmetry = "none".to_string();
+        let mut section = "";
+        let mut has_table = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('@') {
+                let mut words = header.split_whitespace();
+                section = match words.next() {
+                    Some("RULE") => {
+                        table.name = words.next().unwrap_or("").to_string();
+                        "RULE"
+                    }
+                    Some("TABLE") => {
+                        has_table = true;
+                        "TABLE"
+                    }
+                    Some("COLORS") => "COLORS",
+                    Some("TREE") => {
+                        return Err("@TREE rules are not supported, only @TABLE".to_string())
+                    }
+                    _ => "",
+                };
+                continue;
+            }
+            match section {
+                "TABLE" => {
+                    if let Some((key, value)) = line.split_once(':') {
+                        let value = value.trim();
+                        match key.trim() {
+                            "n_states" => {
+                                table.states = value
+                                    .parse::<u16>()
+                                    .ok()
+                                    .filter(|states| (2..=256).contains(states))
+                                    .ok_or_else(|| {
+                                        format!("Invalid n_states (2 to 256): {}", value)
+                                    })?;
+                            }
+                            "neighborhood" => {
+                                table.offsets = match value {
+                                    "Moore" => &MOORE,
+                                    "vonNeumann" => &VON_NEUMANN,
+                                    "hexagonal" => &HEXAGONAL,
+                                    _ => {
+                                        return Err(format!("Unsupported neighborhood: {}", value))
+                                    }
+                                }
+                            }
+                            "symmetries" => symmetry = value.to_string(),
+                            _ => return Err(format!("Unknown table setting: {}", key.trim())),
+                        }
+                    } else if let Some(var) = line.strip_prefix("var ") {
+                        let (name, values) = var
+                            .split_once('=')
+                            .ok_or_else(|| format!("Invalid variable: {}", line))?;
+                        let mut set = StateSet::default();
+                        let values = values.trim().trim_start_matches('{').trim_end_matches('}');
+                        for value in values.split(',').map(str::trim) {
+                            match var_names.get(value) {
+                                Some(&index) => set.union(table.vars[index]),
+                                None => set.insert(table.state(value)?),
+                            }
+                        }
+                        var_names.insert(name.trim().to_string(), table.vars.len());
+                        table.vars.push(set);
+                    } else {
+                        let transition = table.transition(line, &var_names)?;
+                        table.transitions.push(transition);
+                    }
+                }
+                "COLORS" => {
+                    let numbers: Vec<u8> = line
+                        .split_whitespace()
+                        .map(|n| n.parse::<u8>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| format!("Invalid color: {}", line))?;
+                    // Gradient lines (six numbers) are left to the default coloring
+                    if let [state, r, g, b] = numbers[..] {
+                        table.colors.insert(state, (r, g, b));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !has_table {
+            return Err("No @TABLE section found".to_string());
+        }
+        table.symmetries = symmetries(&symmetry, table.offsets.len())?;
+        table.permute = symmetry == "permute";
+        Ok(table)
+    }
+
+    fn state(&self, value: &str) -> Result<u8, String> {
+        value
+            .parse::<u8>()
+            .ok()
+            .filter(|&state| u16::from(state) < self.states)
+            .ok_or_else(|| format!("Invalid state or unknown variable: {}", value))
+    }
+
+    fn transition(
+        &self,
+        line: &str,
+        var_names: &HashMap<String, usize>,
+    ) -> Result<Transition, String> {
+        let values: Vec<String> = if line.contains(',') {
+            line.split(',')
+                .map(|value| value.trim().to_string())
+                .collect()
+        } else {
+            // States under 10 may be written without commas, one digit each
+            line.chars().map(String::from).collect()
+        };
+        if values.len() != self.offsets.len() + 2 {
+            return Err(format!(
+                "Transition needs {} entries: {}",
+                self.offsets.len() + 2,
+                line
+            ));
+        }
+        let entries = values
+            .iter()
+            .map(|value| match var_names.get(value) {
+                Some(&index) => Ok(Entry::Var(index)),
+                None => self.state(value).map(Entry::State),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Transition {
+            center: entries[0],
+            neighbors: entries[1..entries.len() - 1].to_vec(),
+            output: entries[entries.len() - 1],
+        })
+    }
+
+    pub fn color(&self, state: u8) -> Option<(f32, f32, f32)> {
+        self.colors
+            .get(&state)
+            .map(|&(r, g, b)| (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0))
+    }
+
+    /// Runs one generation over the nonzero cells, returning the new nonzero cells.
+    pub fn step(&mut self, cells: &HashMap<Cell, u8>) -> HashMap<Cell, u8> {
+        let mut candidates: Vec<Cell> = cells.keys().copied().collect();
+        for &cell in cells.keys() {
+            for &(dx, dy) in self.offsets {
+                match cell.offset(dx, dy) {
+                    Some(neighbor) if !cells.contains_key(&neighbor) => candidates.push(neighbor),
+                    _ => {}
+                }
+            }
+        }
+        candidates.sort_unstable_by_key(|cell| (cell.0, cell.1));
+        candidates.dedup();
+
+        let mut next = HashMap::new();
+        let mut key = Vec::with_capacity(self.offsets.len() + 1);
+        for cell in candidates {
+            key.clear();
+            key.push(cells.get(&cell).copied().unwrap_or(0));
+            for &(dx, dy) in self.offsets {
+                // Past the edge of the universe counts as empty
+                let neighbor = cell.offset(dx, dy);
+                key.push(
+                    neighbor
+                        .and_then(|neighbor| cells.get(&neighbor))
+                        .copied()
+                        .unwrap_or(0),
+                );
+            }
+            if self.permute {
+                // Every ordering of the neighbors behaves the same, so share one cache entry
+                key[1..].sort_unstable();
+            }
+            let state = match self.cache.get(&key) {
+                Some(&state) => state,
+                None => {
+                    let state = self.next_state(&key);
+                    self.cache.insert(key.clone(), state);
+                    state
+                }
+            };
+            if state != 0 {
+                next.insert(cell, state);
+            }
+        }
+        next
+    }
+
+    fn next_state(&self, key: &[u8]) -> u8 {
+        for transition in &self.transitions {
+            if self.permute {
+                if let Some(state) = self.apply_permuted(transition, key) {
+                    return state;
+                }
+                continue;
+            }
+            for order in &self.symmetries {
+                if let Some(state) = self.apply(transition, key, order) {
+                    return state;
+                }
+            }
+        }
+        key[0]
+    }
+
+    /// The transition's output if it matches with its neighbors taken in `order`.
+    fn apply(&self, transition: &Transition, key: &[u8], order: &[usize]) -> Option<u8> {
+        let mut bound: Vec<(usize, u8)> = Vec::new();
+        if !self.matches(transition.center, key[0], &mut bound) {
+            return None;
+        }
+        for (position, &i) in order.iter().enumerate() {
+            if !self.matches(transition.neighbors[i], key[position + 1], &mut bound) {
+                return None;
+            }
+        }
+        output(transition, &bound)
+    }
+
+    /// The transition's output if its neighbors match the key's in any order: its literal
+    /// states are taken out of the key's multiset of neighbors, then its variables are matched
+    /// against what is left.
+    fn apply_permuted(&self, transition: &Transition, key: &[u8]) -> Option<u8> {
+        let mut bound: Vec<(usize, u8)> = Vec::new();
+        if !self.matches(transition.center, key[0], &mut bound) {
+            return None;
+        }
+        let mut left = key[1..].to_vec();
+        left.sort_unstable();
+        let mut vars = Vec::new();
+        for &entry in &transition.neighbors {
+            match entry {
+                Entry::State(state) => {
+                    let i = left.iter().position(|&neighbor| neighbor == state)?;
+                    left.remove(i);
+                }
+                Entry::Var(var) => vars.push(var),
+            }
+        }
+        if !self.match_vars(&vars, &mut left, &mut bound) {
+            return None;
+        }
+        output(transition, &bound)
+    }
+
+    /// Whether `vars` can each take a different one of the sorted states `left`, consistently
+    /// with the values already `bound`. Each distinct state is only tried once per variable,
+    /// so the search stays small even with every neighbor a variable.
+    fn match_vars(&self, vars: &[usize], left: &mut Vec<u8>, bound: &mut Vec<(usize, u8)>) -> bool {
+        let Some((&var, rest)) = vars.split_first() else {
+            return true;
+        };
+        let mut previous = None;
+        for i in 0..left.len() {
+            let state = left[i];
+            if previous == Some(state) {
+                continue;
+            }
+            previous = Some(state);
+            let bound_before = bound.len();
+            if self.matches(Entry::Var(var), state, bound) {
+                left.remove(i);
+                if self.match_vars(rest, left, bound) {
+                    return true;
+                }
+                left.insert(i, state);
+            }
+            bound.truncate(bound_before);
+        }
+        false
+    }
+
+    /// Whether `state` fits `entry`, binding a variable to it the first time it's seen.
+    fn matches(&self, entry: Entry, state: u8, bound: &mut Vec<(usize, u8)>) -> bool {
+        match entry {
+            Entry::State(expected) => expected == state,
+            Entry::Var(var) => match bound.iter().find(|&&(index, _)| index == var) {
+                Some(&(_, value)) => value == state,
+                None => {
+                    bound.push((var, state));
+                    self.vars[var].contains(state)
+                }
+            },
+        }
+    }
+}
+
+/// A matched transition's new state, looking up the value its output variable was bound to.
+fn output(transition: &Transition, bound: &[(usize, u8)]) -> Option<u8> {
+    match transition.output {
+        Entry::State(state) => Some(state),
+        Entry::Var(var) => bound
+            .iter()
+            .find(|&&(index, _)| index == var)
+            .map(|&(_, value)| value),
+    }
+}
+
+/// Neighbor orderings for a Golly symmetry name, over `n` positions listed clockwise.
+fn symmetries(name: &str, n: usize) -> Result<Vec<Vec<usize>>, String> {
+    let rotations = |count: usize| -> Vec<Vec<usize>> {
+        let step = n / count;
+        (0..count)
+            .map(|r| (0..n).map(|i| (i + r * step) % n).collect())
+            .collect()
+    };
+    let reflected = |orders: Vec<Vec<usize>>| -> Vec<Vec<usize>> {
+        let mut all = orders.clone();
+        all.extend(
+            orders
+                .iter()
+                .map(|order| (0..n).map(|i| order[(n - i) % n]).collect()),
+        );
+        all
+    };
+    let orders = match name {
+        "none" => rotations(1),
+        "reflect_horizontal" => reflected(rotations(1)),
+        // Matched as multisets by `RuleTable::apply_permuted` rather than order by order
+        "permute" => Vec::new(),
+        _ => {
+            let (count, reflect) = match name.strip_prefix("rotate") {
+                Some(rest) => match rest.strip_suffix("reflect") {
+                    Some(count) => (count, true),
+                    None => (rest, false),
+                },
+                None => return Err(format!("Unsupported symmetry: {}", name)),
+            };
+            let count = count
+                .parse::<usize>()
+                .ok()
+                .filter(|&count| count > 0 && n.is_multiple_of(count))
+                .ok_or_else(|| format!("Unsupported symmetry: {}", name))?;
+            if reflect {
+                reflected(rotations(count))
+            } else {
+                rotations(count)
+            }
+        }
+    };
+    Ok(orders)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// WireWorld as a Moore table under `symmetry`: heads become tails, tails wire, and wire
+    /// with one or two heads among its neighbors becomes a head.
+    fn wireworld(symmetry: &str) -> RuleTable {
+        let text = format!(
+            "@RULE WireWorld\n\
+             @TABLE\n\
+             n_states:4\n\
+             neighborhood:Moore\n\
+             symmetries:{}\n\
+             var a={{0,1,2,3}}\n\
+             var b={{0,1,2,3}}\n\
+             var c={{0,1,2,3}}\n\
+             var d={{0,1,2,3}}\n\
+             var e={{0,1,2,3}}\n\
+             var f={{0,1,2,3}}\n\
+             var g={{0,1,2,3}}\n\
+             var h={{0,1,2,3}}\n\
+             var i={{0,2,3}}\n\
+             var j={{0,2,3}}\n\
+             var k={{0,2,3}}\n\
+             var l={{0,2,3}}\n\
+             var m={{0,2,3}}\n\
+             var n={{0,2,3}}\n\
+             var o={{0,2,3}}\n\
+             1,a,b,c,d,e,f,g,h,2\n\
+             2,a,b,c,d,e,f,g,h,3\n\
+             3,1,i,j,k,l,m,n,o,1\n\
+             3,1,1,i,j,k,l,m,n,1\n\
+             @COLORS\n\
+             1 0 128 255\n",
+            symmetry
+        );
+        RuleTable::parse(&text).unwrap()
+    }
+
+    fn cells(list: &[(i32, i32, u8)]) -> HashMap<Cell, u8> {
+        list.iter()
+            .map(|&(x, y, state)| (Cell(x, y), state))
+            .collect()
+    }
+
+    #[test]
+    fn wireworld_moves_an_electron_along_a_wire() {
+        for symmetry in ["rotate4", "permute"] {
+            let mut table = wireworld(symmetry);
+            let wire = cells(&[(0, 0, 2), (1, 0, 1), (2, 0, 3), (3, 0, 3)]);
+            assert_eq!(
+                table.step(&wire),
+                cells(&[(0, 0, 3), (1, 0, 2), (2, 0, 1), (3, 0, 3)]),
+                "{}",
+                symmetry
+            );
+        }
+        assert_eq!(
+            wireworld("permute").color(1),
+            Some((0.0, 128.0 / 255.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn permute_matches_neighbors_in_any_position_but_rotate4_does_not() {
+        // A head diagonally next to the wire: a Moore rotation by quarter turns never brings
+        // it round to the north
+        let diagonal = cells(&[(0, 0, 1), (1, 1, 3)]);
+        assert_eq!(wireworld("permute").step(&diagonal)[&Cell(1, 1)], 1);
+        assert_eq!(wireworld("rotate4").step(&diagonal)[&Cell(1, 1)], 3);
+
+        // Three heads are too many, wherever they are
+        let crowded = cells(&[(0, 0, 1), (2, 0, 1), (1, 2, 1), (1, 1, 3)]);
+        assert_eq!(wireworld("permute").step(&crowded)[&Cell(1, 1)], 3);
+    }
+
+    #[test]
+    fn n_states_goes_up_to_256() {
+        let table = RuleTable::parse("@TABLE\nn_states:256\n255,0,0,0,0,0,0,0,0,0\n").unwrap();
+        assert_eq!(table.states, 256);
+        assert!(RuleTable::parse("@TABLE\nn_states:257\n").is_err());
+    }
+}
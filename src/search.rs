@@ -0,0 +1,157 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::*;
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+use celleste_core::simulation::grid_cells;
+
+use crate::{census, patterns, Cell, Celleste, Rules};
+
+/// Objects found in almost every search; anything else is saved as RLE when it turns up.
+const COMMON_OBJECTS: &[&str] = &[
+    "block", "blinker", "beehive", "glider", "loaf", "boat", "ship", "tub", "pond",
+];
+/// Generations between the censuses that decide whether a soup still moving (e.g. because
+/// of gliders flying off, which never let the pattern repeat) has settled.
+const CENSUS_INTERVAL: usize = 500;
+
+pub struct SearchOptions {
+    pub soups: usize,
+    pub width: i32,
+    pub height: i32,
+    pub rules: String,
+    pub out_dir: PathBuf,
+    pub max_generations: usize,
+    pub seed: u64,
+}
+
+/// What became of one soup.
+struct SoupResult {
+    settled: bool,
+    objects: Vec<census::Object>,
+}
+
+/// Runs `options.soups` random soups in parallel, censuses what each leaves behind, and
+/// writes a summary plus every uncommon object as RLE into `options.out_dir`.
+pub fn run_search(options: &SearchOptions) -> Result<(), String> {
+    let rules = Rules::from_string(&options.rules)?;
+    // Objects are grouped and named by how they evolve under Life, so other rules would get
+    // wrong clusters and names
+    if rules.as_string() != "B3/S23" {
+        return Err(format!(
+            "The census only knows Life's objects, so searches run B3/S23, not {}",
+            options.rules
+        ));
+    }
+    grid_cells(options.width, options.height)
+        .map_err(|err| format!("Invalid soup size: {}", err))?;
+    fs::create_dir_all(&options.out_dir)
+        .map_err(|err| format!("Failed to create {}: {}", options.out_dir.display(), err))?;
+
+    let results: Vec<SoupResult> = (0..options.soups)
+        .into_par_iter()
+        .map(|index| run_soup(index, &rules, options))
+        .collect();
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    // Known objects are saved once whatever phase they turn up in, others once per shape
+    let mut saved_names = HashSet::new();
+    let mut saved_shapes = HashSet::new();
+    let mut rare_files = 0;
+    for (index, result) in results.iter().enumerate() {
+        for object in &result.objects {
+            *counts.entry(&object.name).or_insert(0) += 1;
+            let new = if object.known {
+                saved_names.insert(object.name.as_str())
+            } else {
+                saved_shapes.insert(object.shape.clone())
+            };
+            if !new || COMMON_OBJECTS.contains(&object.name.as_str()) {
+                continue;
+            }
+            let file_name = format!("{}-soup{}.rle", object.name.replace(' ', "_"), index);
+            let path = options.out_dir.join(file_name);
+            let cells: HashSet<Cell> = object.cells.iter().copied().collect();
+            let rle = format!(
+                "#C {} from soup {} of seed {}\n{}",
+                object.name,
+                index,
+                options.seed,
+                patterns::to_rle(&cells, &options.rules)
+            );
+            fs::write(&path, rle)
+                .map_err(|err| format!("Failed to write {}: {}", path.display(), err))?;
+            rare_files += 1;
+        }
+    }
+
+    let unsettled = results.iter().filter(|result| !result.settled).count();
+    let mut counts: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(name, count)| (name.to_string(), count))
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let mut summary = format!(
+        "Searched {} soups of {}x{} in {} (seed {})\n\
+         {} still changing after {} generations, censused as they were\n\n",
+        options.soups,
+        options.width,
+        options.height,
+        options.rules,
+        options.seed,
+        unsettled,
+        options.max_generations
+    );
+    for line in census::lines(&counts) {
+        summary.push_str(&line);
+        summary.push('\n');
+    }
+    let summary_path = options.out_dir.join("summary.txt");
+    fs::write(&summary_path, &summary)
+        .map_err(|err| format!("Failed to write {}: {}", summary_path.display(), err))?;
+    print!("{}", summary);
+    println!(
+        "Summary and {} uncommon objects written to {}",
+        rare_files,
+        options.out_dir.display()
+    );
+    Ok(())
+}
+
+/// Runs the soup numbered `index` until it repeats, its census stops changing, or the
+/// generation limit is reached.
+fn run_soup(index: usize, rules: &Rules, options: &SearchOptions) -> SoupResult {
+    let mut rng = StdRng::seed_from_u64(options.seed.wrapping_add(index as u64));
+    let mut cells = HashSet::new();
+    for y in 0..options.height {
+        for x in 0..options.width {
+            if rng.gen_bool(0.5) {
+                cells.insert(Cell(x, y));
+            }
+        }
+    }
+    let mut universe = Celleste::new(cells.into_iter().collect(), 1.0, rules.clone(), true);
+    let mut last_census = None;
+    let mut settled = false;
+    for generation in 1..=options.max_generations {
+        universe.step();
+        if universe.journal.period().is_some() {
+            settled = true;
+            break;
+        }
+        if generation % CENSUS_INTERVAL == 0 {
+            let census = census::census(&universe.grid.alive_cells);
+            if last_census.as_ref() == Some(&census) {
+                settled = true;
+                break;
+            }
+            last_census = Some(census);
+        }
+    }
+    SoupResult {
+        settled,
+        objects: census::objects(&universe.grid.alive_cells),
+    }
+}
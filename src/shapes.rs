@@ -0,0 +1,112 @@
+use crate::Cell;
+
+/// Largest bounding box a shape may cover, so a zoomed-out drag can't freeze the program.
+pub const MAX_SHAPE_AREA: i64 = 4_000_000;
+
+/// What dragging with the right button does.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    /// Paints (or erases) under the brush as the mouse moves
+    Brush,
+    Line,
+    Rectangle,
+    FilledRectangle,
+    Ellipse,
+}
+
+impl Tool {
+    pub fn next(self) -> Self {
+        match self {
+            Tool::Brush => Tool::Line,
+            Tool::Line => Tool::Rectangle,
+            Tool::Rectangle => Tool::FilledRectangle,
+            Tool::FilledRectangle => Tool::Ellipse,
+            Tool::Ellipse => Tool::Brush,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::Brush => "brush",
+            Tool::Line => "line",
+            Tool::Rectangle => "rectangle",
+            Tool::FilledRectangle => "filled rectangle",
+            Tool::Ellipse => "ellipse",
+        }
+    }
+
+    /// The cells of the shape dragged from `from` to `to`, or `None` for the brush or a shape
+    /// too large to draw.
+    pub fn cells(self, from: Cell, to: Cell) -> Option<Vec<Cell>> {
+        let (min, max) = (
+            Cell(from.0.min(to.0), from.1.min(to.1)),
+            Cell(from.0.max(to.0), from.1.max(to.1)),
+        );
+        let area = (max.0 - min.0 + 1) as i64 * (max.1 - min.1 + 1) as i64;
+        if area > MAX_SHAPE_AREA {
+            return None;
+        }
+        match self {
+            Tool::Brush => None,
+            Tool::Line => Some(line(from, to)),
+            Tool::Rectangle => Some(rectangle(min, max, false)),
+            Tool::FilledRectangle => Some(rectangle(min, max, true)),
+            Tool::Ellipse => Some(ellipse(min, max)),
+        }
+    }
+}
+
+/// Cells on the straight line from `from` to `to`, both included (Bresenham's algorithm).
+pub fn line(from: Cell, to: Cell) -> Vec<Cell> {
+    let (dx, dy) = ((to.0 - from.0).abs(), -(to.1 - from.1).abs());
+    let (step_x, step_y) = ((to.0 - from.0).signum(), (to.1 - from.1).signum());
+    let mut cells = vec![from];
+    let (mut cell, mut error) = (from, dx + dy);
+    while cell != to {
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            cell.0 += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            cell.1 += step_y;
+        }
+        cells.push(cell);
+    }
+    cells
+}
+
+fn rectangle(min: Cell, max: Cell, filled: bool) -> Vec<Cell> {
+    (min.1..=max.1)
+        .flat_map(|y| (min.0..=max.0).map(move |x| Cell(x, y)))
+        .filter(|cell| {
+            filled || cell.0 == min.0 || cell.0 == max.0 || cell.1 == min.1 || cell.1 == max.1
+        })
+        .collect()
+}
+
+/// The outline of the ellipse filling the box: cells inside it with a side neighbor outside.
+fn ellipse(min: Cell, max: Cell) -> Vec<Cell> {
+    let center = ((min.0 + max.0) as f64 / 2.0, (min.1 + max.1) as f64 / 2.0);
+    // Measured to the outer edge of the border cells, so one-cell-wide boxes still have cells
+    let radii = (
+        (max.0 - min.0) as f64 / 2.0 + 0.5,
+        (max.1 - min.1) as f64 / 2.0 + 0.5,
+    );
+    let inside = |x: i32, y: i32| {
+        let (nx, ny) = (
+            (x as f64 - center.0) / radii.0,
+            (y as f64 - center.1) / radii.1,
+        );
+        nx * nx + ny * ny <= 1.0
+    };
+    rectangle(min, max, true)
+        .into_iter()
+        .filter(|cell| {
+            let (x, y) = (cell.0, cell.1);
+            inside(x, y)
+                && !(inside(x - 1, y) && inside(x + 1, y) && inside(x, y - 1) && inside(x, y + 1))
+        })
+        .collect()
+}
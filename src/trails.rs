@@ -0,0 +1,53 @@
+use std::collections::{HashMap, HashSet};
+
+use celleste_core::Cell;
+
+/// Cells that died in the last few generations, drawn fading out behind moving patterns so
+/// gliders and spaceships leave smooth trails.
+#[derive(Clone)]
+pub struct Trails {
+    /// Generations a dead cell keeps fading for
+    length: u8,
+    /// Recently dead cells with the generations since they died
+    dead: HashMap<Cell, u8>,
+    /// The live cells last recorded, to find deaths against
+    previous: HashSet<Cell>,
+}
+
+impl Trails {
+    /// Trails `length` generations long, starting from `cells`.
+    pub fn new(length: u8, cells: &HashSet<Cell>) -> Self {
+        Self {
+            length: length.max(1),
+            dead: HashMap::new(),
+            previous: cells.clone(),
+        }
+    }
+
+    /// Ages every trail cell a generation, then adds the cells that died since the last
+    /// record and drops those that came back to life.
+    pub fn record(&mut self, cells: &HashSet<Cell>) {
+        let length = self.length;
+        self.dead.retain(|cell, age| {
+            *age += 1;
+            *age < length && !cells.contains(cell)
+        });
+        for &cell in self.previous.difference(cells) {
+            self.dead.insert(cell, 0);
+        }
+        self.previous.clone_from(cells);
+    }
+
+    /// Starts over from `cells`, e.g. after loading a pattern.
+    pub fn restart(&mut self, cells: &HashSet<Cell>) {
+        *self = Self::new(self.length, cells);
+    }
+
+    /// Each trail cell with its opacity, from nearly 1 just after dying down towards 0.
+    pub fn cells(&self) -> impl Iterator<Item = (Cell, f32)> + '_ {
+        let length = self.length as f32 + 1.0;
+        self.dead
+            .iter()
+            .map(move |(&cell, &age)| (cell, 1.0 - (age as f32 + 1.0) / length))
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::Cell;
+
+/// Most grid colors a turmite may use; the palette has one entry per nonzero color.
+const MAX_COLORS: usize = 8;
+
+/// Colors painted by turmites, indexed by grid color minus one.
+const PALETTE: [(f32, f32, f32); MAX_COLORS - 1] = [
+    (1.0, 1.0, 1.0),
+    (0.2, 0.6, 1.0),
+    (1.0, 0.8, 0.2),
+    (0.4, 0.9, 0.4),
+    (0.9, 0.4, 0.9),
+    (1.0, 0.5, 0.3),
+    (0.5, 0.9, 0.9),
+];
+
+#[derive(Clone, Copy)]
+enum Turn {
+    None,
+    Right,
+    Reverse,
+    Left,
+}
+
+impl Turn {
+    fn apply(self, direction: u8) -> u8 {
+        let quarter_turns = match self {
+            Turn::None => 0,
+            Turn::Right => 1,
+            Turn::Reverse => 2,
+            Turn::Left => 3,
+        };
+        (direction + quarter_turns) % 4
+    }
+}
+
+/// What a turmite does on a cell of some color: repaint it, turn, and switch state.
+#[derive(Clone, Copy)]
+struct Transition {
+    write: u8,
+    turn: Turn,
+    next_state: u8,
+}
+
+#[derive(Clone, Copy)]
+pub struct Agent {
+    pub cell: Cell,
+    /// 0 north, 1 east, 2 south, 3 west
+    direction: u8,
+    state: u8,
+}
+
+/// A set of turmites walking a shared grid of colored cells. Color 0 is the empty background,
+/// and the cells of every other color are mirrored as the live cells of the universe.
+#[derive(Clone)]
+pub struct Turmites {
+    /// Transitions indexed by state, then by the color under the turmite
+    table: Vec<Vec<Transition>>,
+    pub colors: HashMap<Cell, u8>,
+    agents: Vec<Agent>,
+    initial_agents: Vec<Agent>,
+}
+
+impl Turmites {
+    /// Builds a `model` from its spec: `ant` takes a generalized Langton's ant turn string such
+    /// as `RL` or `LLRR` (one of L, R, N or U per color), and `turmite` a Golly-style table such
+    /// as `{{{1,2,0},{0,8,0}}}` of `{write, turn, next state}` triples per state and color, with
+    /// turns 1 none, 2 right, 4 U-turn and 8 left.
+    pub fn from_spec(model: &str, spec: &str) -> Result<Self, String> {
+        let table = match model {
+            "ant" => parse_ant(spec)?,
+            "turmite" => parse_table(spec)?,
+            _ => {
+                return Err(
+                    "Invalid model. Expected 'life', 'elementary', 'ant' or 'turmite'.".to_string(),
+                )
+            }
+        };
+        Ok(Self {
+            table,
+            colors: HashMap::new(),
+            agents: Vec::new(),
+            initial_agents: Vec::new(),
+        })
+    }
+
+    /// Places `count` turmites side by side around `center`, all facing north. Turmites that
+    /// would start past the edge of the universe start on it instead.
+    pub fn place_agents(&mut self, center: Cell, count: usize) {
+        const SPACING: i64 = 20;
+        let count = i64::try_from(count).unwrap_or(i64::MAX);
+        let first = (center.0 as i64).saturating_sub(SPACING.saturating_mul(count - 1) / 2);
+        self.agents = (0..count)
+            .map(|i| {
+                let x = first.saturating_add(i.saturating_mul(SPACING));
+                Agent {
+                    cell: Cell(x.clamp(i32::MIN as i64, i32::MAX as i64) as i32, center.1),
+                    direction: 0,
+                    state: 0,
+                }
+            })
+            .collect();
+        self.initial_agents = self.agents.clone();
+    }
+
+    pub fn agents(&self) -> &[Agent] {
+        &self.agents
+    }
+
+    /// Puts the turmites back where they started.
+    pub fn restart(&mut self) {
+        self.agents = self.initial_agents.clone();
+    }
+
+    /// A copy with the same table and starting turmites on an empty grid.
+    pub fn blank_like(&self) -> Self {
+        let mut turmites = self.clone();
+        turmites.colors.clear();
+        turmites.restart();
+        turmites
+    }
+
+    /// Brings the colors in line with hand-edited live cells: removed cells are cleared and
+    /// new ones get the first color.
+    pub fn sync(&mut self, alive_cells: &HashSet<Cell>) {
+        self.colors.retain(|cell, _| alive_cells.contains(cell));
+        for &cell in alive_cells {
+            self.colors.entry(cell).or_insert(1);
+        }
+    }
+
+    /// Moves every turmite once, mirroring the repainted cells into `alive_cells`. A turmite
+    /// at the edge of the universe stays where it is rather than stepping off it.
+    pub fn step(&mut self, alive_cells: &mut HashSet<Cell>) {
+        for agent in &mut self.agents {
+            let color = self.colors.get(&agent.cell).copied().unwrap_or(0);
+            let transition = self.table[agent.state as usize][color as usize];
+            if transition.write == 0 {
+                self.colors.remove(&agent.cell);
+                alive_cells.remove(&agent.cell);
+            } else {
+                self.colors.insert(agent.cell, transition.write);
+                alive_cells.insert(agent.cell);
+            }
+            agent.direction = transition.turn.apply(agent.direction);
+            agent.state = transition.next_state;
+            let (dx, dy) = match agent.direction {
+                0 => (0, -1),
+                1 => (1, 0),
+                2 => (0, 1),
+                _ => (-1, 0),
+            };
+            agent.cell = agent.cell.offset(dx, dy).unwrap_or(agent.cell);
+        }
+    }
+
+    /// Display color of a live cell, by the color painted on it.
+    pub fn color_of(&self, cell: Cell) -> (f32, f32, f32) {
+        let color = self.colors.get(&cell).copied().unwrap_or(1).max(1);
+        PALETTE[color as usize - 1]
+    }
+}
+
+fn parse_ant(spec: &str) -> Result<Vec<Vec<Transition>>, String> {
+    let turns = spec
+        .chars()
+        .map(|c| match c.to_ascii_uppercase() {
+            'L' => Ok(Turn::Left),
+            'R' => Ok(Turn::Right),
+            'N' => Ok(Turn::None),
+            'U' => Ok(Turn::Reverse),
+            _ => Err(format!("Invalid ant turn '{}'. Expected L, R, N or U.", c)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if !(2..=MAX_COLORS).contains(&turns.len()) {
+        return Err(format!(
+            "Invalid ant spec. Expected 2 to {} turns, one per color.",
+            MAX_COLORS
+        ));
+    }
+    let colors = turns.len() as u8;
+    let row = turns
+        .into_iter()
+        .enumerate()
+        .map(|(color, turn)| Transition {
+            write: (color as u8 + 1) % colors,
+            turn,
+            next_state: 0,
+        })
+        .collect();
+    Ok(vec![row])
+}
+
+fn parse_table(spec: &str) -> Result<Vec<Vec<Transition>>, String> {
+    const USAGE: &str = "Invalid turmite table. Expected e.g. '{{{1,2,0},{0,8,0}}}'.";
+    let mut table: Vec<Vec<Transition>> = Vec::new();
+    let mut numbers = Vec::new();
+    let mut number = String::new();
+    let mut depth = 0;
+    for c in spec.chars().filter(|c| !c.is_whitespace()) {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        if !number.is_empty() {
+            numbers.push(number.parse::<u8>().map_err(|_| USAGE.to_string())?);
+            number.clear();
+        }
+        match c {
+            '{' => {
+                depth += 1;
+                if depth == 2 {
+                    table.push(Vec::new());
+                }
+            }
+            '}' => {
+                if depth == 3 {
+                    let [write, turn, next_state] = numbers[..] else {
+                        return Err(USAGE.to_string());
+                    };
+                    let turn = match turn {
+                        1 => Turn::None,
+                        2 => Turn::Right,
+                        4 => Turn::Reverse,
+                        8 => Turn::Left,
+                        _ => return Err(format!("Invalid turmite turn {}.", turn)),
+                    };
+                    let row = table.last_mut().ok_or(USAGE)?;
+                    row.push(Transition {
+                        write,
+                        turn,
+                        next_state,
+                    });
+                    numbers.clear();
+                } else if !numbers.is_empty() {
+                    return Err(USAGE.to_string());
+                }
+                depth -= 1;
+            }
+            ',' => {}
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    let colors = table.first().map_or(0, Vec::len);
+    if depth != 0 || !(2..=MAX_COLORS).contains(&colors) {
+        return Err(USAGE.to_string());
+    }
+    for row in &table {
+        if row.len() != colors {
+            return Err("Every turmite state needs a transition for each color.".to_string());
+        }
+        for transition in row {
+            if transition.write as usize >= colors || transition.next_state as usize >= table.len()
+            {
+                return Err(
+                    "Turmite table writes a color or enters a state it doesn't define.".to_string(),
+                );
+            }
+        }
+    }
+    Ok(table)
+}
@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashSet;
+
+use crate::{topology::Torus, Cell};
+
+/// Most edits remembered; the oldest are forgotten first.
+const MAX_UNDO: usize = 500;
+
+/// A manual change to the grid, stored as the cells it brought to life and the ones it killed.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Edit {
+    pub added: Vec<Cell>,
+    pub removed: Vec<Cell>,
+}
+
+impl Edit {
+    /// The edit that sets every cell in `cells` to `alive`, leaving out cells already in that state.
+    pub fn set(
+        cells: impl IntoIterator<Item = Cell>,
+        alive: bool,
+        current: &HashSet<Cell>,
+    ) -> Self {
+        let changed = cells
+            .into_iter()
+            .filter(|cell| current.contains(cell) != alive)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        if alive {
+            Self {
+                added: changed,
+                removed: Vec::new(),
+            }
+        } else {
+            Self {
+                added: Vec::new(),
+                removed: changed,
+            }
+        }
+    }
+
+    /// This edit with its cells wrapped onto `torus`, leaving out any that land on a cell
+    /// already in the state they'd set, so undoing it takes back exactly what changed.
+    pub fn wrapped(self, torus: Torus, current: &HashSet<Cell>) -> Self {
+        let wrap = |cells: Vec<Cell>| cells.into_iter().map(move |cell| torus.wrap(cell));
+        Self {
+            added: Self::set(wrap(self.added), true, current).added,
+            removed: Self::set(wrap(self.removed), false, current).removed,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Adds a later edit's changes, so both are undone together.
+    pub fn extend(&mut self, later: Edit) {
+        self.added.extend(later.added);
+        self.removed.extend(later.removed);
+    }
+
+    pub fn apply(&self, cells: &mut HashSet<Cell>) {
+        for cell in &self.removed {
+            cells.remove(cell);
+        }
+        cells.extend(self.added.iter().copied());
+    }
+
+    /// The edit that takes this one back.
+    fn inverse(&self) -> Edit {
+        Edit {
+            added: self.removed.clone(),
+            removed: self.added.clone(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct UndoStack {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+}
+
+impl UndoStack {
+    /// Remembers an edit that has just been applied; a new edit discards anything undone.
+    pub fn record(&mut self, edit: Edit) {
+        if edit.is_empty() {
+            return;
+        }
+        self.undo.push(edit);
+        if self.undo.len() > MAX_UNDO {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Reverts the latest edit, returning the change made if there was one.
+    pub fn undo(&mut self, cells: &mut HashSet<Cell>) -> Option<Edit> {
+        let edit = self.undo.pop()?;
+        let change = edit.inverse();
+        change.apply(cells);
+        self.redo.push(edit);
+        Some(change)
+    }
+
+    /// Re-applies the latest undone edit, returning the change made if there was one.
+    pub fn redo(&mut self, cells: &mut HashSet<Cell>) -> Option<Edit> {
+        let edit = self.redo.pop()?;
+        edit.apply(cells);
+        let change = edit.clone();
+        self.undo.push(edit);
+        Some(change)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(list: &[(i32, i32)]) -> HashSet<Cell> {
+        list.iter().map(|&(x, y)| Cell(x, y)).collect()
+    }
+
+    #[test]
+    fn undo_and_redo_round_trip_an_edit() {
+        let before = cells(&[(0, 0), (1, 0)]);
+        let mut grid = before.clone();
+        let mut undo = UndoStack::default();
+
+        let mut edit = Edit::set([Cell(1, 0), Cell(2, 0)], true, &grid);
+        edit.extend(Edit::set([Cell(0, 0)], false, &grid));
+        edit.apply(&mut grid);
+        undo.record(edit);
+        let after = grid.clone();
+        assert_eq!(after, cells(&[(1, 0), (2, 0)]));
+
+        assert!(undo.undo(&mut grid).is_some());
+        assert_eq!(grid, before);
+        assert!(undo.undo(&mut grid).is_none());
+        assert!(undo.redo(&mut grid).is_some());
+        assert_eq!(grid, after);
+        assert!(undo.redo(&mut grid).is_none());
+    }
+
+    #[test]
+    fn wrapped_edits_undo_only_what_changed_on_the_torus() {
+        let torus = Torus {
+            width: 4,
+            height: 4,
+        };
+        let before = cells(&[(1, 1)]);
+        let mut grid = before.clone();
+        let mut undo = UndoStack::default();
+
+        // (5, 5) lands on the live (1, 1), so only (-1, 0) becomes a new cell, at (3, 0)
+        let edit = Edit::set([Cell(5, 5), Cell(-1, 0)], true, &grid).wrapped(torus, &grid);
+        assert_eq!(edit.added, vec![Cell(3, 0)]);
+        edit.apply(&mut grid);
+        undo.record(edit);
+
+        undo.undo(&mut grid);
+        assert_eq!(grid, before);
+        undo.redo(&mut grid);
+        assert_eq!(grid, cells(&[(1, 1), (3, 0)]));
+    }
+}
@@ -0,0 +1,39 @@
+use std::io::{self, Write};
+
+use crate::raster::Frame;
+
+/// Writes frames as an uncompressed YUV4MPEG2 (4:4:4) stream that ffmpeg and mpv read directly.
+pub struct Y4mWriter<W: Write> {
+    out: W,
+    planes: Vec<u8>,
+}
+
+impl<W: Write> Y4mWriter<W> {
+    pub fn new(mut out: W, width: u32, height: u32, fps: u32) -> io::Result<Self> {
+        writeln!(
+            out,
+            "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444",
+            width, height, fps
+        )?;
+        Ok(Self {
+            out,
+            planes: vec![0; (width * height * 3) as usize],
+        })
+    }
+
+    pub fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        let plane_len = (frame.width * frame.height) as usize;
+        let (y_plane, chroma) = self.planes.split_at_mut(plane_len);
+        let (u_plane, v_plane) = chroma.split_at_mut(plane_len);
+        for (i, rgba) in frame.pixels.chunks_exact(4).enumerate() {
+            let (r, g, b) = (rgba[0] as i32, rgba[1] as i32, rgba[2] as i32);
+            // BT.601 studio-swing conversion
+            y_plane[i] = (((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8;
+            u_plane[i] = (((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128) as u8;
+            v_plane[i] = (((112 * r - 94 * g - 18 * b + 128) >> 8) + 128) as u8;
+        }
+        self.out.write_all(b"FRAME\n")?;
+        self.out.write_all(&self.planes)?;
+        self.out.flush()
+    }
+}